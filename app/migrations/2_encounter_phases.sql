@@ -0,0 +1,6 @@
+ALTER TABLE encounter ADD COLUMN phases TEXT;
+
+ALTER TABLE encounter ADD COLUMN parent_id INTEGER REFERENCES encounter (id) ON DELETE SET NULL;
+
+CREATE INDEX encounter_parent_id_index
+        ON encounter (parent_id);
@@ -0,0 +1,20 @@
+CREATE TABLE raid_run (
+        id INTEGER PRIMARY KEY,
+        raid TEXT NOT NULL,
+        difficulty TEXT,
+        players TEXT NOT NULL,
+        started_on INTEGER NOT NULL,
+        ended_on INTEGER NOT NULL
+    );
+
+CREATE INDEX raid_run_started_on_index
+        ON raid_run (started_on desc);
+
+ALTER TABLE encounter ADD COLUMN raid_run_id INTEGER REFERENCES raid_run (id) ON DELETE SET NULL;
+
+ALTER TABLE encounter ADD COLUMN gate TEXT;
+
+CREATE INDEX encounter_raid_run_id_index
+        ON encounter (raid_run_id);
+
+ALTER TABLE entity ADD COLUMN damage_dealt INTEGER;
@@ -0,0 +1,5 @@
+ALTER TABLE encounter ADD COLUMN attempt INTEGER;
+
+ALTER TABLE encounter ADD COLUMN boss_hp REAL;
+
+ALTER TABLE encounter ADD COLUMN wipe TEXT;
@@ -0,0 +1 @@
+ALTER TABLE entity ADD COLUMN spec_confidence REAL;
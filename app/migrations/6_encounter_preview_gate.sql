@@ -0,0 +1,4 @@
+ALTER TABLE encounter_preview ADD COLUMN gate TEXT;
+
+UPDATE encounter_preview
+SET gate = (SELECT gate FROM encounter WHERE encounter.id = encounter_preview.id);
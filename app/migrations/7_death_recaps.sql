@@ -0,0 +1 @@
+ALTER TABLE encounter ADD COLUMN death_recaps TEXT;
@@ -0,0 +1,175 @@
+use std::{fs::{self, File}, io::{BufReader, BufWriter, ErrorKind, Read, Write}, path::{Path, PathBuf}, time::{Duration, Instant}};
+
+use anyhow::{bail, Result};
+use chrono::Utc;
+
+/// Capture layout
+///
+/// header: magic (4 bytes) | version (u16) | started at, unix ms (i64)
+/// frame:  elapsed since start, µs (u64) | opcode (u16) | length (u32) | payload
+///
+/// All integers are little endian.
+pub const CAPTURE_MAGIC: &[u8; 4] = b"SYPC";
+pub const CAPTURE_VERSION: u16 = 1;
+pub const CAPTURE_EXTENSION: &str = "sypc";
+pub const CAPTURE_HEADER_SIZE: usize = 4 + 2 + 8;
+pub const CAPTURE_FRAME_HEADER_SIZE: usize = 8 + 2 + 4;
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct CaptureWriter {
+    path: PathBuf,
+    writer: BufWriter<File>,
+    started: Instant,
+    last_flush: Instant,
+    frames: u64,
+}
+
+impl CaptureWriter {
+    pub fn create(path: PathBuf) -> Result<Self> {
+        let file = File::create(&path)?;
+        let mut writer = BufWriter::new(file);
+
+        writer.write_all(CAPTURE_MAGIC)?;
+        writer.write_all(&CAPTURE_VERSION.to_le_bytes())?;
+        writer.write_all(&Utc::now().timestamp_millis().to_le_bytes())?;
+
+        let now = Instant::now();
+
+        Ok(Self {
+            path,
+            writer,
+            started: now,
+            last_flush: now,
+            frames: 0
+        })
+    }
+
+    pub fn write(&mut self, opcode: u16, data: &[u8]) -> Result<()> {
+        let elapsed = self.started.elapsed().as_micros() as u64;
+
+        self.writer.write_all(&elapsed.to_le_bytes())?;
+        self.writer.write_all(&opcode.to_le_bytes())?;
+        self.writer.write_all(&(data.len() as u32).to_le_bytes())?;
+        self.writer.write_all(data)?;
+        self.frames += 1;
+
+        // keep the file usable if the app goes down mid-raid
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        self.last_flush = Instant::now();
+
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+}
+
+impl Drop for CaptureWriter {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+pub struct CaptureFrame {
+    pub elapsed: Duration,
+    pub opcode: u16,
+    pub data: Vec<u8>,
+}
+
+pub struct CaptureReader {
+    reader: BufReader<File>,
+    started_at: i64,
+}
+
+impl CaptureReader {
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = File::open(path)?;
+        let mut reader = BufReader::new(file);
+
+        let mut header = [0u8; CAPTURE_HEADER_SIZE];
+        reader.read_exact(&mut header)?;
+
+        if header[0..4] != CAPTURE_MAGIC[..] {
+            bail!("{} is not a packet capture", path.display());
+        }
+
+        let version = u16::from_le_bytes([header[4], header[5]]);
+
+        if version != CAPTURE_VERSION {
+            bail!("unsupported capture version {} in {}", version, path.display());
+        }
+
+        let started_at = i64::from_le_bytes(header[6..14].try_into()?);
+
+        Ok(Self {
+            reader,
+            started_at
+        })
+    }
+
+    /// Unix timestamp in milliseconds at which the capture was started.
+    pub fn started_at(&self) -> i64 {
+        self.started_at
+    }
+
+    /// Returns `None` at the end of the capture. A truncated trailing frame,
+    /// which happens when the app is killed while recording, is treated as the end.
+    pub fn next_frame(&mut self) -> Result<Option<CaptureFrame>> {
+        let mut header = [0u8; CAPTURE_FRAME_HEADER_SIZE];
+
+        match self.reader.read_exact(&mut header) {
+            Ok(_) => {},
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+
+        let elapsed = u64::from_le_bytes(header[0..8].try_into()?);
+        let opcode = u16::from_le_bytes([header[8], header[9]]);
+        let length = u32::from_le_bytes(header[10..14].try_into()?) as usize;
+
+        let mut data = vec![0u8; length];
+
+        match self.reader.read_exact(&mut data) {
+            Ok(_) => {},
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            Err(err) => return Err(err.into()),
+        }
+
+        Ok(Some(CaptureFrame {
+            elapsed: Duration::from_micros(elapsed),
+            opcode,
+            data
+        }))
+    }
+}
+
+/// Resolves a capture path to the list of files to play back.
+/// A directory yields all captures inside it, ordered by name which is also recording order.
+pub fn list_captures(path: &Path) -> Result<Vec<PathBuf>> {
+    if !path.is_dir() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut captures: Vec<PathBuf> = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == CAPTURE_EXTENSION))
+        .collect();
+
+    captures.sort();
+
+    Ok(captures)
+}
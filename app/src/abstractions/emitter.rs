@@ -0,0 +1,82 @@
+use std::sync::Mutex;
+
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+/// Sink for the events produced by the live pipeline.
+pub trait EventEmitter: Send + Sync {
+    fn emit_value(&self, event: &str, payload: Value) -> Result<()>;
+
+    /// Delivers the event only to the window with the given label.
+    fn emit_value_to(&self, _target: &str, event: &str, payload: Value) -> Result<()> {
+        self.emit_value(event, payload)
+    }
+}
+
+impl dyn EventEmitter {
+    pub fn emit<S: Serialize>(&self, event: &str, payload: S) -> Result<()> {
+        self.emit_value(event, serde_json::to_value(payload)?)
+    }
+
+    pub fn emit_to<S: Serialize>(&self, target: &str, event: &str, payload: S) -> Result<()> {
+        self.emit_value_to(target, event, serde_json::to_value(payload)?)
+    }
+}
+
+pub struct TauriEventEmitter(AppHandle);
+
+impl EventEmitter for TauriEventEmitter {
+    fn emit_value(&self, event: &str, payload: Value) -> Result<()> {
+        self.0.emit(event, payload)?;
+
+        Ok(())
+    }
+
+    fn emit_value_to(&self, target: &str, event: &str, payload: Value) -> Result<()> {
+        self.0.emit_to(target, event, payload)?;
+
+        Ok(())
+    }
+}
+
+impl TauriEventEmitter {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self(app_handle)
+    }
+}
+
+/// Keeps every emitted event in memory, used when running without a window system.
+#[derive(Default)]
+pub struct MemoryEventEmitter(Mutex<Vec<(String, Value)>>);
+
+impl EventEmitter for MemoryEventEmitter {
+    fn emit_value(&self, event: &str, payload: Value) -> Result<()> {
+        self.0.lock().unwrap().push((event.to_string(), payload));
+
+        Ok(())
+    }
+}
+
+impl MemoryEventEmitter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn events(&self) -> Vec<(String, Value)> {
+        self.0.lock().unwrap().clone()
+    }
+
+    pub fn last(&self, event: &str) -> Option<Value> {
+        self.0.lock().unwrap()
+            .iter()
+            .rev()
+            .find(|(name, _)| name == event)
+            .map(|(_, payload)| payload.clone())
+    }
+
+    pub fn take(&self) -> Vec<(String, Value)> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
@@ -1,16 +1,38 @@
 #![allow(dead_code)]
 
-use std::{sync::mpsc::{self, Receiver}, thread::{self}};
+use std::{path::{Path, PathBuf}, sync::mpsc::{self, Receiver, SyncSender}, thread::{self}, time::Instant};
+use log::*;
 use meter_core_fake::packets::structures::SkillDamageEvent;
 use meter_core_fake::packets::opcodes::Pkt;
 
 pub use meter_core_fake::*;
 
-use crate::abstractions::{DamageEncryptionHandler, PacketSource, PacketReceiver};
-use anyhow::Result;
+use crate::abstractions::{capture::{list_captures, CaptureReader}, DamageEncryptionHandler, PacketSource, PacketReceiver, ScriptedPacket};
+use anyhow::{bail, Result};
 
 pub struct DefaultDamageEncryptionHandler;
-pub struct FakePacketSource;
+
+/// Emits a fixed list of packets, see [`crate::abstractions::ScriptedRaidBuilder`].
+pub struct FakePacketSource {
+    packets: Vec<ScriptedPacket>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaySpeed {
+    /// Keeps the recorded inter-packet timing.
+    Original,
+    /// Divides the recorded inter-packet timing by the given factor.
+    Accelerated(f64),
+    /// Emits packets as fast as the consumer reads them.
+    Unbounded,
+}
+
+/// Plays back captures written by [`crate::abstractions::RecordingPacketSource`].
+/// `path` can point to a single capture or to a directory of captures.
+pub struct ReplayPacketSource {
+    path: PathBuf,
+    speed: ReplaySpeed,
+}
 
 pub struct FakeReceiver {
     inner: Receiver<(Pkt, Vec<u8>)>,
@@ -26,11 +48,17 @@ impl PacketSource<FakeReceiver> for FakePacketSource {
     fn start(&self, _port: u16) -> Result<FakeReceiver> {
 
         let (emitter, rx) = mpsc::channel();
+        let packets = self.packets.clone();
 
         let builder = thread::Builder::new()
             .name("fake-sniffer".to_string());
 
         let handle = builder.spawn(move || {
+            for packet in packets {
+                thread::sleep(packet.delay);
+                emitter.send((packet.op, packet.data))?;
+            }
+
             anyhow::Ok::<()>(())
         })?;
 
@@ -38,6 +66,86 @@ impl PacketSource<FakeReceiver> for FakePacketSource {
     }
 }
 
+impl PacketSource<FakeReceiver> for ReplayPacketSource {
+    fn start(&self, _port: u16) -> Result<FakeReceiver> {
+
+        let captures = list_captures(&self.path)?;
+        let speed = self.speed;
+
+        if let ReplaySpeed::Accelerated(factor) = speed
+            && !(factor.is_finite() && factor > 0.0) {
+            bail!("invalid replay speed {}", factor);
+        }
+
+        if captures.is_empty() {
+            bail!("no captures found in {}", self.path.display());
+        }
+        let (emitter, rx) = mpsc::sync_channel(1024);
+
+        // validate up front so that a bad path fails start rather than the replay thread
+        for capture in captures.iter() {
+            CaptureReader::open(capture)?;
+        }
+
+        let builder = thread::Builder::new()
+            .name("replay-sniffer".to_string());
+
+        builder.spawn(move || {
+            for capture in captures {
+                info!("replaying {}", capture.display());
+
+                if let Err(err) = Self::replay(&capture, speed, &emitter) {
+                    warn!("replay of {} stopped: {:?}", capture.display(), err);
+                    break;
+                }
+            }
+
+            info!("replay finished");
+        })?;
+
+        Ok(FakeReceiver { inner: rx })
+    }
+}
+
+impl ReplayPacketSource {
+    pub fn new(path: PathBuf, speed: ReplaySpeed) -> Self {
+        Self {
+            path,
+            speed
+        }
+    }
+
+    fn replay(path: &Path, speed: ReplaySpeed, emitter: &SyncSender<(Pkt, Vec<u8>)>) -> Result<()> {
+        let mut reader = CaptureReader::open(path)?;
+        let started = Instant::now();
+
+        while let Some(frame) = reader.next_frame()? {
+            let Ok(op) = Pkt::try_from(frame.opcode) else {
+                warn!("skipping unknown opcode {}", frame.opcode);
+                continue;
+            };
+
+            let due = match speed {
+                ReplaySpeed::Original => Some(frame.elapsed),
+                ReplaySpeed::Accelerated(factor) => Some(frame.elapsed.div_f64(factor)),
+                ReplaySpeed::Unbounded => None,
+            };
+
+            if let Some(due) = due {
+                let elapsed = started.elapsed();
+
+                if due > elapsed {
+                    thread::sleep(due - elapsed);
+                }
+            }
+
+            emitter.send((op, frame.data))?;
+        }
+
+        Ok(())
+    }
+}
+
 impl DamageEncryptionHandler for DefaultDamageEncryptionHandler {
     fn start(&mut self) -> Result<()> {
         
@@ -56,7 +164,11 @@ impl DamageEncryptionHandler for DefaultDamageEncryptionHandler {
 
 impl FakePacketSource {
     pub fn new() -> Self {
-        Self
+        Self { packets: Vec::new() }
+    }
+
+    pub fn from_script(packets: Vec<ScriptedPacket>) -> Self {
+        Self { packets }
     }
 }
 
@@ -3,9 +3,14 @@
 
 pub mod traits;
 pub mod region;
+pub mod capture;
+pub mod recorder;
+pub mod emitter;
 
 pub use traits::*;
 pub use region::*;
+pub use recorder::*;
+pub use emitter::*;
 
 #[cfg(feature = "meter-core")]
 pub mod snow_meter;
@@ -19,6 +24,12 @@ pub mod fake_meter;
 #[cfg(feature = "meter-core-fake")]
 pub use fake_meter::*;
 
+#[cfg(feature = "meter-core-fake")]
+pub mod scripted;
+
+#[cfg(feature = "meter-core-fake")]
+pub use scripted::*;
+
 #[cfg(feature = "meter-core")]
 pub use snow_meter::packets::opcodes::Pkt;
 
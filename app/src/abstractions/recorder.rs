@@ -0,0 +1,118 @@
+use std::{fs, marker::PhantomData, path::PathBuf};
+
+use anyhow::Result;
+use chrono::Local;
+use log::*;
+
+use crate::abstractions::{capture::{CaptureWriter, CAPTURE_EXTENSION}, PacketReceiver, PacketSource, Pkt};
+
+/// Wraps any packet source and tees every received packet into a capture file.
+/// A new file is started on each `InitEnv` so that a capture maps to a single zone.
+/// Files are created on the first packet after a rotation, so no capture is ever empty.
+/// Without a directory the source is a plain pass-through.
+pub struct RecordingPacketSource<PR: PacketReceiver, PC: PacketSource<PR>> {
+    inner: PC,
+    directory: Option<PathBuf>,
+    _marker: PhantomData<PR>,
+}
+
+pub struct RecordingReceiver<PR: PacketReceiver> {
+    inner: PR,
+    directory: Option<PathBuf>,
+    writer: Option<CaptureWriter>,
+    pending: bool,
+    sequence: u32,
+}
+
+impl<PR: PacketReceiver, PC: PacketSource<PR>> RecordingPacketSource<PR, PC> {
+    pub fn new(inner: PC, directory: Option<PathBuf>) -> Self {
+        Self {
+            inner,
+            directory,
+            _marker: PhantomData
+        }
+    }
+}
+
+impl<PR: PacketReceiver, PC: PacketSource<PR>> PacketSource<RecordingReceiver<PR>> for RecordingPacketSource<PR, PC> {
+    fn start(&self, port: u16) -> Result<RecordingReceiver<PR>> {
+        if let Some(directory) = self.directory.as_ref() {
+            fs::create_dir_all(directory)?;
+        }
+
+        let inner = self.inner.start(port)?;
+        let receiver = RecordingReceiver {
+            inner,
+            directory: self.directory.clone(),
+            writer: None,
+            pending: self.directory.is_some(),
+            sequence: 0,
+        };
+
+        Ok(receiver)
+    }
+}
+
+impl<PR: PacketReceiver> PacketReceiver for RecordingReceiver<PR> {
+    fn recv(&mut self) -> Result<(Pkt, Vec<u8>)> {
+        let (op, data) = self.inner.recv()?;
+
+        if matches!(op, Pkt::InitEnv) {
+            self.rotate();
+        }
+
+        if self.pending {
+            self.open();
+        }
+
+        if let Some(writer) = self.writer.as_mut()
+            && let Err(err) = writer.write(op as u16, &data) {
+            warn!("failed to write to capture {}: {:?}", writer.path().display(), err);
+            self.writer = None;
+        }
+
+        Ok((op, data))
+    }
+}
+
+impl<PR: PacketReceiver> RecordingReceiver<PR> {
+    /// Closes the current capture, if any, the next packet opens a new one.
+    fn rotate(&mut self) {
+        if self.directory.is_none() {
+            return;
+        }
+
+        if let Some(writer) = self.writer.take() {
+            info!("closed capture {} with {} packets", writer.path().display(), writer.frames());
+        }
+
+        self.pending = true;
+    }
+
+    /// Recording failures are logged and never interrupt packet processing.
+    fn open(&mut self) {
+        self.pending = false;
+
+        let Some(directory) = self.directory.as_ref() else {
+            return;
+        };
+
+        let file_name = format!(
+            "capture_{}_{:03}.{}",
+            Local::now().format("%Y%m%d_%H%M%S"),
+            self.sequence,
+            CAPTURE_EXTENSION);
+        let path = directory.join(file_name);
+        self.sequence += 1;
+
+        match CaptureWriter::create(path.clone()) {
+            Ok(writer) => {
+                info!("recording packets to {}", path.display());
+                self.writer = Some(writer);
+            },
+            Err(err) => {
+                warn!("could not create capture {}: {:?}", path.display(), err);
+            }
+        }
+    }
+}
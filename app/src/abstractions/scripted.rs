@@ -0,0 +1,517 @@
+use std::time::Duration;
+
+use meter_core_fake::packets::definitions::*;
+use meter_core_fake::packets::opcodes::Pkt;
+use meter_core_fake::packets::structures::*;
+use serde::Serialize;
+
+const STAT_HP: u8 = 1;
+const STAT_MAX_HP: u8 = 27;
+const CRITICAL_MODIFIER: u8 = 1;
+const CLEAR_SIGNAL: u32 = 57;
+const WIPE_SIGNAL: u32 = 58;
+
+/// A packet together with the time to wait before it is emitted.
+#[derive(Debug, Clone)]
+pub struct ScriptedPacket {
+    pub delay: Duration,
+    pub op: Pkt,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptedPlayer {
+    pub entity_id: u64,
+    pub character_id: u64,
+    pub name: String,
+    pub class_id: u16,
+    pub gear_level: f32,
+    pub hp: i64,
+    pub skills: Vec<u32>,
+    pub crit_rate: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptedNpc {
+    pub entity_id: u64,
+    pub npc_id: u32,
+    pub level: u16,
+    pub hp: i64,
+}
+
+/// Low level writer which turns packet structs into the raw form returned by a `PacketReceiver`.
+#[derive(Default)]
+pub struct PacketScript {
+    packets: Vec<ScriptedPacket>,
+    pending_delay: Duration,
+}
+
+impl PacketScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn wait(&mut self, delay: Duration) -> &mut Self {
+        self.pending_delay += delay;
+        self
+    }
+
+    pub fn push<T: Serialize>(&mut self, op: Pkt, pkt: &T) -> &mut Self {
+        let data = encode(pkt);
+        self.push_raw(op, data)
+    }
+
+    pub fn push_raw(&mut self, op: Pkt, data: Vec<u8>) -> &mut Self {
+        let delay = std::mem::take(&mut self.pending_delay);
+        self.packets.push(ScriptedPacket { delay, op, data });
+        self
+    }
+
+    pub fn init_env(&mut self, player_id: u64) -> &mut Self {
+        self.push(Pkt::InitEnv, &PKTInitEnv {
+            player_id,
+            ..Default::default()
+        })
+    }
+
+    pub fn init_pc(&mut self, player: &ScriptedPlayer) -> &mut Self {
+        self.push(Pkt::InitPC, &PKTInitPC {
+            player_id: player.entity_id,
+            character_id: player.character_id,
+            name: player.name.clone(),
+            class_id: player.class_id as _,
+            gear_level: player.gear_level as _,
+            stat_pairs: stat_pairs(player.hp, player.hp),
+            ..Default::default()
+        })
+    }
+
+    pub fn new_pc(&mut self, player: &ScriptedPlayer) -> &mut Self {
+        self.push(Pkt::NewPC, &PKTNewPC {
+            pc_struct: PCStruct {
+                player_id: player.entity_id,
+                character_id: player.character_id,
+                name: player.name.clone(),
+                class_id: player.class_id as _,
+                max_item_level: player.gear_level as _,
+                stat_pairs: stat_pairs(player.hp, player.hp),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    }
+
+    pub fn party_info(&mut self, raid_instance_id: u32, party_instance_id: u32, members: &[ScriptedPlayer]) -> &mut Self {
+        let party_member_datas = members
+            .iter()
+            .map(|member| PartyMemberData {
+                name: member.name.clone(),
+                character_id: member.character_id,
+                class_id: member.class_id as _,
+                gear_level: member.gear_level as _,
+                ..Default::default()
+            })
+            .collect();
+
+        self.push(Pkt::PartyInfo, &PKTPartyInfo {
+            raid_instance_id: raid_instance_id as _,
+            party_instance_id: party_instance_id as _,
+            party_member_datas,
+            ..Default::default()
+        })
+    }
+
+    pub fn zone_member_load_status(&mut self, zone_id: u32, zone_level: u32) -> &mut Self {
+        self.push(Pkt::ZoneMemberLoadStatusNotify, &PKTZoneMemberLoadStatusNotify {
+            zone_id: zone_id as _,
+            zone_level: zone_level as _,
+            ..Default::default()
+        })
+    }
+
+    pub fn new_npc(&mut self, npc: &ScriptedNpc, hp: i64) -> &mut Self {
+        self.push(Pkt::NewNpc, &PKTNewNpc {
+            npc_struct: NpcStruct {
+                object_id: npc.entity_id,
+                type_id: npc.npc_id,
+                level: npc.level as _,
+                stat_pairs: stat_pairs(hp, npc.hp),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+    }
+
+    pub fn skill_start(&mut self, source_id: u64, skill_id: u32) -> &mut Self {
+        self.push(Pkt::SkillStartNotify, &PKTSkillStartNotify {
+            source_id,
+            skill_id,
+            ..Default::default()
+        })
+    }
+
+    pub fn skill_damage(&mut self, source_id: u64, skill_id: u32, target: &ScriptedNpc, damage: i64, current_hp: i64, crit: bool) -> &mut Self {
+        let event = SkillDamageEvent {
+            target_id: target.entity_id,
+            damage,
+            modifier: if crit { CRITICAL_MODIFIER as _ } else { 0 },
+            cur_hp: current_hp,
+            max_hp: target.hp,
+            ..Default::default()
+        };
+
+        self.push(Pkt::SkillDamageNotify, &PKTSkillDamageNotify {
+            source_id,
+            skill_id,
+            skill_damage_events: vec![event],
+            ..Default::default()
+        })
+    }
+
+    /// Adds a buff or, when `value` is set, a shield to the target.
+    pub fn status_effect_add(&mut self, target_id: u64, source_id: u64, status_effect_id: u32, instance_id: u32, value: u64, duration: f32) -> &mut Self {
+        let mut status_effect_data = StatusEffectData {
+            source_id,
+            status_effect_id,
+            status_effect_instance_id: instance_id as _,
+            total_time: duration as _,
+            stack_count: 1,
+            ..Default::default()
+        };
+
+        if value > 0 {
+            let mut bytes = value.to_le_bytes().to_vec();
+            bytes.extend_from_slice(&value.to_le_bytes());
+            status_effect_data.value.bytearray_0 = Some(bytes);
+        }
+
+        self.push(Pkt::StatusEffectAddNotify, &PKTStatusEffectAddNotify {
+            object_id: target_id,
+            status_effect_data,
+            ..Default::default()
+        })
+    }
+
+    pub fn status_effect_remove(&mut self, target_id: u64, instance_id: u32) -> &mut Self {
+        self.push(Pkt::StatusEffectRemoveNotify, &PKTStatusEffectRemoveNotify {
+            object_id: target_id,
+            status_effect_instance_ids: vec![instance_id as _],
+            ..Default::default()
+        })
+    }
+
+    pub fn death(&mut self, target_id: u64) -> &mut Self {
+        self.push(Pkt::DeathNotify, &PKTDeathNotify {
+            target_id,
+            ..Default::default()
+        })
+    }
+
+    pub fn trigger_start(&mut self, signal: u32) -> &mut Self {
+        self.push(Pkt::TriggerStartNotify, &PKTTriggerStartNotify {
+            signal: signal as _,
+            ..Default::default()
+        })
+    }
+
+    pub fn raid_boss_kill(&mut self) -> &mut Self {
+        self.push_raw(Pkt::RaidBossKillNotify, Vec::new())
+    }
+
+    pub fn raid_result(&mut self) -> &mut Self {
+        self.push_raw(Pkt::RaidResult, Vec::new())
+    }
+
+    pub fn build(self) -> Vec<ScriptedPacket> {
+        self.packets
+    }
+}
+
+/// Boss phase, the boss is replaced by the next phase npc once its hp reaches zero.
+#[derive(Debug, Clone)]
+pub struct ScriptedPhase {
+    pub npc: ScriptedNpc,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptedDeath {
+    pub player: usize,
+    pub at: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScriptedShield {
+    pub source: usize,
+    pub target: usize,
+    pub status_effect_id: u32,
+    pub value: u64,
+    pub at: Duration,
+}
+
+/// Builds a complete raid: local player setup, parties, boss phases and the clear or wipe.
+/// Damage is spread evenly over the fight so that every phase dies at its scheduled time.
+pub struct ScriptedRaidBuilder {
+    parties: Vec<Vec<ScriptedPlayer>>,
+    phases: Vec<ScriptedPhase>,
+    deaths: Vec<ScriptedDeath>,
+    shields: Vec<ScriptedShield>,
+    duration: Duration,
+    tick: Duration,
+    zone_id: u32,
+    zone_level: u32,
+    cleared: bool,
+    seed: u64,
+}
+
+impl ScriptedRaidBuilder {
+    pub fn new() -> Self {
+        Self {
+            parties: Vec::new(),
+            phases: Vec::new(),
+            deaths: Vec::new(),
+            shields: Vec::new(),
+            duration: Duration::from_secs(60),
+            tick: Duration::from_secs(1),
+            zone_id: 0,
+            zone_level: 1,
+            cleared: true,
+            seed: 1,
+        }
+    }
+
+    /// Creates a party from `(name, class_id)` pairs, the first player of the first party is the local player.
+    pub fn add_party(mut self, members: &[(&str, u16)]) -> Self {
+        let offset = self.parties.iter().map(|party| party.len()).sum::<usize>() as u64;
+
+        let party = members
+            .iter()
+            .enumerate()
+            .map(|(index, (name, class_id))| {
+                let index = offset + index as u64;
+                ScriptedPlayer {
+                    entity_id: 1000 + index,
+                    character_id: 5000 + index,
+                    name: name.to_string(),
+                    class_id: *class_id,
+                    gear_level: 1680.0,
+                    hp: 500_000,
+                    skills: default_skills(*class_id),
+                    crit_rate: 0.5,
+                }
+            })
+            .collect();
+
+        self.parties.push(party);
+        self
+    }
+
+    pub fn add_phase(mut self, npc_id: u32, hp: i64) -> Self {
+        let entity_id = 9000 + self.phases.len() as u64;
+        self.phases.push(ScriptedPhase {
+            npc: ScriptedNpc {
+                entity_id,
+                npc_id,
+                level: 60,
+                hp,
+            }
+        });
+        self
+    }
+
+    pub fn add_death(mut self, player: usize, at: Duration) -> Self {
+        self.deaths.push(ScriptedDeath { player, at });
+        self
+    }
+
+    pub fn add_shield(mut self, source: usize, target: usize, status_effect_id: u32, value: u64, at: Duration) -> Self {
+        self.shields.push(ScriptedShield { source, target, status_effect_id, value, at });
+        self
+    }
+
+    pub fn set_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn set_tick(mut self, tick: Duration) -> Self {
+        self.tick = tick;
+        self
+    }
+
+    pub fn set_zone(mut self, zone_id: u32, zone_level: u32) -> Self {
+        self.zone_id = zone_id;
+        self.zone_level = zone_level;
+        self
+    }
+
+    /// Without a clear the last boss is left at 1 hp and the raid ends on a wipe.
+    pub fn set_cleared(mut self, cleared: bool) -> Self {
+        self.cleared = cleared;
+        self
+    }
+
+    /// Seed for the crit rolls, the same seed always produces the same packets.
+    pub fn set_seed(mut self, seed: u64) -> Self {
+        self.seed = seed.max(1);
+        self
+    }
+
+    pub fn players(&self) -> Vec<&ScriptedPlayer> {
+        self.parties.iter().flatten().collect()
+    }
+
+    pub fn build(self) -> Vec<ScriptedPacket> {
+        let mut script = PacketScript::new();
+        let players: Vec<ScriptedPlayer> = self.parties.iter().flatten().cloned().collect();
+        let mut rng = self.seed;
+
+        let Some(local) = players.first() else {
+            return script.build();
+        };
+
+        script.init_env(local.entity_id);
+        script.init_pc(local);
+
+        for player in players.iter().skip(1) {
+            script.new_pc(player);
+        }
+
+        for (index, party) in self.parties.iter().enumerate() {
+            script.party_info(1, index as u32 + 1, party);
+        }
+
+        script.zone_member_load_status(self.zone_id, self.zone_level);
+
+        let ticks = (self.duration.as_millis() / self.tick.as_millis().max(1)).max(1) as usize;
+        let ticks_per_phase = (ticks / self.phases.len().max(1)).max(1);
+        let mut deaths = self.deaths.clone();
+        let mut shields = self.shields.clone();
+        let mut dead: Vec<bool> = vec![false; players.len()];
+        let mut instance_id = 1;
+        let mut phases_cleared = 0;
+
+        for (phase_index, phase) in self.phases.iter().enumerate() {
+            let npc = &phase.npc;
+            let mut hp = npc.hp;
+            // hp the boss is left with at the end of the phase
+            let floor = if !self.cleared && phase_index + 1 == self.phases.len() { 1 } else { 0 };
+            script.new_npc(npc, hp);
+
+            for tick in 0..ticks_per_phase {
+                let elapsed = self.tick * (phase_index * ticks_per_phase + tick) as u32;
+                script.wait(self.tick);
+
+                for death in deaths.extract_if(.., |death| death.at <= elapsed) {
+                    if let Some(player) = players.get(death.player) {
+                        dead[death.player] = true;
+                        script.death(player.entity_id);
+                    }
+                }
+
+                for shield in shields.extract_if(.., |shield| shield.at <= elapsed) {
+                    if let (Some(source), Some(target)) = (players.get(shield.source), players.get(shield.target)) {
+                        script.status_effect_add(target.entity_id, source.entity_id, shield.status_effect_id, instance_id, shield.value, 6.0);
+                        instance_id += 1;
+                    }
+                }
+
+                let alive: Vec<&ScriptedPlayer> = players
+                    .iter()
+                    .zip(dead.iter())
+                    .filter(|(_, dead)| !**dead)
+                    .map(|(player, _)| player)
+                    .collect();
+
+                if alive.is_empty() {
+                    break;
+                }
+
+                let remaining_ticks = (ticks_per_phase - tick) as i64;
+                let tick_damage = (hp - floor) / remaining_ticks;
+                let per_player = (tick_damage / alive.len() as i64).max(1);
+
+                for player in alive {
+                    let damage = per_player.min(hp - floor);
+                    if damage <= 0 {
+                        break;
+                    }
+
+                    let skill_id = player.skills[tick % player.skills.len()];
+                    let crit = next_random(&mut rng) < player.crit_rate;
+                    hp -= damage;
+
+                    script.skill_start(player.entity_id, skill_id);
+                    script.skill_damage(player.entity_id, skill_id, npc, damage, hp, crit);
+                }
+
+                if hp <= 0 {
+                    break;
+                }
+            }
+
+            if hp > 0 {
+                break;
+            }
+
+            script.death(npc.entity_id);
+            phases_cleared += 1;
+        }
+
+        // the clear follows the outcome, e.g. a party which died out before the kill wipes
+        if !self.phases.is_empty() && phases_cleared == self.phases.len() {
+            script.raid_boss_kill();
+            script.trigger_start(CLEAR_SIGNAL);
+            script.raid_result();
+        } else {
+            script.trigger_start(WIPE_SIGNAL);
+        }
+
+        script.build()
+    }
+}
+
+impl Default for ScriptedRaidBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ScriptedRaidBuilder {
+    /// Two full parties against a two phase boss, one death and a support shield.
+    pub fn demo() -> Self {
+        Self::new()
+            .add_party(&[("Berserker", 102), ("Sharpshooter", 502), ("Wardancer", 302), ("Bard", 204)])
+            .add_party(&[("Aeromancer", 603), ("Artillerist", 504), ("Deathblade", 402), ("Paladin", 105)])
+            .add_phase(485800, 5_000_000_000)
+            .add_phase(485801, 7_500_000_000)
+            .add_death(2, Duration::from_secs(40))
+            .add_shield(3, 0, 211606, 100_000, Duration::from_secs(10))
+            .set_duration(Duration::from_secs(120))
+            .set_zone(37543, 1)
+    }
+}
+
+fn stat_pairs(hp: i64, max_hp: i64) -> Vec<StatPair> {
+    vec![
+        StatPair { stat_type: STAT_HP as _, value: hp },
+        StatPair { stat_type: STAT_MAX_HP as _, value: max_hp },
+    ]
+}
+
+fn default_skills(class_id: u16) -> Vec<u32> {
+    let base = class_id as u32 * 100;
+    (1..=8).map(|index| base * 100 + index * 10).collect()
+}
+
+/// xorshift, good enough to make crits look random while staying reproducible
+fn next_random(state: &mut u64) -> f64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    (*state % 10_000) as f64 / 10_000.0
+}
+
+fn encode<T: Serialize>(pkt: &T) -> Vec<u8> {
+    bincode::serde::encode_to_vec(pkt, bincode::config::standard())
+        .expect("could not encode scripted packet")
+}
@@ -1,11 +1,11 @@
 #![allow(dead_code)]
 
 use anyhow::Result;
-use std::{path::PathBuf, thread::JoinHandle};
+use std::{path::PathBuf, sync::Arc, thread::JoinHandle};
 use log::*;
 use tauri::{AppHandle, Manager};
 
-use crate::{data::AssetPreloader, settings::Settings, updater::UpdateManager};
+use crate::{abstractions::{EventEmitter, TauriEventEmitter}, data::AssetPreloader, database::Repository, live::AppListener, local::LocalPlayerRepository, settings::Settings, updater::UpdateManager};
 
 macro_rules! background_worker {
     ($name:expr, $args:expr, $body:expr) => {
@@ -22,6 +22,7 @@ pub struct BackgroundWorkerArgs {
     pub app_handle: AppHandle,
     pub port: u16,
     pub region_file_path: PathBuf,
+    pub recordings_path: PathBuf,
     pub settings: Settings,
     pub version: String
 }
@@ -47,42 +48,51 @@ impl BackgroundWorker {
             app_handle,
             port,
             region_file_path,
+            recordings_path,
             settings,
             version
         } = args;
 
+        let recordings_path = settings.general.record_packets.then_some(recordings_path);
+
         let asset_preloader = app_handle.state::<AssetPreloader>();
         info!("waiting for assets to load");
-        asset_preloader.wait().unwrap();
+        asset_preloader.wait()?;
 
         info!("waiting for update manager");
         let update_manager = app_handle.state::<UpdateManager>();
         update_manager.wait().await.unwrap();
 
         info!("listening on port: {}", port);
-        
+
+        let emitter: Arc<dyn EventEmitter> = Arc::new(TauriEventEmitter::new(app_handle.clone()));
+        let listener = AppListener::new(settings.general.boss_only_damage);
+        listener.listen(&app_handle, emitter.clone());
+        let repository = Arc::new(app_handle.state::<Repository>().inner().clone());
+        let local = app_handle.state::<LocalPlayerRepository>().inner().clone();
+
         #[cfg(feature = "meter-core")]
         {
             use std::marker::PhantomData;
 
-            use crate::{abstractions::{DefaultRegionAccessor, SnowDamageEncryptionHandler, WindivertPacketCapture}, api::{SnowHeartbeatApi, SnowStatsApi}, live::{self, StartArgs}};
+            use crate::{abstractions::{DefaultRegionAccessor, RecordingPacketSource, SnowDamageEncryptionHandler, WindivertPacketCapture}, api::{SnowHeartbeatApi, SnowStatsApi}, live::{self, StartArgs}};
 
             let heartbeat_api = Box::new(SnowHeartbeatApi::new(settings.env.hearbeat_api_url.clone()));
             let region_accessor = Box::new(DefaultRegionAccessor::new(region_file_path.clone().into()));
             let packet_source = WindivertPacketCapture::new(region_file_path.display().to_string());
+            let packet_source = RecordingPacketSource::new(packet_source, recordings_path);
             let damage_handler = SnowDamageEncryptionHandler::new();
             let stats_api = {
-                use crate::local::LocalPlayerRepository;
-
-                let local: tauri::State<'_, LocalPlayerRepository> = app_handle.state::<LocalPlayerRepository>();
                 let local_info = local.read()?;
-                Box::new(SnowStatsApi::new(settings.env.stats_api_url.clone(), local_info.client_id))
+                Arc::new(SnowStatsApi::new(settings.env.stats_api_url.clone(), local_info.client_id))
             };
 
-            app_handle.manage(stats_api);
-            
             let args = StartArgs  {
-                app_handle,
+                emitter,
+                listener,
+                repository,
+                stats_api,
+                local,
                 port,
                 settings,
                 version,
@@ -100,19 +110,25 @@ impl BackgroundWorker {
         {
             use std::marker::PhantomData;
 
-            use crate::{abstractions::{DefaultDamageEncryptionHandler, FakePacketSource, FakeRegionAccessor}, api::{FakeHeartbeatApi, FakeStatsApi}, live::{self, StartArgs}};
+            use crate::{abstractions::{DefaultDamageEncryptionHandler, FakePacketSource, FakeRegionAccessor, RecordingPacketSource}, api::{FakeHeartbeatApi, FakeStatsApi}, live::{self, StartArgs}};
 
             let heartbeat_api = Box::new(FakeHeartbeatApi::new());
             let region_accessor = Box::new(FakeRegionAccessor::new("EUC".into()));
+            #[cfg(feature = "develop")]
+            let packet_source = FakePacketSource::from_script(crate::abstractions::ScriptedRaidBuilder::demo().build());
+            #[cfg(not(feature = "develop"))]
             let packet_source = FakePacketSource::new();
+            let packet_source = RecordingPacketSource::new(packet_source, recordings_path);
             let damage_handler = DefaultDamageEncryptionHandler::new();
 
-            let stats_api = Box::new(FakeStatsApi::new());
-
-            app_handle.manage(stats_api);
+            let stats_api = Arc::new(FakeStatsApi::new());
 
             let args = StartArgs {
-                app_handle,
+                emitter,
+                listener,
+                repository,
+                stats_api,
+                local,
                 port,
                 settings,
                 version,
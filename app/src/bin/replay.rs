@@ -0,0 +1,108 @@
+use std::{fs::File, marker::PhantomData, path::PathBuf, sync::Arc};
+
+use anyhow::{anyhow, bail, Context, Result};
+use sayafushi_lib::{
+    abstractions::{DefaultDamageEncryptionHandler, EventEmitter, FakeRegionAccessor, MemoryEventEmitter, ReplayPacketSource, ReplaySpeed},
+    api::{FakeHeartbeatApi, FakeStatsApi},
+    data::AssetPreloader,
+    database::Database,
+    live::{self, AppListener, StartArgs},
+    local::LocalPlayerRepository,
+    settings::{EnvironmentSettings, GeneralSettings, Settings},
+};
+
+/// Runs a recorded capture through the live pipeline without any window.
+///
+/// usage: replay <capture file or directory> [--speed <factor>|--original] [--output <file>] [--database <file>] [--settings <file>]
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let mut capture: Option<PathBuf> = None;
+    let mut speed = ReplaySpeed::Unbounded;
+    let mut output: Option<PathBuf> = None;
+    let mut database_path = PathBuf::from("replay.db");
+    let mut settings_path: Option<PathBuf> = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--original" => speed = ReplaySpeed::Original,
+            "--speed" => {
+                let factor = args.next().ok_or_else(|| anyhow!("missing speed factor"))?;
+                let factor: f64 = factor.parse().map_err(|_| anyhow!("invalid speed factor {}", factor))?;
+
+                if !factor.is_finite() || factor <= 0.0 {
+                    bail!("speed factor must be greater than 0, got {}", factor);
+                }
+
+                speed = ReplaySpeed::Accelerated(factor);
+            },
+            "--output" => output = args.next().map(PathBuf::from),
+            "--database" => {
+                database_path = args.next().map(PathBuf::from).ok_or_else(|| anyhow!("missing database path"))?;
+            },
+            "--settings" => {
+                settings_path = Some(args.next().map(PathBuf::from).ok_or_else(|| anyhow!("missing settings path"))?);
+            },
+            _ => capture = Some(PathBuf::from(arg)),
+        }
+    }
+
+    let capture = capture.ok_or_else(|| anyhow!("missing capture path"))?;
+    let version = env!("CARGO_PKG_VERSION").to_string();
+    let current_dir = std::env::current_dir()?;
+    let settings = read_settings(settings_path)?;
+
+    AssetPreloader::new(current_dir.join("meter-data")).wait()?;
+
+    let database = Database::new(database_path, &current_dir.join("migrations"), &version)?;
+    let local = LocalPlayerRepository::new(current_dir.join("replay_local_players.json"))?;
+    let memory = Arc::new(MemoryEventEmitter::new());
+    let emitter: Arc<dyn EventEmitter> = memory.clone();
+
+    let args = StartArgs {
+        packet_source: ReplayPacketSource::new(capture, speed),
+        emitter,
+        listener: AppListener::new(false),
+        repository: Arc::new(database.create_repository()),
+        stats_api: Arc::new(FakeStatsApi::new()),
+        local,
+        port: 0,
+        settings,
+        version,
+        heartbeat_api: Box::new(FakeHeartbeatApi::new()),
+        region_accessor: Box::new(FakeRegionAccessor::new("EUC".into())),
+        damage_handler: DefaultDamageEncryptionHandler::new(),
+        _marker: PhantomData,
+    };
+
+    for save in live::start(args)? {
+        save.await?;
+    }
+
+    let events = memory.events();
+    println!("replay finished, {} events emitted", events.len());
+
+    if let Some(output) = output {
+        let encounter = memory.last("encounter-update");
+        serde_json::to_writer_pretty(File::create(&output)?, &encounter)?;
+        println!("last encounter written to {}", output.display());
+    }
+
+    Ok(())
+}
+
+/// Without a file the replay runs with the built-in defaults, it does not need the settings template.
+fn read_settings(path: Option<PathBuf>) -> Result<Settings> {
+    let Some(path) = path else {
+        return Ok(Settings {
+            env: EnvironmentSettings::default(),
+            general: GeneralSettings::default(),
+            extra: Default::default(),
+        });
+    };
+
+    let reader = File::open(&path).with_context(|| format!("could not read {}", path.display()))?;
+    let settings = serde_json::from_reader(reader).with_context(|| format!("malformed {}", path.display()))?;
+
+    Ok(settings)
+}
@@ -6,6 +6,8 @@ use window_vibrancy::Color;
 
 pub const WINDOW_MS: i64 = 5_000;
 pub const WINDOW_S: i64 = 5;
+/// Incoming hits kept for a death recap.
+pub const DEATH_RECAP_WINDOW_MS: i64 = 10_000;
 pub const METER_WINDOW_LABEL: &str = "main";
 pub const METER_MINI_WINDOW_LABEL: &str = "mini";
 pub const LOGS_WINDOW_LABEL: &str = "logs";
@@ -15,6 +17,9 @@ pub const DEFAULT_SETTINGS_PATH: &str = "settings.template.json";
 pub const SETTINGS_PATH: &str = "settings.json";
 pub const LOCAL_PLAYERS_PATH: &str = "local_players.json";
 pub const REGION_PATH: &str = "current_region";
+pub const RECORDINGS_PATH: &str = "recordings";
+pub const METER_DATA_PATH: &str = "meter-data";
+pub const METER_DATA_PACKAGES_PATH: &str = "meter-data-packages";
 pub const STEAM_GAME_URL: &str = "steam://rungameid/1599340";
 pub const GAME_EXE_NAME: &str = "LOSTARK.exe";
 pub const TASK_NAME: &str = "LOA_Logs_Auto_Start";
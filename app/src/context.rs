@@ -16,6 +16,9 @@ pub struct AppContext {
     pub migrations_path: PathBuf,
     pub local_player_path: PathBuf,
     pub region_file_path: PathBuf,
+    pub recordings_path: PathBuf,
+    pub meter_data_path: PathBuf,
+    pub meter_data_packages_path: PathBuf,
 }
 
 impl AppContext {
@@ -28,6 +31,9 @@ impl AppContext {
         let migrations_path = current_dir.join(MIGRATIONS_PATH);
         let local_player_path = current_dir.join(LOCAL_PLAYERS_PATH);
         let region_file_path = current_dir.join(REGION_PATH);
+        let recordings_path = current_dir.join(RECORDINGS_PATH);
+        let meter_data_path = current_dir.join(METER_DATA_PATH);
+        let meter_data_packages_path = current_dir.join(METER_DATA_PACKAGES_PATH);
 
         Ok(Self {
             version,
@@ -37,7 +43,10 @@ impl AppContext {
             database_path,
             migrations_path,
             local_player_path,
-            region_file_path
+            region_file_path,
+            recordings_path,
+            meter_data_path,
+            meter_data_packages_path
         })
     }
 }
@@ -1,140 +1,262 @@
-use std::{fs::File, io::Read, ops::Deref, sync::{Mutex, OnceLock}, thread::{self, JoinHandle}};
+use std::{fs::File, io::Read, ops::Deref, path::{Path, PathBuf}, sync::{Arc, LazyLock, Mutex}, thread::{self, JoinHandle}};
+use arc_swap::{ArcSwapOption, Guard};
 use serde::de::DeserializeOwned;
 use hashbrown::{HashMap, HashSet};
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, bail, Context, Result};
+use log::*;
 
+use crate::data_updater::current_package;
 use crate::models::*;
 
-pub static COMBAT_EFFECT_DATA: OnceLockWrapper<HashMap<i32, CombatEffectData>> = OnceLockWrapper::new();
-pub static ENGRAVING_DATA: OnceLockWrapper<HashMap<u32, EngravingData>> = OnceLockWrapper::new();
-pub static SKILL_BUFF_DATA: OnceLockWrapper<HashMap<u32, SkillBuffData>> = OnceLockWrapper::new();
-pub static SKILL_DATA: OnceLockWrapper<HashMap<u32, SkillData>> = OnceLockWrapper::new();
-pub static SKILL_EFFECT_DATA: OnceLockWrapper<HashMap<u32, SkillEffectData>> = OnceLockWrapper::new();
-pub static SUPPORT_AP_GROUP: OnceLockWrapper<HashSet<u32>> = OnceLockWrapper::new();
-pub static SUPPORT_IDENTITY_GROUP: OnceLockWrapper<HashSet<u32>> = OnceLockWrapper::new();
-pub static STAT_TYPE_MAP: OnceLockWrapper<HashMap<String, u32>> = OnceLockWrapper::new();
-pub static ESTHER_DATA: OnceLockWrapper<Vec<Esther>> = OnceLockWrapper::new();
-pub static NPC_DATA: OnceLockWrapper<HashMap<u32, Npc>> = OnceLockWrapper::new();
-pub static GEM_SKILL_MAP: OnceLockWrapper<HashMap<u32, Vec<u32>>> = OnceLockWrapper::new();
-pub static RAID_MAP: OnceLockWrapper<HashMap<String, String>> = OnceLockWrapper::new();
-
-pub struct OnceLockWrapper<T>(OnceLock<T>);
-
-impl<T> OnceLockWrapper<T> {
-    pub const fn new() -> Self {
-        Self(OnceLock::new())
-    }
+pub mod table;
+
+use table::LazyTable;
+
+static METER_DATA: ArcSwapOption<MeterData> = ArcSwapOption::const_empty();
+
+pub static SUPPORT_AP_GROUP: LazyLock<HashSet<u32>> = LazyLock::new(|| HashSet::from([
+    101204, // bard
+    101105, // paladin
+    314004, // artist
+    480030, // valkyrie
+]));
+
+pub static SUPPORT_IDENTITY_GROUP: LazyLock<HashSet<u32>> = LazyLock::new(|| HashSet::from([
+    211400, // bard serenade of courage
+    368000, // paladin holy aura
+    310501, // artist moonfall
+    480018, // valkyrie release light
+]));
+
+/// Stat types looked up by name when parsing combat effects.
+const REQUIRED_STAT_TYPES: [&str; 8] = [
+    "def",
+    "magical_inc_rate",
+    "move_speed",
+    "vehicle_move_speed_rate",
+    "attack_power_sub_rate_1",
+    "skill_damage_sub_rate_2",
+    "fire_dam_rate",
+    "elements_dam_rate",
+];
+
+/// The installed meter-data.
+///
+/// Keep the returned value for as long as related lookups are made, a reload swaps every
+/// table at once and the previous data is freed when its last reader lets go.
+pub fn meter_data() -> MeterDataRef {
+    MeterDataRef(METER_DATA.load())
+}
 
-    pub fn set(&self, value: T) -> Result<(), T> {
-        self.0.set(value)
+pub struct MeterDataRef(Guard<Option<Arc<MeterData>>>);
+
+impl MeterDataRef {
+    pub fn is_loaded(&self) -> bool {
+        self.0.is_some()
     }
 }
 
-impl<T> Deref for OnceLockWrapper<T> {
-    type Target = T;
+impl Deref for MeterDataRef {
+    type Target = MeterData;
 
     fn deref(&self) -> &Self::Target {
-        self.0.get().expect("OnceLockWrapper not initialized")
+        self.0.as_deref().expect("meter-data not loaded")
     }
 }
 
-pub struct AssetPreloader(Mutex<Option<JoinHandle<()>>>);
+#[derive(Debug, Default, serde::Deserialize)]
+struct MeterDataVersion {
+    version: String,
+}
+
+/// Every meter-data asset, parsed and validated before it replaces the installed data.
+pub struct MeterData {
+    pub version: String,
+    pub combat_effect: LazyTable<i32, CombatEffectData>,
+    pub engraving: HashMap<u32, EngravingData>,
+    pub skill_buff: LazyTable<u32, SkillBuffData>,
+    pub skill: LazyTable<u32, SkillData>,
+    pub skill_effect: HashMap<u32, SkillEffectData>,
+    pub stat_type: HashMap<String, u32>,
+    pub esther: Vec<Esther>,
+    pub npc: LazyTable<u32, Npc>,
+    pub gem_skill_map: HashMap<u32, Vec<u32>>,
+    pub raid_catalog: RaidCatalog,
+    pub raid_difficulty: RaidDifficultyData,
+    pub spec_rules: SpecRules,
+}
 
-fn load<T: DeserializeOwned>(path: &str, buffer: &mut Vec<u8>) -> T {
+fn load<T: DeserializeOwned>(folder: &Path, file_name: &str, buffer: &mut Vec<u8>) -> Result<T> {
+    let path = folder.join(file_name);
     buffer.clear();
-    let mut file = File::open(path).unwrap();
-    file.read_to_end(buffer).unwrap();
-    serde_json::from_slice::<T>(buffer).unwrap()
+
+    File::open(&path)
+        .and_then(|mut file| file.read_to_end(buffer))
+        .with_context(|| format!("could not read {}", path.display()))?;
+
+    serde_json::from_slice::<T>(buffer)
+        .with_context(|| format!("malformed {}", path.display()))
+}
+
+impl MeterData {
+    pub fn load(folder: &Path) -> Result<Self> {
+        let mut buffer = Vec::new();
+
+        let version = match folder.join("version.json").exists() {
+            true => load::<MeterDataVersion>(folder, "version.json", &mut buffer)?.version,
+            false => "unknown".to_string(),
+        };
+
+        let gem_skill_map = {
+            let raw: HashMap<String, (String, String, Vec<u32>)> =
+                load(folder, "GemSkillGroup.json", &mut buffer)?;
+            raw.into_iter()
+                .filter_map(|(key, entry)| key.parse::<u32>().ok().map(|id| (id, entry.2)))
+                .collect()
+        };
+
+        let data = Self {
+            version,
+            combat_effect: LazyTable::load(folder, "CombatEffect.json")?,
+            engraving: load(folder, "Ability.json", &mut buffer)?,
+            skill_buff: LazyTable::load(folder, "SkillBuff.json")?,
+            skill: LazyTable::load(folder, "Skill.json")?,
+            skill_effect: load(folder, "SkillEffect.json", &mut buffer)?,
+            stat_type: load(folder, "StatType.json", &mut buffer)?,
+            esther: load(folder, "Esther.json", &mut buffer)?,
+            npc: LazyTable::load(folder, "Npc.json")?,
+            gem_skill_map,
+            raid_difficulty: load(folder, "RaidDifficulty.json", &mut buffer)?,
+            spec_rules: load(folder, "SpecRules.json", &mut buffer)?,
+            raid_catalog: load(folder, "RaidCatalog.json", &mut buffer)?,
+        };
+
+        data.validate()?;
+
+        Ok(data)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.skill.is_empty() {
+            bail!("Skill.json has no skills");
+        }
+
+        if self.skill_buff.is_empty() {
+            bail!("SkillBuff.json has no buffs");
+        }
+
+        if self.npc.is_empty() {
+            bail!("Npc.json has no npcs");
+        }
+
+        if let Some(name) = REQUIRED_STAT_TYPES.iter().find(|name| !self.stat_type.contains_key(**name)) {
+            bail!("StatType.json is missing \"{}\"", name);
+        }
+
+        Ok(())
+    }
+
+    /// Swaps this data in for every reader at once.
+    pub fn install(self) {
+        METER_DATA.store(Some(Arc::new(self)));
+    }
+}
+
+enum AssetState {
+    Loading(JoinHandle<Result<()>>),
+    Loaded,
+    Failed(String),
+}
+
+pub struct AssetPreloader {
+    folder: PathBuf,
+    packages: Option<PathBuf>,
+    state: Mutex<AssetState>,
+}
+
+/// Installs the current package if it loads and the bundled copy otherwise.
+fn load_first(folder: &Path, packages: Option<&Path>) -> Result<String> {
+    let mut last_err = anyhow!("no meter-data folder");
+    let package = packages.and_then(current_package);
+
+    for folder in package.iter().map(PathBuf::as_path).chain([folder]) {
+        match MeterData::load(folder) {
+            Ok(data) => {
+                let version = data.version.clone();
+                data.install();
+                return Ok(version);
+            },
+            Err(err) => {
+                warn!("could not load meter-data from {}: {:#}", folder.display(), err);
+                last_err = err;
+            },
+        }
+    }
+
+    Err(last_err)
 }
 
 impl AssetPreloader {
-    pub fn new() -> Self {
-        let handle = thread::spawn(|| {
-            let mut buffer = Vec::with_capacity(1024 * 1024 * 30);
-
-            COMBAT_EFFECT_DATA
-                .set(load("meter-data/CombatEffect.json", &mut buffer))
-                .unwrap();
-
-            ENGRAVING_DATA
-                .set(load("meter-data/Ability.json", &mut buffer))
-                .unwrap();
-
-            SKILL_BUFF_DATA
-                .set(load("meter-data/SkillBuff.json", &mut buffer))
-                .unwrap();
-
-            SKILL_DATA
-                .set(load("meter-data/Skill.json", &mut buffer))
-                .unwrap();
-
-            SKILL_EFFECT_DATA
-                .set(load("meter-data/SkillEffect.json", &mut buffer))
-                .unwrap();
-
-            STAT_TYPE_MAP
-                .set(load("meter-data/StatType.json", &mut buffer))
-                .unwrap();
-
-            ESTHER_DATA
-                .set(load("meter-data/Esther.json", &mut buffer))
-                .unwrap();
-
-            NPC_DATA
-                .set(load("meter-data/Npc.json", &mut buffer))
-                .unwrap();
-
-            GEM_SKILL_MAP
-                .set({
-                    let raw: HashMap<String, (String, String, Vec<u32>)> =
-                        load("meter-data/GemSkillGroup.json", &mut buffer);
-                    raw.into_iter()
-                        .filter_map(|(key, entry)| key.parse::<u32>().ok().map(|id| (id, entry.2)))
-                        .collect()
-                })
-                .unwrap();
-
-            RAID_MAP
-                .set({
-                    let encounters: HashMap<String, HashMap<String, Vec<String>>> =
-                        load("meter-data/encounters.json", &mut buffer);
-                    encounters
-                        .values()
-                        .flat_map(|raid| raid.iter())
-                        .flat_map(|(gate, bosses)| {
-                            bosses.iter().map(move |boss| (boss.clone(), gate.clone()))
-                        })
-                        .collect()
-                })
-                .unwrap();
-
-            SUPPORT_AP_GROUP
-                .set(HashSet::from([
-                    101204, // bard
-                    101105, // paladin
-                    314004, // artist
-                    480030, // valkyrie
-                ]))
-                .unwrap();
-
-            SUPPORT_IDENTITY_GROUP
-                .set(HashSet::from([
-                    211400, // bard serenade of courage
-                    368000, // paladin holy aura
-                    310501, // artist moonfall
-                    480018, // valkyrie release light
-                ]))
-                .unwrap();
-        });
-
-        Self(Mutex::new(Some(handle)))
+    pub fn new(folder: PathBuf) -> Self {
+        Self::start(folder, None)
+    }
+
+    /// Prefers the current package in `packages` over the bundled `folder`, it is looked up again on reload.
+    pub fn with_packages(folder: PathBuf, packages: PathBuf) -> Self {
+        Self::start(folder, Some(packages))
+    }
+
+    fn start(folder: PathBuf, packages: Option<PathBuf>) -> Self {
+        let handle = {
+            let folder = folder.clone();
+            let packages = packages.clone();
+            thread::spawn(move || load_first(&folder, packages.as_deref()).map(|_| ()))
+        };
+
+        Self {
+            folder,
+            packages,
+            state: Mutex::new(AssetState::Loading(handle)),
+        }
     }
 
     pub fn wait(&self) -> Result<()> {
-        if let Some(handle) = self.0.lock().unwrap().take() {
-            handle.join().map_err(|err| anyhow!("Thread panicked: {:?}", err))?;
+        let mut state = self.state.lock().unwrap();
+
+        if let AssetState::Loading(_) = &*state {
+            let AssetState::Loading(handle) = std::mem::replace(&mut *state, AssetState::Loaded) else {
+                unreachable!();
+            };
+
+            let result = handle
+                .join()
+                .map_err(|err| anyhow!("Thread panicked: {:?}", err))
+                .and_then(|result| result);
+
+            if let Err(err) = result {
+                error!("could not load meter-data: {:#}", err);
+                *state = AssetState::Failed(format!("{:#}", err));
+            }
         }
 
-        Ok(())
+        match &*state {
+            AssetState::Failed(err) => Err(anyhow!("could not load meter-data: {}", err)),
+            _ => Ok(()),
+        }
     }
-}
\ No newline at end of file
+
+    /// Loads the meter-data folder again and swaps it in, the current data stays in use if it fails.
+    ///
+    /// Returns the version of the loaded data.
+    pub fn reload(&self) -> Result<String> {
+        // a failed initial load can be fixed by a reload
+        let _ = self.wait();
+
+        let version = load_first(&self.folder, self.packages.as_deref())?;
+
+        *self.state.lock().unwrap() = AssetState::Loaded;
+        info!("reloaded meter-data version {}", version);
+
+        Ok(version)
+    }
+}
@@ -0,0 +1,314 @@
+//! Indexed binary copy of the large meter-data tables, built from the json on first run.
+//!
+//! Layout, little endian: magic, format version, length and modification time of the source json,
+//! entry count, then `count` index entries `(key: i64, offset: u32, len: u32)` sorted by key,
+//! followed by the records. A record is the minified json of one entry and is only deserialized
+//! on its first lookup. The file is memory mapped and, as index entries have a fixed width,
+//! lookups binary search the index in place. If the binary copy can not be written, e.g. the
+//! install folder is read-only, the json is parsed straight into memory instead.
+
+use std::{cmp::Ordering, fs::{self, File}, io::Write, marker::PhantomData, ops::Deref, path::Path, sync::OnceLock, time::UNIX_EPOCH};
+use anyhow::{bail, Context, Result};
+use hashbrown::HashMap;
+use log::*;
+use memmap2::Mmap;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+const MAGIC: &[u8; 4] = b"SFMD";
+const FORMAT_VERSION: u32 = 1;
+const HEADER_LEN: usize = 4 + 4 + 8 + 8 + 4;
+const INDEX_ENTRY_LEN: usize = 8 + 4 + 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SourceInfo {
+    len: u64,
+    modified: u64,
+}
+
+enum TableData {
+    Mapped(Mmap),
+    /// Freshly built table, kept in memory for the rest of the run.
+    Owned(Vec<u8>),
+}
+
+impl Deref for TableData {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            TableData::Mapped(mmap) => mmap,
+            TableData::Owned(data) => data,
+        }
+    }
+}
+
+enum Entries<V> {
+    /// Records of a binary table, each deserialized on its first lookup.
+    Indexed {
+        data: TableData,
+        count: usize,
+        values: Vec<OnceLock<Option<V>>>,
+    },
+    /// Parsed from the json as no binary copy could be written.
+    Parsed(HashMap<i64, V>),
+}
+
+pub struct LazyTable<K, V> {
+    entries: Entries<V>,
+    _marker: PhantomData<K>,
+}
+
+impl<K, V> LazyTable<K, V>
+where
+    K: Copy + Into<i64>,
+    V: DeserializeOwned,
+{
+    /// Opens `<name>.bin` next to `<name>.json`, rebuilding it if the json changed since.
+    pub fn load(folder: &Path, file_name: &str) -> Result<Self> {
+        let source_path = folder.join(file_name);
+        let path = source_path.with_extension("bin");
+
+        let source = match fs::metadata(&source_path) {
+            Ok(metadata) => source_info(&metadata)?,
+            // only the binary copy was shipped
+            Err(_) if path.exists() => return Self::open(&path, None),
+            Err(err) => return Err(err).with_context(|| format!("could not read {}", source_path.display())),
+        };
+
+        match Self::open(&path, Some(source)) {
+            Ok(table) => return Ok(table),
+            Err(err) if path.exists() => debug!("rebuilding {}: {:#}", path.display(), err),
+            Err(_) => {},
+        }
+
+        let temp_path = path.with_extension("bin.tmp");
+
+        let file = match File::create(&temp_path) {
+            Ok(file) => file,
+            Err(err) => {
+                warn!("could not write {}, keeping it in memory: {}", path.display(), err);
+                return Self::parse(&source_path);
+            }
+        };
+
+        let data = build(&source_path, source)?;
+
+        if let Err(err) = write(file, &temp_path, &path, &data) {
+            warn!("could not write {}: {:#}", path.display(), err);
+        }
+
+        Self::from_data(TableData::Owned(data), None).with_context(|| format!("malformed {}", path.display()))
+    }
+
+    fn open(path: &Path, source: Option<SourceInfo>) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("could not read {}", path.display()))?;
+        // SAFETY: tables are never written in place, a rebuilt table is renamed over the old file and
+        // a new meter-data package goes to its own folder. The mapping is owned by the table, so it
+        // is unmapped once the meter-data holding it is replaced and the last reader drops it.
+        let mmap = unsafe { Mmap::map(&file) }.with_context(|| format!("could not map {}", path.display()))?;
+
+        Self::from_data(TableData::Mapped(mmap), source).with_context(|| format!("malformed {}", path.display()))
+    }
+
+    fn from_data(data: TableData, source: Option<SourceInfo>) -> Result<Self> {
+        if data.len() < HEADER_LEN || &data[0..4] != MAGIC {
+            bail!("not a meter-data table");
+        }
+
+        if read_u32(&data, 4) != FORMAT_VERSION {
+            bail!("unsupported format version");
+        }
+
+        let built_from = SourceInfo {
+            len: read_u64(&data, 8),
+            modified: read_u64(&data, 16),
+        };
+
+        if source.is_some_and(|source| source != built_from) {
+            bail!("outdated");
+        }
+
+        let count = read_u32(&data, 24) as usize;
+        let records_start = HEADER_LEN + count * INDEX_ENTRY_LEN;
+
+        if data.len() < records_start {
+            bail!("truncated index");
+        }
+
+        let mut previous = None;
+
+        for position in 0..count {
+            let (key, offset, len) = read_entry(&data, position);
+
+            if (offset as usize) < records_start || (offset as usize + len as usize) > data.len() {
+                bail!("record {} out of bounds", key);
+            }
+
+            if previous.is_some_and(|previous| previous >= key) {
+                bail!("index is not sorted");
+            }
+
+            previous = Some(key);
+        }
+
+        let values = (0..count).map(|_| OnceLock::new()).collect();
+
+        Ok(Self {
+            entries: Entries::Indexed { data, count, values },
+            _marker: PhantomData,
+        })
+    }
+
+    fn parse(source_path: &Path) -> Result<Self> {
+        let json = fs::read(source_path).with_context(|| format!("could not read {}", source_path.display()))?;
+        let entries: HashMap<String, V> = serde_json::from_slice(&json)
+            .with_context(|| format!("malformed {}", source_path.display()))?;
+
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| match key.parse::<i64>() {
+                Ok(id) => Ok((id, value)),
+                Err(_) => bail!("malformed {}: invalid key {}", source_path.display(), key),
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            entries: Entries::Parsed(entries),
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let key: i64 = (*key).into();
+
+        let (data, values) = match &self.entries {
+            Entries::Indexed { data, values, .. } => (data, values),
+            Entries::Parsed(entries) => return entries.get(&key),
+        };
+
+        let position = self.find(key)?;
+
+        values[position]
+            .get_or_init(|| {
+                let (_, offset, len) = read_entry(data, position);
+                let record = &data[offset as usize..offset as usize + len as usize];
+
+                match serde_json::from_slice(record) {
+                    Ok(value) => Some(value),
+                    Err(err) => {
+                        warn!("malformed meter-data record {}: {}", key, err);
+                        None
+                    }
+                }
+            })
+            .as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.entries {
+            Entries::Indexed { count, .. } => *count,
+            Entries::Parsed(entries) => entries.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn find(&self, key: i64) -> Option<usize> {
+        let Entries::Indexed { data, count, .. } = &self.entries else {
+            return None;
+        };
+
+        let (mut low, mut high) = (0, *count);
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+
+            match read_entry(data, middle).0.cmp(&key) {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return Some(middle),
+            }
+        }
+
+        None
+    }
+}
+
+fn source_info(metadata: &fs::Metadata) -> Result<SourceInfo> {
+    let modified = metadata.modified()?.duration_since(UNIX_EPOCH)?.as_nanos() as u64;
+
+    Ok(SourceInfo {
+        len: metadata.len(),
+        modified,
+    })
+}
+
+/// Converts a json object keyed by numeric ids.
+fn build(source_path: &Path, source: SourceInfo) -> Result<Vec<u8>> {
+    let json = fs::read(source_path).with_context(|| format!("could not read {}", source_path.display()))?;
+    let entries: Map<String, Value> = serde_json::from_slice(&json)
+        .with_context(|| format!("malformed {}", source_path.display()))?;
+
+    let mut records = Vec::with_capacity(entries.len());
+
+    for (key, value) in entries {
+        let key: i64 = key
+            .parse()
+            .with_context(|| format!("malformed {}: invalid key {}", source_path.display(), key))?;
+        records.push((key, serde_json::to_vec(&value)?));
+    }
+
+    records.sort_by_key(|(key, _)| *key);
+    records.dedup_by_key(|(key, _)| *key);
+
+    let records_start = HEADER_LEN + records.len() * INDEX_ENTRY_LEN;
+    let records_len: usize = records.iter().map(|(_, record)| record.len()).sum();
+    let mut data = Vec::with_capacity(records_start + records_len);
+
+    data.extend_from_slice(MAGIC);
+    data.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+    data.extend_from_slice(&source.len.to_le_bytes());
+    data.extend_from_slice(&source.modified.to_le_bytes());
+    data.extend_from_slice(&(records.len() as u32).to_le_bytes());
+
+    let mut offset = records_start;
+
+    for (key, record) in &records {
+        data.extend_from_slice(&key.to_le_bytes());
+        data.extend_from_slice(&(offset as u32).to_le_bytes());
+        data.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        offset += record.len();
+    }
+
+    for (_, record) in records {
+        data.extend_from_slice(&record);
+    }
+
+    Ok(data)
+}
+
+fn write(mut file: File, temp_path: &Path, path: &Path, data: &[u8]) -> Result<()> {
+    file.write_all(data)?;
+    drop(file);
+    fs::rename(temp_path, path)?;
+
+    Ok(())
+}
+
+/// `(key, offset, len)` of the index entry at `position`.
+fn read_entry(data: &[u8], position: usize) -> (i64, u32, u32) {
+    let position = HEADER_LEN + position * INDEX_ENTRY_LEN;
+
+    (read_u64(data, position) as i64, read_u32(data, position + 8), read_u32(data, position + 12))
+}
+
+fn read_u32(data: &[u8], position: usize) -> u32 {
+    u32::from_le_bytes(data[position..position + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], position: usize) -> u64 {
+    u64::from_le_bytes(data[position..position + 8].try_into().unwrap())
+}
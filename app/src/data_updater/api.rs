@@ -0,0 +1,55 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+
+use super::DataManifest;
+
+#[async_trait]
+pub trait DataPackageApi: Send + Sync + 'static {
+    async fn get_manifest(&self) -> Result<DataManifest>;
+    async fn download(&self, path: &str) -> Result<Vec<u8>>;
+}
+
+#[derive(Clone)]
+pub struct HttpDataPackageApi {
+    base_url: String,
+    client: Client,
+}
+
+#[async_trait]
+impl DataPackageApi for HttpDataPackageApi {
+    async fn get_manifest(&self) -> Result<DataManifest> {
+        let url = format!("{}/manifest.json", self.base_url);
+        let manifest = self.client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<DataManifest>()
+            .await?;
+
+        Ok(manifest)
+    }
+
+    async fn download(&self, path: &str) -> Result<Vec<u8>> {
+        let url = format!("{}/{}", self.base_url, path);
+        let data = self.client
+            .get(url)
+            .send()
+            .await?
+            .error_for_status()?
+            .bytes()
+            .await?;
+
+        Ok(data.to_vec())
+    }
+}
+
+impl HttpDataPackageApi {
+    pub fn new(base_url: String) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: Client::new(),
+        }
+    }
+}
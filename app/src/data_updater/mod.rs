@@ -0,0 +1,44 @@
+pub mod api;
+pub mod models;
+pub mod updater;
+
+pub use api::*;
+pub use models::*;
+pub use updater::*;
+
+use log::*;
+use tauri::{AppHandle, Manager};
+
+use crate::{context::AppContext, data::AssetPreloader, settings::Settings};
+
+/// Checks for a newer meter-data package in the background and reloads the assets once installed.
+pub fn setup_data_updater(app_handle: &AppHandle, context: &AppContext, settings: &Settings) {
+    if settings.env.meter_data_url.is_empty() {
+        return;
+    }
+
+    let api = HttpDataPackageApi::new(settings.env.meter_data_url.clone());
+    let updater = DataPackageUpdater::new(
+        api,
+        context.meter_data_packages_path.clone(),
+        context.meter_data_path.clone());
+    let app_handle = app_handle.clone();
+
+    tauri::async_runtime::spawn(async move {
+        match updater.update().await {
+            Ok(Some(version)) => {
+                let result = tauri::async_runtime::spawn_blocking(move || {
+                    app_handle.state::<AssetPreloader>().reload()
+                }).await;
+
+                match result {
+                    Ok(Ok(_)) => {},
+                    Ok(Err(err)) => error!("could not reload meter-data {}: {:#}", version, err),
+                    Err(err) => error!("could not reload meter-data {}: {}", version, err),
+                }
+            },
+            Ok(None) => {},
+            Err(err) => warn!("could not update meter-data: {:#}", err),
+        }
+    });
+}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// Published next to the data packages as `manifest.json`.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataManifest {
+    pub version: String,
+    pub files: Vec<DataManifestFile>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataManifestFile {
+    /// File name inside the meter-data folder, e.g. `Skill.json`.
+    pub name: String,
+    /// Gzip compressed file, relative to the manifest.
+    pub path: String,
+    /// Hex encoded sha256 of the compressed file.
+    pub sha256: String,
+}
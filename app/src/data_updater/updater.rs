@@ -0,0 +1,269 @@
+use std::{cmp::Ordering, fs, io::Read, path::{Path, PathBuf}};
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use log::*;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::data::MeterData;
+use super::{DataManifest, DataManifestFile, DataPackageApi};
+
+/// Name of the file in the packages folder which holds the version of the package in use.
+const CURRENT_PACKAGE: &str = "current";
+const STAGING_EXTENSION: &str = "staging";
+
+/// Downloads meter-data packages into `<packages>/<version>` next to the bundled copy.
+///
+/// A package is built in a staging folder, validated and moved to its own folder, then the
+/// `current` pointer file is switched over. Installed folders are never modified or deleted
+/// while the app runs, as their tables may be memory mapped, see `prepare_packages`.
+pub struct DataPackageUpdater<A: DataPackageApi> {
+    api: A,
+    packages_path: PathBuf,
+    bundled_path: PathBuf,
+}
+
+impl<A: DataPackageApi> DataPackageUpdater<A> {
+    pub fn new(api: A, packages_path: PathBuf, bundled_path: PathBuf) -> Self {
+        Self {
+            api,
+            packages_path,
+            bundled_path,
+        }
+    }
+
+    pub fn installed_version(&self) -> Option<String> {
+        current_package(&self.packages_path)
+            .and_then(|package| read_version(&package))
+            .or_else(|| read_version(&self.bundled_path))
+    }
+
+    /// Returns the manifest if it differs from the installed version and is newer than the bundled copy.
+    pub async fn check(&self) -> Result<Option<DataManifest>> {
+        let manifest = self.api.get_manifest().await?;
+
+        if !is_package_name(&manifest.version) {
+            bail!("invalid version in manifest: {}", manifest.version);
+        }
+
+        if self.installed_version().as_deref() == Some(manifest.version.as_str()) {
+            return Ok(None);
+        }
+
+        let bundled_version = read_version(&self.bundled_path);
+
+        if bundled_version.is_some_and(|bundled| compare_versions(&manifest.version, &bundled) != Some(Ordering::Greater)) {
+            info!("meter-data package {} is not newer than the bundled copy", manifest.version);
+            return Ok(None);
+        }
+
+        Ok(Some(manifest))
+    }
+
+    pub async fn install(&self, manifest: &DataManifest) -> Result<()> {
+        let staging = self.staging_path(manifest);
+
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+
+        fs::create_dir_all(&staging)?;
+
+        // a package only has to contain the files which changed, binary tables are rebuilt on load
+        for entry in fs::read_dir(&self.bundled_path)
+            .with_context(|| format!("could not read {}", self.bundled_path.display()))?
+        {
+            let entry = entry?;
+            let is_table = entry.path().extension().is_some_and(|extension| extension == "bin");
+
+            if entry.file_type()?.is_file() && !is_table {
+                fs::copy(entry.path(), staging.join(entry.file_name()))?;
+            }
+        }
+
+        for file in &manifest.files {
+            let data = self.download(file).await?;
+            fs::write(staging.join(&file.name), data)?;
+        }
+
+        // the bundled copy the package was built on, see `prepare_packages`
+        let version = json!({
+            "version": manifest.version,
+            "bundledVersion": read_version(&self.bundled_path),
+        });
+        fs::write(staging.join("version.json"), serde_json::to_vec_pretty(&version)?)?;
+
+        // the validated data is dropped right away, so its tables are unmapped before the move
+        let folder = staging.clone();
+        tokio::task::spawn_blocking(move || MeterData::load(&folder).map(|_| ()))
+            .await?
+            .context("downloaded meter-data is invalid")?;
+
+        let package = self.packages_path.join(&manifest.version);
+
+        // left behind by an earlier run which did not switch to it, never mapped
+        if package.exists() {
+            fs::remove_dir_all(&package)
+                .with_context(|| format!("could not replace {}", package.display()))?;
+        }
+
+        fs::rename(&staging, &package)
+            .with_context(|| format!("could not move {}", staging.display()))?;
+        set_current_package(&self.packages_path, &manifest.version)?;
+        info!("installed meter-data version {}", manifest.version);
+
+        Ok(())
+    }
+
+    /// Installs the latest package, returns its version if anything changed.
+    pub async fn update(&self) -> Result<Option<String>> {
+        let Some(manifest) = self.check().await? else {
+            info!("meter-data is up to date");
+            return Ok(None);
+        };
+
+        let result = self.install(&manifest).await;
+        let _ = fs::remove_dir_all(self.staging_path(&manifest));
+        result?;
+
+        Ok(Some(manifest.version))
+    }
+
+    fn staging_path(&self, manifest: &DataManifest) -> PathBuf {
+        self.packages_path.join(format!("{}.{}", manifest.version, STAGING_EXTENSION))
+    }
+
+    async fn download(&self, file: &DataManifestFile) -> Result<Vec<u8>> {
+        if Path::new(&file.name).file_name().is_none_or(|name| name != file.name.as_str()) {
+            bail!("invalid file name in manifest: {}", file.name);
+        }
+
+        let compressed = self.api.download(&file.path).await
+            .with_context(|| format!("could not download {}", file.path))?;
+
+        let checksum = format!("{:x}", Sha256::digest(&compressed));
+
+        if !checksum.eq_ignore_ascii_case(&file.sha256) {
+            bail!("checksum mismatch for {}: expected {}, got {}", file.path, file.sha256, checksum);
+        }
+
+        let mut data = Vec::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut data)
+            .with_context(|| format!("could not decompress {}", file.path))?;
+
+        Ok(data)
+    }
+}
+
+fn read_version(folder: &Path) -> Option<String> {
+    read_version_field(folder, "version")
+}
+
+fn read_version_field(folder: &Path, field: &str) -> Option<String> {
+    let data = fs::read(folder.join("version.json")).ok()?;
+    let value: serde_json::Value = serde_json::from_slice(&data).ok()?;
+
+    value[field].as_str().map(str::to_string)
+}
+
+/// The folder of the package in use, `None` if the bundled copy is used.
+pub fn current_package(packages_path: &Path) -> Option<PathBuf> {
+    let version = fs::read_to_string(packages_path.join(CURRENT_PACKAGE)).ok()?;
+    let version = version.trim();
+
+    if !is_package_name(version) {
+        warn!("ignoring invalid meter-data package {:?}", version);
+        return None;
+    }
+
+    let package = packages_path.join(version);
+    package.is_dir().then_some(package)
+}
+
+/// Cleans up the packages folder on start, before any table is mapped.
+///
+/// Drops the current package if it was built on another bundled copy, e.g. when an app update
+/// shipped newer meter-data, so it does not shadow it. Every other folder, such as replaced
+/// packages and interrupted downloads, is deleted. Returns the package to load, if any.
+pub fn prepare_packages(packages_path: &Path, bundled_path: &Path) -> Result<Option<PathBuf>> {
+    if !packages_path.exists() {
+        return Ok(None);
+    }
+
+    let mut current = current_package(packages_path);
+
+    if let Some(package) = &current {
+        let built_on = read_version_field(package, "bundledVersion");
+        let bundled_version = read_version(bundled_path);
+
+        if built_on != bundled_version {
+            info!(
+                "dropping meter-data package built on bundled version {:?}, bundled version is {:?}",
+                built_on, bundled_version
+            );
+            current = None;
+        }
+    }
+
+    if current.is_none() {
+        let pointer = packages_path.join(CURRENT_PACKAGE);
+
+        if pointer.exists() {
+            fs::remove_file(&pointer)?;
+        }
+    }
+
+    for entry in fs::read_dir(packages_path)? {
+        let path = entry?.path();
+
+        if path.file_name().is_some_and(|name| name == CURRENT_PACKAGE) || current.as_ref() == Some(&path) {
+            continue;
+        }
+
+        debug!("removing {}", path.display());
+
+        let result = match path.is_dir() {
+            true => fs::remove_dir_all(&path),
+            false => fs::remove_file(&path),
+        };
+
+        if let Err(err) = result {
+            warn!("could not remove {}: {}", path.display(), err);
+        }
+    }
+
+    Ok(current)
+}
+
+/// Points `current` at the installed package, the pointer is replaced in one rename.
+fn set_current_package(packages_path: &Path, version: &str) -> Result<()> {
+    let pointer = packages_path.join(CURRENT_PACKAGE);
+    let temp_path = pointer.with_extension("tmp");
+
+    fs::write(&temp_path, version)?;
+    fs::rename(&temp_path, &pointer)
+        .with_context(|| format!("could not switch to meter-data package {}", version))?;
+
+    Ok(())
+}
+
+/// A version is used as a folder name, so it has to be a plain file name.
+fn is_package_name(version: &str) -> bool {
+    !version.is_empty()
+        && version != CURRENT_PACKAGE
+        && Path::new(version).file_name().is_some_and(|name| name == version)
+        && Path::new(version).extension().is_none_or(|extension| extension != STAGING_EXTENSION && extension != "tmp")
+}
+
+/// Compares dotted numeric versions, e.g. `1.2.10` and `1.2.9`, `None` if either is not numeric.
+fn compare_versions(left: &str, right: &str) -> Option<Ordering> {
+    let parse = |version: &str| {
+        version
+            .split('.')
+            .map(|part| part.parse::<u64>().ok())
+            .collect::<Option<Vec<_>>>()
+    };
+
+    Some(parse(left)?.cmp(&parse(right)?))
+}
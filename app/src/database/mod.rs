@@ -12,6 +12,7 @@ use std::{fs, path::{Path, PathBuf}};
 
 pub use migrator::*;
 pub use repository::Repository;
+pub use utils::compute_raid_summary;
 
 pub struct Database(r2d2::Pool<SqliteConnectionManager>, PathBuf);
 
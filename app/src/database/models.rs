@@ -32,6 +32,32 @@ pub struct InsertEncounterArgs {
     pub manual: bool,
     pub skill_cast_log: HashMap<u64, HashMap<u32, BTreeMap<i64, SkillCast>>>,
     pub skill_cooldowns: HashMap<u32, Vec<CastEvent>>,
+    /// Gate name from the raid map, e.g. `Valtan G1`.
+    pub gate: Option<String>,
+}
+
+pub struct GetRaidRunsArgs {
+    pub page: i32,
+    pub page_size: i32,
+}
+
+pub struct GetAttemptHistoryArgs {
+    pub boss_name: String,
+    pub difficulty: String,
+}
+
+pub struct GetDeathRecapsArgs {
+    pub encounter_id: i32,
+    /// Only the deaths of this player.
+    pub name: Option<String>,
+}
+
+pub struct RaidRunEncounter {
+    pub id: i64,
+    pub gate: Option<String>,
+    pub boss_name: String,
+    pub duration: i64,
+    pub cleared: bool,
 }
 
 pub struct EncounterColumns;
@@ -58,6 +84,12 @@ impl EncounterColumns {
     pub const TOTAL_EFFECTIVE_SHIELDING: usize = 18;
     pub const APPLIED_SHIELD_BUFFS: usize = 19;
     pub const BOSS_HP_LOG: usize = 20;
+    pub const STAGGER_LOG: usize = 21;
+    pub const PHASES: usize = 22;
+    pub const PARENT_ID: usize = 23;
+    pub const ATTEMPT: usize = 24;
+    pub const WIPE: usize = 25;
+    pub const DEATH_RECAPS: usize = 26;
 }
 
 pub struct EncounterPreviewColumns;
@@ -78,6 +110,7 @@ impl EncounterPreviewColumns {
     pub const SUPPORT_BRAND: usize = 12;
     pub const SUPPORT_IDENTITY: usize = 13;
     pub const SUPPORT_HYPER: usize = 14;
+    pub const GATE: usize = 15;
 }
 
 pub struct EncounterEntityColumns;
@@ -102,5 +135,6 @@ impl EncounterEntityColumns {
     pub const ARK_PASSIVE_DATA: usize = 17;
     pub const LOADOUT_HASH: usize = 18;
     pub const COMBAT_POWER: usize = 19;
-    pub const CURRENT_SHIELD: usize = 20;
+    pub const SPEC_CONFIDENCE: usize = 20;
+    pub const CURRENT_SHIELD: usize = 21;
 }
\ No newline at end of file
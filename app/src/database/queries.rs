@@ -20,7 +20,13 @@ SELECT
     total_shielding,
     total_effective_shielding,
     applied_shield_buffs,
-    boss_hp_log
+    boss_hp_log,
+    stagger_log,
+    phases,
+    parent_id,
+    attempt,
+    wipe,
+    death_recaps
 FROM encounter
 JOIN encounter_preview
     USING (id)
@@ -48,7 +54,8 @@ SELECT
     ark_passive_active,
     ark_passive_data,
     loadout_hash,
-    combat_power
+    combat_power,
+    spec_confidence
 FROM entity
 WHERE encounter_id = ?;
 ";
@@ -69,10 +76,55 @@ INSERT INTO encounter
     applied_shield_buffs,
     misc,
     version,
-    boss_hp_log
+    boss_hp_log,
+    stagger_log,
+    phases,
+    parent_id,
+    raid_run_id,
+    gate,
+    attempt,
+    boss_hp,
+    wipe,
+    death_recaps
 )
 VALUES
-(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)";
+(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)";
+
+pub const SELECT_NEXT_ATTEMPT: &str = r"
+SELECT COALESCE(MAX(attempt), 0) + 1
+FROM encounter
+JOIN encounter_preview
+    USING (id)
+WHERE current_boss = ?1 AND difficulty = ?2;
+";
+
+pub const SELECT_ATTEMPT_HISTORY: &str = r"
+SELECT
+    id,
+    attempt,
+    fight_start,
+    duration,
+    cleared,
+    boss_hp
+FROM encounter
+JOIN encounter_preview
+    USING (id)
+WHERE current_boss = ?1 AND difficulty = ?2
+ORDER BY fight_start, id;
+";
+
+pub const SELECT_CHILD_ENCOUNTER_IDS: &str = r"
+SELECT id
+FROM encounter
+WHERE parent_id = ?
+ORDER BY id;
+";
+
+pub const SELECT_DEATH_RECAPS: &str = r"
+SELECT death_recaps
+FROM encounter
+WHERE id = ?
+";
 
 pub const INSERT_ENTITY: &str = r"
 INSERT INTO entity (
@@ -100,10 +152,12 @@ INSERT INTO entity (
     support_ap,
     support_brand,
     support_identity,
-    support_hyper
+    support_hyper,
+    damage_dealt,
+    spec_confidence
 )
 VALUES
-(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25)";
+(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25, ?26, ?27)";
 
 pub const INSERT_ENCOUNTER_PREVIEW: &str = r"
 INSERT INTO encounter_preview
@@ -117,18 +171,114 @@ INSERT INTO encounter_preview
     local_player,
     my_dps,
     cleared,
-    boss_only_damage
+    boss_only_damage,
+    gate
     )
 VALUES
-(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)";
+(?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)";
 
+pub const SELECT_LATEST_RAID_RUN: &str = r"
+SELECT id
+FROM raid_run
+WHERE raid = ?1
+    AND difficulty = ?2
+    AND players = ?3
+    AND ended_on >= ?4
+ORDER BY ended_on DESC
+LIMIT 1;
+";
+
+pub const INSERT_RAID_RUN: &str = r"
+INSERT INTO raid_run
+(
+    raid,
+    difficulty,
+    players,
+    started_on,
+    ended_on
+)
+VALUES
+(?1, ?2, ?3, ?4, ?5)";
+
+pub const SELECT_RAID_RUN_GATES: &str = r"
+SELECT
+    id,
+    gate
+FROM encounter
+WHERE raid_run_id = ?
+ORDER BY last_combat_packet DESC, id DESC;
+";
+
+pub const UPDATE_RAID_RUN_ENDED_ON: &str = r"
+UPDATE raid_run
+SET ended_on = MAX(ended_on, ?2)
+WHERE id = ?1;
+";
+
+pub const SELECT_RAID_RUNS: &str = r"
+SELECT
+    id,
+    raid,
+    difficulty,
+    started_on,
+    ended_on
+FROM raid_run
+WHERE EXISTS (SELECT 1 FROM encounter WHERE raid_run_id = raid_run.id)
+ORDER BY started_on DESC
+LIMIT ? OFFSET ?;
+";
+
+pub const SELECT_RAID_RUN_COUNT: &str = r"
+SELECT COUNT(*)
+FROM raid_run
+WHERE EXISTS (SELECT 1 FROM encounter WHERE raid_run_id = raid_run.id);
+";
+
+pub const SELECT_RAID_RUN_ENCOUNTERS: &str = r"
+SELECT
+    id,
+    gate,
+    current_boss,
+    duration,
+    cleared
+FROM encounter
+JOIN encounter_preview
+    USING (id)
+WHERE raid_run_id = ?
+ORDER BY fight_start, id;
+";
+
+pub const SELECT_RAID_RUN_PLAYERS: &str = r"
+SELECT
+    entity.name,
+    MAX(entity.class_id),
+    SUM(COALESCE(entity.damage_dealt, entity.dps * (encounter_preview.duration / 1000))) AS damage_dealt,
+    SUM(encounter_preview.duration)
+FROM entity
+JOIN encounter
+    ON encounter.id = entity.encounter_id
+JOIN encounter_preview
+    ON encounter_preview.id = entity.encounter_id
+WHERE encounter.raid_run_id = ? AND entity.entity_type = 'PLAYER'
+GROUP BY entity.name
+ORDER BY damage_dealt DESC;
+";
+
+/// Wipes of a boss which was cleared, or has a favorite, are kept as its attempt history.
+/// Other bosses lose every attempt, so their numbering starts over without gaps.
 pub const DELETE_NOT_FAV_UNCLEARED_ENCOUNTERS: &str = r"
 DELETE
 FROM encounter
 WHERE id IN (
     SELECT id
-    FROM encounter_preview
-    WHERE cleared = 0 AND favorite = 0
+    FROM encounter_preview wipe
+    WHERE cleared = 0 AND NOT EXISTS (
+        SELECT 1
+        FROM encounter_preview other
+        WHERE other.current_boss = wipe.current_boss
+            AND other.difficulty IS wipe.difficulty
+            AND (other.cleared = 1 OR other.favorite = 1)
+    )
 )";
 
 pub const DELETE_UNCLEARED_ENCOUNTERS: &str = r"
@@ -136,8 +286,14 @@ DELETE
 FROM encounter
 WHERE id IN (
     SELECT id
-    FROM encounter_preview
-    WHERE cleared = 0
+    FROM encounter_preview wipe
+    WHERE cleared = 0 AND NOT EXISTS (
+        SELECT 1
+        FROM encounter_preview other
+        WHERE other.current_boss = wipe.current_boss
+            AND other.difficulty IS wipe.difficulty
+            AND other.cleared = 1
+    )
 )";
 
 pub const DELETE_UNFAVOURITE_ENCOUNTERS: &str = r"
@@ -3,12 +3,13 @@ use anyhow::{Ok, Result};
 use hashbrown::HashMap;
 use log::*;
 use r2d2_sqlite::SqliteConnectionManager;
-use rusqlite::{params, params_from_iter, OptionalExtension, Transaction};
+use rusqlite::{params, params_from_iter, OptionalExtension, Transaction, TransactionBehavior};
 use serde_json::json;
 
 pub const DB_VERSION: i32 = 5;
 
-use crate::{database::{models::*, queries::*, utils::*}, live::utils::compress_json, models::*};
+use crate::{database::{models::*, queries::*, sql_types::JsonColumn, utils::*}, live::{phase::{parse_gate, RAID_RUN_TIMEOUT_MS}, utils::compress_json}, models::*};
+#[derive(Clone)]
 pub struct Repository(r2d2::Pool<SqliteConnectionManager>);
 
 impl Repository {
@@ -205,8 +206,28 @@ impl Repository {
         let sync: Option<String> = statement.query_row(params![id], |row| row.get(0)).optional()?;
         encounter.sync = sync;
 
+        let mut statement = connection.prepare_cached(SELECT_CHILD_ENCOUNTER_IDS)?;
+        encounter.child_ids = statement
+            .query_map(params![id], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+
         encounter.entities = entities;
 
+        // encounters saved before the summary was persisted
+        if encounter.raid_summary.is_none() {
+            let party_info = encounter.encounter_damage_stats.misc
+                .as_ref()
+                .and_then(|misc| misc.party_info.as_ref())
+                .map(|party_info| {
+                    let mut parties: Vec<_> = party_info.iter().collect();
+                    parties.sort_by_key(|(party_id, _)| **party_id);
+                    parties.into_iter().map(|(_, members)| members.clone()).collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            encounter.raid_summary = compute_raid_summary(&encounter, &party_info);
+        }
+
         Ok(encounter)
     }
 
@@ -252,9 +273,12 @@ impl Repository {
 
     pub fn insert_data(&self, args: InsertEncounterArgs) -> Result<i64> {
         let mut connection = self.0.get()?;
-        let transaction = connection.transaction()?;
+        // saves run in parallel tasks, take the write lock up front so that a gate sees the one saved before it
+        let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
 
-        let last_insert_id = self.insert_encounter(&transaction, &args)?;
+        let raid_run_id = self.upsert_raid_run(&transaction, &args)?;
+        let parent_id = self.find_parent_gate(&transaction, &args, raid_run_id)?;
+        let last_insert_id = self.insert_encounter(&transaction, &args, raid_run_id, parent_id)?;
         self.insert_entities(&transaction, &args, last_insert_id)?;
         self.insert_encounter_preview(&transaction, &args, last_insert_id)?;
 
@@ -263,10 +287,189 @@ impl Repository {
         Ok(last_insert_id)
     }
 
+    pub fn get_raid_runs(&self, args: GetRaidRunsArgs) -> Result<RaidRunsOverview> {
+
+        let GetRaidRunsArgs {
+            page,
+            page_size
+        } = args;
+
+        let connection = self.0.get()?;
+        let offset = (page - 1) * page_size;
+
+        let mut statement = connection.prepare_cached(SELECT_RAID_RUNS)?;
+        let mut runs = statement
+            .query_map(params![page_size, offset], map_raid_run)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut encounters_statement = connection.prepare_cached(SELECT_RAID_RUN_ENCOUNTERS)?;
+        let mut players_statement = connection.prepare_cached(SELECT_RAID_RUN_PLAYERS)?;
+
+        for run in runs.iter_mut() {
+            let encounters = encounters_statement
+                .query_map(params![run.id], map_raid_run_encounter)?
+                .collect::<Result<Vec<_>, _>>()?;
+
+            run.gates = group_raid_run_gates(encounters);
+            run.clear_time = run.gates.iter().map(|gate| gate.clear_time).sum();
+            run.players = players_statement
+                .query_map(params![run.id], map_raid_run_player)?
+                .collect::<Result<_, _>>()?;
+        }
+
+        let total_runs = connection.query_row(SELECT_RAID_RUN_COUNT, [], |row| row.get(0))?;
+
+        Ok(RaidRunsOverview {
+            runs,
+            total_runs,
+        })
+    }
+
+    pub fn get_attempt_history(&self, args: GetAttemptHistoryArgs) -> Result<AttemptHistory> {
+
+        let GetAttemptHistoryArgs {
+            boss_name,
+            difficulty
+        } = args;
+
+        let connection = self.0.get()?;
+        let mut statement = connection.prepare_cached(SELECT_ATTEMPT_HISTORY)?;
+
+        let attempts = statement
+            .query_map(params![boss_name, difficulty], map_encounter_attempt)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let best_boss_hp = attempts
+            .iter()
+            .filter_map(|attempt| attempt.boss_hp)
+            .min_by(|a, b| a.total_cmp(b));
+        let first_clear = attempts
+            .iter()
+            .find(|attempt| attempt.cleared)
+            .map(|attempt| attempt.id);
+
+        Ok(AttemptHistory {
+            boss_name,
+            difficulty,
+            attempts,
+            best_boss_hp,
+            first_clear,
+        })
+    }
+
+    pub fn get_death_recaps(&self, args: GetDeathRecapsArgs) -> Result<Vec<DeathRecap>> {
+
+        let GetDeathRecapsArgs {
+            encounter_id,
+            name
+        } = args;
+
+        let connection = self.0.get()?;
+        let mut statement = connection.prepare_cached(SELECT_DEATH_RECAPS)?;
+
+        let death_recaps: Option<JsonColumn<Vec<DeathRecap>>> = statement
+            .query_row(params![encounter_id], |row| row.get(0))
+            .optional()?
+            .flatten();
+
+        let mut death_recaps = death_recaps
+            .map(|JsonColumn(death_recaps)| death_recaps)
+            .unwrap_or_default();
+
+        if let Some(name) = name {
+            death_recaps.retain(|recap| recap.name == name);
+        }
+
+        Ok(death_recaps)
+    }
+
+    /// Finds the raid run the encounter belongs to or starts a new one.
+    ///
+    /// Gates of the same raid and difficulty, done by the same players within
+    /// [`RAID_RUN_TIMEOUT_MS`] of each other, belong to one run.
+    fn upsert_raid_run(
+        &self,
+        transaction: &Transaction,
+        args: &InsertEncounterArgs,
+    ) -> Result<Option<i64>> {
+        let InsertEncounterArgs {
+            encounter,
+            raid_difficulty,
+            gate,
+            ..
+        } = args;
+
+        let Some((raid, _)) = gate.as_deref().and_then(parse_gate) else {
+            return Ok(None);
+        };
+
+        let players = raid_run_players(encounter);
+        let params = params![
+            raid,
+            raid_difficulty,
+            players,
+            encounter.fight_start - RAID_RUN_TIMEOUT_MS
+        ];
+        let raid_run_id: Option<i64> = transaction
+            .prepare_cached(SELECT_LATEST_RAID_RUN)?
+            .query_row(params, |row| row.get(0))
+            .optional()?;
+
+        let raid_run_id = match raid_run_id {
+            Some(id) => {
+                let params = params![id, encounter.last_combat_packet];
+                transaction.prepare_cached(UPDATE_RAID_RUN_ENDED_ON)?.execute(params)?;
+                id
+            }
+            None => {
+                let params = params![
+                    raid,
+                    raid_difficulty,
+                    players,
+                    encounter.fight_start,
+                    encounter.last_combat_packet
+                ];
+                transaction.prepare_cached(INSERT_RAID_RUN)?.execute(params)?;
+                transaction.last_insert_rowid()
+            }
+        };
+
+        Ok(Some(raid_run_id))
+    }
+
+    /// Latest encounter of the raid run with a lower gate, e.g. `Valtan G1` for `Valtan G2`.
+    ///
+    /// Retries of a gate therefore share the parent of the first attempt.
+    fn find_parent_gate(
+        &self,
+        transaction: &Transaction,
+        args: &InsertEncounterArgs,
+        raid_run_id: Option<i64>,
+    ) -> Result<Option<i64>> {
+        let (Some(raid_run_id), Some((_, gate))) = (raid_run_id, args.gate.as_deref().and_then(parse_gate)) else {
+            return Ok(None);
+        };
+
+        let mut statement = transaction.prepare_cached(SELECT_RAID_RUN_GATES)?;
+        let rows = statement.query_map(params![raid_run_id], map_raid_run_gate)?;
+
+        for row in rows {
+            let (id, gate_name) = row?;
+
+            if gate_name.as_deref().and_then(parse_gate).is_some_and(|(_, previous)| previous < gate) {
+                return Ok(Some(id));
+            }
+        }
+
+        Ok(None)
+    }
+
     fn insert_encounter(
         &self,
         transaction: &rusqlite::Transaction,
         args: &InsertEncounterArgs,
+        raid_run_id: Option<i64>,
+        parent_id: Option<i64>,
     ) -> Result<i64> {
         let InsertEncounterArgs {
             encounter,
@@ -277,6 +480,8 @@ impl Repository {
             rdps_valid,
             ntp_fight_start,
             region,
+            gate,
+            raid_difficulty,
             ..
         } = args;
 
@@ -304,9 +509,19 @@ impl Repository {
             rdps_message: if *rdps_valid { None } else { Some("invalid_stats".into()) },
             ntp_fight_start: Some(*ntp_fight_start),
             manual_save: Some(args.manual),
+            raid_summary: compute_raid_summary(encounter, party_info),
             ..Default::default()
         };
 
+        let attempt: i32 = transaction
+            .prepare_cached(SELECT_NEXT_ATTEMPT)?
+            .query_row(params![encounter.current_boss_name, raid_difficulty], |row| row.get(0))?;
+        let boss_hp = if *raid_clear {
+            Some(0.0)
+        } else {
+            encounter.wipe.as_ref().and_then(|wipe| wipe.boss_hp)
+        };
+
         let params = params![
             encounter.last_combat_packet,
             stats.total_damage_dealt,
@@ -322,6 +537,15 @@ impl Repository {
             json!(misc),
             DB_VERSION,
             compress_json(boss_hp_log),
+            (!stats.stagger_stats.is_empty()).then(|| json!(stats.stagger_stats)),
+            (!stats.phases.is_empty()).then(|| json!(stats.phases)),
+            parent_id,
+            raid_run_id,
+            gate,
+            attempt,
+            boss_hp,
+            encounter.wipe.as_ref().map(|wipe| json!(wipe)),
+            (!encounter.death_recaps.is_empty()).then(|| json!(encounter.death_recaps)),
         ];
 
         let mut statement = transaction.prepare_cached(INSERT_ENCOUNTER)?;
@@ -367,6 +591,10 @@ impl Repository {
                 apply_player_info(&mut entity, info);
             }
 
+            if entity.entity_type == EntityType::Player {
+                apply_spec(&mut entity, &encounter.encounter_damage_stats.buffs);
+            }
+
             apply_cast_logs(&mut entity, cast_log, skill_cast_log);
 
             let compressed_skills = compress_json(&entity.skills);
@@ -400,6 +628,8 @@ impl Repository {
                 support.map(|b| b.brand),
                 support.map(|b| b.identity),
                 support.map(|b| b.hyper),
+                entity.damage_stats.damage_dealt,
+                entity.spec_confidence,
             ];
 
             statement.execute(params)?;
@@ -419,6 +649,7 @@ impl Repository {
             encounter,
             raid_clear,
             raid_difficulty,
+            gate,
             ..
         } = args;
 
@@ -451,6 +682,7 @@ impl Repository {
             local_player_dps,
             raid_clear,
             encounter.boss_only_damage,
+            gate,
         ];
 
         transaction.prepare_cached(INSERT_ENCOUNTER_PREVIEW)?.execute(params)?;
@@ -2,7 +2,7 @@ use std::collections::BTreeMap;
 use std::str::FromStr;
 use hashbrown::HashMap;
 
-use crate::data::GEM_SKILL_MAP;
+use crate::data::meter_data;
 use crate::database::models::*;
 use crate::database::sql_types::{CompressedJson, JsonColumn};
 use crate::api::InspectInfo;
@@ -103,7 +103,8 @@ pub fn prepare_get_encounter_preview_query(search: String, filter: SearchFilter)
     le.support_ap,      -- 11
     le.support_brand,   -- 12
     le.support_identity,-- 13
-    le.support_hyper    -- 14
+    le.support_hyper,   -- 14
+    e.gate              -- 15
     FROM encounter_preview e
     LEFT JOIN entity le ON le.encounter_id = e.id AND le.name = e.local_player
     {}
@@ -137,7 +138,7 @@ pub fn prepare_get_encounter_preview_query(search: String, filter: SearchFilter)
 pub fn map_encounter(row: &rusqlite::Row) -> rusqlite::Result<(Encounter, bool)> {
     
     let misc_str: String = row.get(EncounterColumns::MISC).unwrap_or_default();
-    let misc = serde_json::from_str::<EncounterMisc>(misc_str.as_str())
+    let mut misc = serde_json::from_str::<EncounterMisc>(misc_str.as_str())
         .map(Some)
         .unwrap_or_default();
 
@@ -171,6 +172,20 @@ pub fn map_encounter(row: &rusqlite::Row) -> rusqlite::Result<(Encounter, bool)>
         (boss_hp_log, buffs, debuffs, applied_shield_buffs)
     };
 
+    // persisted in misc only, moved so it is not sent twice
+    let raid_summary = misc.as_mut().and_then(|misc| misc.raid_summary.take());
+    let stagger_stats = row
+        .get::<_, Option<JsonColumn<HashMap<String, BossStagger>>>>(EncounterColumns::STAGGER_LOG)
+        .ok()
+        .flatten()
+        .map(|JsonColumn(stagger_stats)| stagger_stats)
+        .unwrap_or_default();
+    let phases = row
+        .get::<_, Option<JsonColumn<Vec<EncounterPhase>>>>(EncounterColumns::PHASES)
+        .ok()
+        .flatten()
+        .map(|JsonColumn(phases)| phases)
+        .unwrap_or_default();
     let total_shielding = row.get(EncounterColumns::TOTAL_SHIELDING).unwrap_or_default();
     let total_effective_shielding = row.get(EncounterColumns::TOTAL_EFFECTIVE_SHIELDING).unwrap_or_default();
     let encounter_damage_stats = EncounterDamageStats {
@@ -186,6 +201,8 @@ pub fn map_encounter(row: &rusqlite::Row) -> rusqlite::Result<(Encounter, bool)>
         total_effective_shielding,
         applied_shield_buffs,
         boss_hp_log,
+        stagger_stats,
+        phases,
         ..Default::default()
     };
 
@@ -200,6 +217,20 @@ pub fn map_encounter(row: &rusqlite::Row) -> rusqlite::Result<(Encounter, bool)>
         favorite: row.get(EncounterColumns::FAVORITE)?,
         cleared: row.get(EncounterColumns::CLEARED)?,
         boss_only_damage: row.get(EncounterColumns::BOSS_ONLY_DAMAGE)?,
+        raid_summary,
+        parent_id: row.get(EncounterColumns::PARENT_ID).unwrap_or_default(),
+        attempt: row.get(EncounterColumns::ATTEMPT).unwrap_or_default(),
+        wipe: row
+            .get::<_, Option<JsonColumn<WipeInfo>>>(EncounterColumns::WIPE)
+            .ok()
+            .flatten()
+            .map(|JsonColumn(wipe)| wipe),
+        death_recaps: row
+            .get::<_, Option<JsonColumn<Vec<DeathRecap>>>>(EncounterColumns::DEATH_RECAPS)
+            .ok()
+            .flatten()
+            .map(|JsonColumn(death_recaps)| death_recaps)
+            .unwrap_or_default(),
         ..Default::default()
     };
 
@@ -227,9 +258,103 @@ pub fn map_encounter_preview(row: &rusqlite::Row) -> rusqlite::Result<EncounterP
         support_brand: row.get(EncounterPreviewColumns::SUPPORT_BRAND).unwrap_or_default(),
         support_identity: row.get(EncounterPreviewColumns::SUPPORT_IDENTITY).unwrap_or_default(),
         support_hyper: row.get(EncounterPreviewColumns::SUPPORT_HYPER).unwrap_or_default(),
+        gate: row.get(EncounterPreviewColumns::GATE).unwrap_or_default(),
+    })
+}
+
+pub fn map_encounter_attempt(row: &rusqlite::Row) -> rusqlite::Result<EncounterAttempt> {
+    Ok(EncounterAttempt {
+        id: row.get(0)?,
+        attempt: row.get(1)?,
+        fight_start: row.get(2)?,
+        duration: row.get(3)?,
+        cleared: row.get::<_, Option<bool>>(4)?.unwrap_or_default(),
+        boss_hp: row.get(5)?,
+    })
+}
+
+pub fn map_raid_run(row: &rusqlite::Row) -> rusqlite::Result<RaidRun> {
+    Ok(RaidRun {
+        id: row.get(0)?,
+        raid: row.get(1)?,
+        difficulty: row.get(2)?,
+        started_on: row.get(3)?,
+        ended_on: row.get(4)?,
+        ..Default::default()
+    })
+}
+
+pub fn map_raid_run_encounter(row: &rusqlite::Row) -> rusqlite::Result<RaidRunEncounter> {
+    Ok(RaidRunEncounter {
+        id: row.get(0)?,
+        gate: row.get(1)?,
+        boss_name: row.get(2)?,
+        duration: row.get(3)?,
+        cleared: row.get::<_, Option<bool>>(4)?.unwrap_or_default(),
+    })
+}
+
+pub fn map_raid_run_gate(row: &rusqlite::Row) -> rusqlite::Result<(i64, Option<String>)> {
+    Ok((row.get(0)?, row.get(1)?))
+}
+
+pub fn map_raid_run_player(row: &rusqlite::Row) -> rusqlite::Result<RaidRunPlayer> {
+    let damage_dealt: i64 = row.get::<_, Option<i64>>(2)?.unwrap_or_default();
+    let duration: i64 = row.get::<_, Option<i64>>(3)?.unwrap_or_default();
+
+    Ok(RaidRunPlayer {
+        name: row.get(0)?,
+        class_id: row.get(1)?,
+        damage_dealt,
+        dps: damage_dealt / (duration / 1000).max(1),
     })
 }
 
+/// Groups the encounters of a raid run by gate, in the order the gates were first attempted.
+pub fn group_raid_run_gates(encounters: Vec<RaidRunEncounter>) -> Vec<RaidRunGate> {
+    let mut gates: Vec<RaidRunGate> = Vec::new();
+
+    for encounter in encounters {
+        let gate_name = encounter.gate.unwrap_or_else(|| encounter.boss_name.clone());
+
+        let idx = gates.iter().position(|gate| gate.gate == gate_name);
+        let gate = match idx {
+            Some(idx) => &mut gates[idx],
+            None => {
+                gates.push(RaidRunGate {
+                    gate: gate_name,
+                    ..Default::default()
+                });
+                gates.last_mut().unwrap()
+            }
+        };
+
+        gate.attempts += 1;
+        gate.boss_name = encounter.boss_name;
+        gate.encounter_ids.push(encounter.id);
+
+        if encounter.cleared {
+            gate.cleared = true;
+            gate.clear_time = encounter.duration;
+        } else {
+            gate.wipes += 1;
+        }
+    }
+
+    gates
+}
+
+/// Party composition of an encounter, used to tell raid runs apart.
+pub fn raid_run_players(encounter: &Encounter) -> String {
+    let mut players: Vec<_> = encounter.entities.values()
+        .filter(|e| e.entity_type == EntityType::Player && e.class_id != 0)
+        .map(|e| e.name.as_str())
+        .collect();
+
+    players.sort_unstable();
+    players.join(",")
+}
+
 pub fn map_entity(row: &rusqlite::Row, is_compressed: bool) -> rusqlite::Result<EncounterEntity> {
 
     let (skills, damage_stats) = if is_compressed {
@@ -271,6 +396,7 @@ pub fn map_entity(row: &rusqlite::Row, is_compressed: bool) -> rusqlite::Result<
         ark_passive_data,
         loadout_hash: row.get(EncounterEntityColumns::LOADOUT_HASH).unwrap_or_default(),
         combat_power: row.get(EncounterEntityColumns::COMBAT_POWER).unwrap_or_default(),
+        spec_confidence: row.get(EncounterEntityColumns::SPEC_CONFIDENCE).unwrap_or_default(),
         current_shield: row.get(EncounterEntityColumns::CURRENT_SHIELD).unwrap_or(0),
         ..Default::default()
     };
@@ -369,6 +495,83 @@ pub fn compute_support_buffs(
     buffs
 }
 
+/// Aggregates damage, rdps and support buffs per party, `party_info` order defines the party ids.
+pub fn compute_raid_summary(
+    encounter: &Encounter,
+    party_info: &[Vec<String>],
+) -> Option<RaidSummary> {
+    if party_info.is_empty() {
+        return None;
+    }
+
+    let duration_seconds = ((encounter.last_combat_packet - encounter.fight_start) / 1000).max(1);
+    let support_buffs = compute_support_buffs(encounter, party_info);
+
+    let mut summary = RaidSummary::default();
+
+    for (party_id, party) in party_info.iter().enumerate() {
+        let mut party_summary = PartySummary {
+            party_id: party_id as i32,
+            ..Default::default()
+        };
+
+        for entity in party.iter().filter_map(|name| encounter.entities.get(name)) {
+            let damage_stats = &entity.damage_stats;
+
+            party_summary.damage_dealt += damage_stats.damage_dealt;
+            party_summary.rdps_damage_given += damage_stats.rdps_damage_given;
+            party_summary.rdps_damage_received += damage_stats.rdps_damage_received;
+            party_summary.members.push(PartyMemberSummary {
+                name: entity.name.clone(),
+                class_id: entity.class_id,
+                damage_dealt: damage_stats.damage_dealt,
+                dps: damage_stats.damage_dealt / duration_seconds,
+                rdps_damage_given: damage_stats.rdps_damage_given,
+                rdps_damage_received: damage_stats.rdps_damage_received,
+                ..Default::default()
+            });
+
+            if let Some(buffs) = support_buffs.get(&entity.name) {
+                party_summary.support = Some(PartySupportSummary {
+                    name: entity.name.clone(),
+                    brand: buffs.brand,
+                    buff: buffs.buff,
+                    identity: buffs.identity,
+                    hyper: buffs.hyper,
+                });
+            }
+        }
+
+        party_summary.dps = party_summary.damage_dealt / duration_seconds;
+        summary.total_damage_dealt += party_summary.damage_dealt;
+        summary.rdps_damage_given += party_summary.rdps_damage_given;
+        summary.parties.push(party_summary);
+    }
+
+    summary.dps = summary.total_damage_dealt / duration_seconds;
+
+    let total_damage_dealt = summary.total_damage_dealt;
+
+    for party in summary.parties.iter_mut() {
+        party.damage_share = damage_share(party.damage_dealt, total_damage_dealt);
+
+        for member in party.members.iter_mut() {
+            member.party_damage_share = damage_share(member.damage_dealt, party.damage_dealt);
+            member.raid_damage_share = damage_share(member.damage_dealt, total_damage_dealt);
+        }
+    }
+
+    Some(summary)
+}
+
+fn damage_share(damage: i64, total: i64) -> f64 {
+    if total <= 0 {
+        return 0.0;
+    }
+
+    damage as f64 / total as f64
+}
+
 pub fn should_insert_entity(entity: &EncounterEntity, local_player: &str) -> bool {
     ((entity.entity_type == EntityType::Player && entity.class_id > 0)
         || entity.name == local_player
@@ -408,7 +611,7 @@ pub fn apply_player_info(
 ) {
     for gem in info.gems.iter().flatten() {
         let skill_ids = if matches!(gem.gem_type, 34 | 35 | 65 | 63 | 61) {
-            GEM_SKILL_MAP.get(&gem.skill_id).cloned().unwrap_or_default()
+            meter_data().gem_skill_map.get(&gem.skill_id).cloned().unwrap_or_default()
         } else {
             vec![gem.skill_id]
         };
@@ -438,29 +641,24 @@ pub fn apply_player_info(
     entity.ark_passive_data = info.ark_passive_data.clone();
     entity.loadout_hash = info.loadout_snapshot.clone();
     entity.combat_power = info.combat_power.as_ref().map(|c| c.score);
+}
 
-    // Set spec for special cases
-    if entity.class_id == 104 {
-        if let Some(engr) = &entity.engraving_data {
-            if engr.iter().any(|e| e == "Awakening" || e == "Drops of Ether") {
-                entity.spec = Some("Princess".to_string());
-            }
-        }
+/// Detects the spec again now that engravings and ark passive nodes are known,
+/// a spec detected live is only replaced by a more confident one.
+pub fn apply_spec(entity: &mut EncounterEntity, buffs: &HashMap<u32, StatusEffect>) {
+    // the enlightenment tree identifies the spec on its own
+    let skip_min_check = entity.ark_passive_data.is_some();
+    let detected = get_player_spec(entity, buffs, skip_min_check);
+
+    if detected.spec == "Unknown" {
+        return;
     }
 
-    // Fallback spec detection
-    if entity.spec.as_deref() == Some("Unknown") {
-        if let Some(tree) = info.ark_passive_data.as_ref() {
-            if let Some(enlightenment) = tree.enlightenment.as_ref() {
-                for node in enlightenment.iter() {
-                    let spec = get_spec_from_ark_passive(node);
-                    if spec != "Unknown" {
-                        entity.spec = Some(spec);
-                        break;
-                    }
-                }
-            }
-        }
+    let current = entity.spec_confidence.unwrap_or_default();
+
+    if entity.spec.is_none() || detected.confidence >= current {
+        entity.spec = Some(detected.spec);
+        entity.spec_confidence = Some(detected.confidence);
     }
 }
 
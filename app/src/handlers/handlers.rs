@@ -6,7 +6,7 @@ use window_vibrancy::{apply_blur, clear_blur};
 use crate::autostart::{AutoLaunch, AutoLaunchManager};
 use crate::constants::*;
 use crate::data::AssetPreloader;
-use crate::database::models::{GetEncounterPreviewArgs, InsertSyncLogsArgs};
+use crate::database::models::{GetAttemptHistoryArgs, GetDeathRecapsArgs, GetEncounterPreviewArgs, GetRaidRunsArgs, InsertSyncLogsArgs};
 use crate::database::{Database, Repository};
 use crate::ui::AppHandleExtensions;
 use crate::handlers::error::AppError;
@@ -19,8 +19,12 @@ use crate::updater::UpdateManager;
 pub fn generate_handlers() -> Box<dyn Fn(Invoke) -> bool + Send + Sync> {
     Box::new(generate_handler![
         load,
+        reload_meter_data,
         load_encounters_preview,
         load_encounter,
+        load_raid_runs,
+        load_attempt_history,
+        load_death_recaps,
         get_encounter_count,
         open_most_recent_encounter,
         delete_encounter,
@@ -56,13 +60,22 @@ pub fn generate_handlers() -> Box<dyn Fn(Invoke) -> bool + Send + Sync> {
 
 #[command]
 pub fn load(asset_preloader: State<AssetPreloader>, update_manager: State<UpdateManager>) -> Result<LoadResult, AppError> {
-    asset_preloader.wait().expect("could not load assets");
+    asset_preloader.wait().map_err(|err| AppError::Message(format!("{:#}", err)))?;
 
     Ok(LoadResult {
         update_status: update_manager.get_status()
     })
 }
 
+#[command(async)]
+pub fn reload_meter_data(asset_preloader: State<AssetPreloader>) -> Result<String, AppError> {
+    let version = asset_preloader
+        .reload()
+        .map_err(|err| AppError::Message(format!("{:#}", err)))?;
+
+    Ok(version)
+}
+
 #[command]
 pub fn load_encounters_preview(
     repository: State<Repository>,
@@ -92,6 +105,45 @@ pub fn load_encounter(repository: State<Repository>, id: String) -> Encounter {
     encounter
 }
 
+#[command(async)]
+pub fn load_raid_runs(repository: State<Repository>, page: i32, page_size: i32) -> RaidRunsOverview {
+
+    let args = GetRaidRunsArgs {
+        page,
+        page_size
+    };
+
+    let runs = repository.get_raid_runs(args).expect("could not get raid runs");
+
+    runs
+}
+
+#[command(async)]
+pub fn load_attempt_history(repository: State<Repository>, boss_name: String, difficulty: String) -> AttemptHistory {
+
+    let args = GetAttemptHistoryArgs {
+        boss_name,
+        difficulty
+    };
+
+    let history = repository.get_attempt_history(args).expect("could not get attempt history");
+
+    history
+}
+
+#[command(async)]
+pub fn load_death_recaps(repository: State<Repository>, id: i32, name: Option<String>) -> Vec<DeathRecap> {
+
+    let args = GetDeathRecapsArgs {
+        encounter_id: id,
+        name
+    };
+
+    let death_recaps = repository.get_death_recaps(args).expect("could not get death recaps");
+
+    death_recaps
+}
+
 #[command]
 pub fn get_sync_candidates(repository: State<Repository>, force_resync: bool) -> Vec<i32> {
 
@@ -1,8 +1,8 @@
 #[allow(unused_imports)]
 
 mod autostart;
-mod abstractions;
-mod live;
+pub mod abstractions;
+pub mod live;
 mod misc;
 mod context;
 mod constants;
@@ -11,14 +11,15 @@ pub mod database;
 mod handlers;
 mod setup;
 mod logger;
-mod settings;
+pub mod settings;
 mod shell;
 mod background;
-mod data;
+pub mod data;
+pub mod data_updater;
 mod updater;
 mod ui;
-mod api;
-mod local;
+pub mod api;
+pub mod local;
 
 use anyhow::Result;
 use tauri::Context;
@@ -29,6 +30,7 @@ use crate::context::AppContext;
 use crate::database::Database;
 use crate::handlers::generate_handlers;
 use crate::data::AssetPreloader;
+use crate::data_updater::prepare_packages;
 use crate::local::LocalPlayerRepository;
 use crate::logger::{setup_logger, setup_panic_hook};
 use crate::misc::load_windivert;
@@ -48,7 +50,13 @@ pub fn run() -> Result<()> {
     let auto_launch_manager = AutoLaunchManager::new(
         &package_info.name,
         &context.app_path.display().to_string());
-    let loader = AssetPreloader::new();
+    if let Err(err) = prepare_packages(&context.meter_data_packages_path, &context.meter_data_path) {
+        warn!("could not clean up meter-data packages: {:#}", err);
+    }
+    let loader = AssetPreloader::with_packages(
+        context.meter_data_path.clone(),
+        context.meter_data_packages_path.clone(),
+    );
     let settings_manager = SettingsManager::new(context.settings_path.clone())?;
     let database = Database::new(
         context.database_path.clone(),
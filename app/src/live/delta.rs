@@ -0,0 +1,273 @@
+use hashbrown::HashMap;
+use serde::Serialize;
+
+use crate::models::*;
+
+/// Changes since the previous `encounter-update` or `encounter-delta`.
+///
+/// `sequence` restarts at 1 after every full snapshot, a gap means the meter missed an update
+/// and should send an `encounter-resync-request`.
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EncounterDelta {
+    pub sequence: u64,
+    pub last_combat_packet: i64,
+    pub fight_start: i64,
+    pub duration: i64,
+    pub current_boss_name: String,
+    pub encounter_damage_stats: EncounterDamageStats,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raid_summary: Option<RaidSummary>,
+    /// Entities which changed, `skills` only holds the skills which changed.
+    pub entities: Vec<EncounterEntity>,
+    pub removed: Vec<String>,
+}
+
+impl EncounterDelta {
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Counters which move whenever an entity is updated by the encounter state.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct EntityFingerprint {
+    current_hp: i64,
+    max_hp: i64,
+    current_shield: u64,
+    is_dead: bool,
+    class_id: u32,
+    gear_score: u32,
+    spec: Option<String>,
+    damage_dealt: i64,
+    damage_taken: i64,
+    deaths: i64,
+    shields_given: u64,
+    shields_received: u64,
+    damage_absorbed: u64,
+    damage_absorbed_on_others: u64,
+    rdps_damage_received: i64,
+    rdps_damage_given: i64,
+    incapacitations: usize,
+    casts: i64,
+    hits: i64,
+    counters: i64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct SkillFingerprint {
+    casts: i64,
+    hits: i64,
+    total_damage: i64,
+    rdps_damage_received: i64,
+    rdps_damage_given: i64,
+    skill_casts: usize,
+    time_available: Option<i64>,
+    cooldown_casts: Option<i64>,
+    max_casts: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+struct SentEntity {
+    fingerprint: EntityFingerprint,
+    skills: HashMap<u32, SkillFingerprint>,
+}
+
+/// Remembers what the meter has received so only the entities and skills which changed are cloned.
+#[derive(Debug, Default)]
+pub struct DeltaTracker {
+    has_snapshot: bool,
+    fight_start: i64,
+    sequence: u64,
+    sent: HashMap<String, SentEntity>,
+}
+
+impl DeltaTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A delta can't be applied when the meter hasn't seen this encounter yet.
+    pub fn needs_snapshot(&self, encounter: &Encounter) -> bool {
+        !self.has_snapshot || self.fight_start != encounter.fight_start
+    }
+
+    pub fn invalidate(&mut self) {
+        self.has_snapshot = false;
+    }
+
+    /// Records the state sent with a full `encounter-update`.
+    pub fn snapshot(&mut self, encounter: &Encounter) {
+        self.has_snapshot = true;
+        self.fight_start = encounter.fight_start;
+        self.sequence = 0;
+        self.sent = encounter
+            .entities
+            .iter()
+            .filter(|(_, entity)| should_send_entity(entity))
+            .map(|(name, entity)| (name.clone(), SentEntity::new(entity)))
+            .collect();
+    }
+
+    pub fn delta(&mut self, encounter: &Encounter) -> EncounterDelta {
+        let mut entities = Vec::new();
+
+        for (name, entity) in encounter.entities.iter() {
+            if !should_send_entity(entity) {
+                continue;
+            }
+
+            let fingerprint = EntityFingerprint::new(entity);
+
+            match self.sent.get_mut(name) {
+                Some(sent) => {
+                    if sent.fingerprint == fingerprint {
+                        continue;
+                    }
+
+                    let changed_skills: Vec<u32> = entity
+                        .skills
+                        .iter()
+                        .filter(|(id, skill)| {
+                            sent.skills.get(*id) != Some(&SkillFingerprint::new(skill))
+                        })
+                        .map(|(id, _)| *id)
+                        .collect();
+
+                    for id in changed_skills.iter() {
+                        sent.skills.insert(*id, SkillFingerprint::new(&entity.skills[id]));
+                    }
+                    sent.fingerprint = fingerprint;
+
+                    entities.push(clone_with_skills(entity, &changed_skills));
+                },
+                None => {
+                    self.sent.insert(name.clone(), SentEntity::new(entity));
+                    entities.push(entity.clone());
+                },
+            }
+        }
+
+        let mut removed = Vec::new();
+
+        self.sent.retain(|name, _| {
+            let keep = encounter.entities.get(name).is_some_and(should_send_entity);
+            if !keep {
+                removed.push(name.clone());
+            }
+            keep
+        });
+
+        if !entities.is_empty() || !removed.is_empty() {
+            self.sequence += 1;
+        }
+
+        EncounterDelta {
+            sequence: self.sequence,
+            last_combat_packet: encounter.last_combat_packet,
+            fight_start: encounter.fight_start,
+            duration: encounter.duration,
+            current_boss_name: encounter.current_boss_name.clone(),
+            encounter_damage_stats: encounter.encounter_damage_stats.clone(),
+            raid_summary: None,
+            entities,
+            removed,
+        }
+    }
+}
+
+/// Entities shown by the meter.
+pub fn should_send_entity(entity: &EncounterEntity) -> bool {
+    ((entity.entity_type == EntityType::Player && entity.class_id > 0)
+        || entity.entity_type == EntityType::Esther
+        || entity.entity_type == EntityType::Boss)
+        && entity.damage_stats.damage_dealt > 0
+}
+
+impl SentEntity {
+    fn new(entity: &EncounterEntity) -> Self {
+        Self {
+            fingerprint: EntityFingerprint::new(entity),
+            skills: entity
+                .skills
+                .iter()
+                .map(|(id, skill)| (*id, SkillFingerprint::new(skill)))
+                .collect(),
+        }
+    }
+}
+
+impl EntityFingerprint {
+    fn new(entity: &EncounterEntity) -> Self {
+        let damage_stats = &entity.damage_stats;
+
+        Self {
+            current_hp: entity.current_hp,
+            max_hp: entity.max_hp,
+            current_shield: entity.current_shield,
+            is_dead: entity.is_dead,
+            class_id: entity.class_id,
+            gear_score: entity.gear_score.to_bits(),
+            spec: entity.spec.clone(),
+            damage_dealt: damage_stats.damage_dealt,
+            damage_taken: damage_stats.damage_taken,
+            deaths: damage_stats.deaths,
+            shields_given: damage_stats.shields_given,
+            shields_received: damage_stats.shields_received,
+            damage_absorbed: damage_stats.damage_absorbed,
+            damage_absorbed_on_others: damage_stats.damage_absorbed_on_others,
+            rdps_damage_received: damage_stats.rdps_damage_received,
+            rdps_damage_given: damage_stats.rdps_damage_given,
+            incapacitations: damage_stats.incapacitations.len(),
+            casts: entity.skill_stats.casts,
+            hits: entity.skill_stats.hits,
+            counters: entity.skill_stats.counters,
+        }
+    }
+}
+
+impl SkillFingerprint {
+    fn new(skill: &Skill) -> Self {
+        Self {
+            casts: skill.casts,
+            hits: skill.hits,
+            total_damage: skill.total_damage,
+            rdps_damage_received: skill.rdps_damage_received,
+            rdps_damage_given: skill.rdps_damage_given,
+            skill_casts: skill.skill_cast_log.len(),
+            time_available: skill.time_available,
+            cooldown_casts: skill.cooldown_efficiency.as_ref().map(|efficiency| efficiency.casts),
+            max_casts: skill.cooldown_efficiency.as_ref().map(|efficiency| efficiency.max_casts),
+        }
+    }
+}
+
+fn clone_with_skills(entity: &EncounterEntity, skill_ids: &[u32]) -> EncounterEntity {
+    EncounterEntity {
+        id: entity.id,
+        character_id: entity.character_id,
+        npc_id: entity.npc_id,
+        name: entity.name.clone(),
+        entity_type: entity.entity_type,
+        class_id: entity.class_id,
+        class: entity.class.clone(),
+        gear_score: entity.gear_score,
+        current_hp: entity.current_hp,
+        max_hp: entity.max_hp,
+        current_shield: entity.current_shield,
+        is_dead: entity.is_dead,
+        skills: skill_ids
+            .iter()
+            .map(|id| (*id, entity.skills[id].clone()))
+            .collect(),
+        damage_stats: entity.damage_stats.clone(),
+        skill_stats: entity.skill_stats.clone(),
+        engraving_data: entity.engraving_data.clone(),
+        ark_passive_active: entity.ark_passive_active,
+        ark_passive_data: entity.ark_passive_data.clone(),
+        spec: entity.spec.clone(),
+        spec_confidence: entity.spec_confidence,
+        loadout_hash: entity.loadout_hash.clone(),
+        combat_power: entity.combat_power,
+    }
+}
@@ -1,26 +1,33 @@
+use crate::abstractions::EventEmitter;
 use crate::database::models::InsertEncounterArgs;
 use crate::database::Repository;
 use crate::data::*;
-use crate::live::entity_tracker::{Entity, EntityTracker};
 use crate::live::skill_tracker::{CastEvent, SkillTracker};
+use crate::live::phase::PhaseTracker;
+use crate::live::stagger::StaggerTracker;
 use crate::api::{GetCharacterInfoArgs, InspectInfo, StatsApi};
-use crate::live::status_tracker::StatusEffectDetails;
+use crate::live::store::entity::Entity;
+use crate::live::store::status::StatusEffectDetails;
 use crate::live::utils::*;
 use crate::models::*;
 use chrono::Utc;
 use hashbrown::HashMap;
 use log::{info, warn};
 use crate::abstractions::packets::common::SkillMoveOptionData;
+use crate::abstractions::packets::definitions::PKTIdentityGaugeChangeNotify;
 use crate::abstractions::packets::structures::SkillCooldownStruct;
 use rsntp::SntpClient;
+use crate::constants::DEATH_RECAP_WINDOW_MS;
 use std::cmp::max;
+use std::collections::VecDeque;
 use std::default::Default;
-use tauri::{AppHandle, Emitter, Manager};
+use std::sync::Arc;
 use tokio::task;
 
-#[derive(Debug)]
 pub struct EncounterState {
-    pub app_handle: AppHandle,
+    pub emitter: Arc<dyn EventEmitter>,
+    pub repository: Arc<Repository>,
+    pub stats_api: Arc<dyn StatsApi>,
     pub version: String,
     pub client_id: String,
     pub encounter: Encounter,
@@ -34,10 +41,18 @@ pub struct EncounterState {
     cast_log: HashMap<String, HashMap<u32, Vec<i32>>>,
 
     boss_hp_log: HashMap<String, Vec<BossHpLog>>,
+    stagger_tracker: StaggerTracker,
+    phase_tracker: PhaseTracker,
+    identity_log: HashMap<String, IdentityLog>,
+    stance_log: HashMap<String, Vec<(i64, u8)>>,
+    /// Recent npc hits on each player, the last one is blamed when the player dies.
+    incoming_hits: HashMap<String, VecDeque<DeathRecapHit>>,
+    death_recaps: Vec<DeathRecap>,
 
     pub party_info: Vec<Vec<String>>,
     pub raid_difficulty: String,
     pub raid_difficulty_id: u32,
+    pub zone_id: Option<u32>,
     pub boss_only_damage: bool,
     pub region: Option<String>,
 
@@ -51,17 +66,23 @@ pub struct EncounterState {
     custom_id_map: HashMap<u32, u32>,
 
     pub damage_is_valid: bool,
+
+    save_tasks: Vec<task::JoinHandle<()>>,
 }
 
 impl EncounterState {
     pub fn new(
         version: String,
         client_id: String,
-        app_handle: AppHandle) -> EncounterState {
+        emitter: Arc<dyn EventEmitter>,
+        repository: Arc<Repository>,
+        stats_api: Arc<dyn StatsApi>) -> EncounterState {
         EncounterState {
             version,
             client_id,
-            app_handle,
+            emitter,
+            repository,
+            stats_api,
             encounter: Encounter::default(),
             resetting: false,
             raid_clear: false,
@@ -70,11 +91,18 @@ impl EncounterState {
 
             damage_log: HashMap::new(),
             boss_hp_log: HashMap::new(),
+            stagger_tracker: StaggerTracker::new(),
+            phase_tracker: PhaseTracker::new(),
+            identity_log: HashMap::new(),
+            stance_log: HashMap::new(),
+            incoming_hits: HashMap::new(),
+            death_recaps: Vec::new(),
             cast_log: HashMap::new(),
 
             party_info: Vec::new(),
             raid_difficulty: "".to_string(),
             raid_difficulty_id: 0,
+            zone_id: None,
             boss_only_damage: false,
             region: None,
 
@@ -89,6 +117,8 @@ impl EncounterState {
             custom_id_map: HashMap::new(),
 
             damage_is_valid: true,
+
+            save_tasks: Vec::new(),
         }
     }
 
@@ -106,6 +136,17 @@ impl EncounterState {
         self.damage_log = HashMap::new();
         self.cast_log = HashMap::new();
         self.boss_hp_log = HashMap::new();
+        self.stagger_tracker = StaggerTracker::new();
+        self.phase_tracker = PhaseTracker::new();
+        self.identity_log = HashMap::new();
+        self.incoming_hits = HashMap::new();
+        self.death_recaps = Vec::new();
+        // the stance carries over to the next encounter
+        for log in self.stance_log.values_mut() {
+            if let Some(last) = log.pop() {
+                *log = vec![last];
+            }
+        }
         self.party_info = Vec::new();
 
         self.ntp_fight_start = 0;
@@ -197,7 +238,7 @@ impl EncounterState {
             e.name == self.encounter.local_player || e.damage_stats.damage_dealt > 0
         });
 
-        self.app_handle
+        self.emitter
             .emit("zone-change", "")
             .expect("failed to emit zone-change");
 
@@ -205,7 +246,7 @@ impl EncounterState {
     }
 
     pub fn on_phase_transition(&mut self, phase_code: i32) {
-        self.app_handle
+        self.emitter
             .emit("phase-transition", phase_code)
             .expect("failed to emit phase-transition");
 
@@ -238,11 +279,11 @@ impl EncounterState {
             .entry(entity.name.clone())
             .and_modify(|player| {
                 player.id = entity.id;
-                player.gear_score = entity.gear_level;
+                player.gear_score = entity.gear_level();
                 player.current_hp = hp;
                 player.max_hp = max_hp;
-                if entity.character_id > 0 {
-                    player.character_id = entity.character_id;
+                if entity.character_id() > 0 {
+                    player.character_id = entity.character_id();
                 }
             })
             .or_insert_with(|| {
@@ -255,21 +296,21 @@ impl EncounterState {
 
     // add or update npc to encounter
     // we set current boss if npc matches criteria
-    pub fn on_new_npc(&mut self, entity: Entity, hp: i64, max_hp: i64) {
+    pub fn on_new_npc(&mut self, entity: Entity, hp: i64, max_hp: i64, timestamp: i64) {
         let entity_name = entity.name.clone();
         self.encounter
             .entities
             .entry(entity_name.clone())
             .and_modify(|e| {
-                if entity.entity_type != EntityType::Boss && e.entity_type != EntityType::Boss {
-                    e.npc_id = entity.npc_id;
+                if !entity.is_boss() && e.entity_type != EntityType::Boss {
+                    e.npc_id = entity.npc_id();
                     e.id = entity.id;
                     e.current_hp = hp;
                     e.max_hp = max_hp;
-                } else if entity.entity_type == EntityType::Boss && e.entity_type == EntityType::Npc
+                } else if entity.is_boss() && e.entity_type == EntityType::Npc
                 {
                     e.entity_type = EntityType::Boss;
-                    e.npc_id = entity.npc_id;
+                    e.npc_id = entity.npc_id();
                     e.id = entity.id;
                     e.current_hp = hp;
                     e.max_hp = max_hp;
@@ -282,6 +323,8 @@ impl EncounterState {
                 npc
             });
 
+        let previous_boss = self.encounter.current_boss_name.clone();
+
         if let Some(npc) = self.encounter.entities.get(&entity_name) {
             if npc.entity_type == EntityType::Boss {
                 // if current encounter has no boss, we set the boss
@@ -298,25 +341,45 @@ impl EncounterState {
                 };
             }
         }
+
+        if self.encounter.fight_start != 0
+            && !previous_boss.is_empty()
+            && previous_boss != self.encounter.current_boss_name
+        {
+            self.phase_tracker.split(
+                PhaseReason::BossSwap,
+                &self.encounter.current_boss_name,
+                timestamp,
+                self.encounter.fight_start,
+            );
+        }
     }
 
-    pub fn on_death(&mut self, dead_entity: &Entity) {
+    pub fn on_boss_battle_status(&mut self, timestamp: i64) {
+        self.phase_tracker.split(
+            PhaseReason::BattleStatus,
+            &self.encounter.current_boss_name,
+            timestamp,
+            self.encounter.fight_start,
+        );
+    }
+
+    pub fn on_death(&mut self, dead_entity: &Entity, status_effects: Vec<StatusEffectDetails>) {
         let entity = self
             .encounter
             .entities
             .entry(dead_entity.name.clone())
             .or_insert_with(|| encounter_entity_from_entity(dead_entity));
 
-        if (dead_entity.entity_type != EntityType::Player
-            && dead_entity.entity_type != EntityType::Boss)
+        if !(dead_entity.is_player() || dead_entity.is_boss())
             || entity.id != dead_entity.id
-            || (entity.entity_type == EntityType::Boss && entity.npc_id != dead_entity.npc_id)
+            || (entity.entity_type == EntityType::Boss && entity.npc_id != dead_entity.npc_id())
         {
             return;
         }
 
         if entity.entity_type == EntityType::Boss
-            && dead_entity.entity_type == EntityType::Boss
+            && dead_entity.is_boss()
             && entity.name == self.encounter.current_boss_name
             && !entity.is_dead
         {
@@ -337,6 +400,40 @@ impl EncounterState {
                 // cap duration to death time if it exceeds it
                 x.duration = x.timestamp - entity.damage_stats.death_time;
             });
+
+        if entity.entity_type == EntityType::Player {
+            let recap = DeathRecap {
+                time: entity.damage_stats.death_time - self.encounter.fight_start,
+                name: entity.name.clone(),
+                class_id: entity.class_id,
+                ..Default::default()
+            };
+            self.on_player_killed(recap, &status_effects);
+        }
+    }
+
+    fn on_player_killed(&mut self, mut recap: DeathRecap, status_effects: &[StatusEffectDetails]) {
+        let hits = self.incoming_hits.remove(&recap.name).unwrap_or_default();
+
+        if let Some(last_hit) = hits.back() {
+            add_killing_blow(&mut self.encounter.entities, &recap.name, last_hit);
+        }
+
+        if self.encounter.fight_start == 0 {
+            return;
+        }
+
+        let now = Utc::now();
+        recap.hits = hits
+            .into_iter()
+            .filter(|hit| hit.time >= recap.time - DEATH_RECAP_WINDOW_MS)
+            .collect();
+        recap.status_effects = status_effects
+            .iter()
+            .map(|status_effect| get_death_recap_status_effect(status_effect, now))
+            .collect();
+
+        self.death_recaps.push(recap);
     }
 
     pub fn on_skill_cooldown(&mut self, cooldown_struct: SkillCooldownStruct) {
@@ -388,6 +485,22 @@ impl EncounterState {
         // );
     }
 
+    /// Refreshes the cooldown stats of the local player before they are sent to the meter.
+    pub fn update_cooldown_efficiency(&mut self) {
+        if self.encounter.fight_start == 0 {
+            return;
+        }
+
+        if let Some(entity) = self.encounter.entities.get_mut(&self.encounter.local_player) {
+            apply_cooldown_efficiency(
+                entity,
+                &self.skill_tracker.skill_cooldowns,
+                self.encounter.fight_start,
+                self.encounter.last_combat_packet,
+            );
+        }
+    }
+
     pub fn on_skill_start(
         &mut self,
         source_entity: &Entity,
@@ -424,12 +537,11 @@ impl EncounterState {
                 entity
             });
 
-        if entity.class_id == 0
-            && source_entity.entity_type == EntityType::Player
-            && source_entity.class_id > 0
-        {
-            entity.class_id = source_entity.class_id;
-            entity.class = get_class_from_id(&source_entity.class_id);
+        if let Some(player) = source_entity.as_player() {
+            if entity.class_id == 0 && player.class_id > 0 {
+                entity.class_id = player.class_id;
+                entity.class = get_class_from_id(&player.class_id);
+            }
         }
 
         entity.is_dead = false;
@@ -478,7 +590,7 @@ impl EncounterState {
             .push(relative_timestamp);
 
         // if this is a getup skill and we have an ongoing abnormal move incapacitation, this will end it
-        if let Some(skill_data) = SKILL_DATA.get(&skill_id) {
+        if let Some(skill_data) = meter_data().skill.get(&skill_id) {
             if skill_data.skill_type == "getup" {
                 for ongoing_event in entity
                     .damage_stats
@@ -501,8 +613,9 @@ impl EncounterState {
         // set spec for supports to determine buff source
         if is_support_class(&entity.class_id) && entity.spec.is_none() {
             let spec = get_player_spec(entity, &self.encounter.encounter_damage_stats.buffs, true);
-            if spec != "Unknown" {
-                entity.spec = Some(spec);
+            if spec.spec != "Unknown" {
+                entity.spec = Some(spec.spec);
+                entity.spec_confidence = Some(spec.confidence);
             }
         }
 
@@ -519,7 +632,6 @@ impl EncounterState {
         se_on_source: Vec<StatusEffectDetails>,
         se_on_target: Vec<StatusEffectDetails>,
         _target_count: i32,
-        _entity_tracker: &EntityTracker,
         timestamp: i64,
     ) {
         let hit_flag = match damage_data.modifier & 0xf {
@@ -564,9 +676,12 @@ impl EncounterState {
         }
 
         let mut skill_effect_id = damage_data.skill_effect_id;
-        let is_battle_item = is_battle_item(&proj_entity.skill_effect_id, "attack");
-        if proj_entity.entity_type == EntityType::Projectile && is_battle_item {
-            skill_effect_id = proj_entity.skill_effect_id;
+        let projectile_effect_id = proj_entity
+            .as_projectile()
+            .map_or(0, |projectile| projectile.skill_effect_id);
+        let is_battle_item = is_battle_item(&projectile_effect_id, "attack");
+        if is_battle_item {
+            skill_effect_id = projectile_effect_id;
         }
 
         // ensure source entity exists in encounter
@@ -634,7 +749,7 @@ impl EncounterState {
             };
 
             self.encounter.boss_only_damage = self.boss_only_damage;
-            self.app_handle
+            self.emitter
                 .emit("raid-start", timestamp)
                 .expect("failed to emit raid-start");
         }
@@ -713,12 +828,55 @@ impl EncounterState {
 
         source_entity.damage_stats.damage_dealt += damage;
 
+        if let Some((_, stance)) = self.stance_log.get(&source_entity.name).and_then(|log| log.last()) {
+            *skill.damage_by_stance.entry(*stance).or_default() += damage;
+            *source_entity.damage_stats.damage_by_stance.entry(*stance).or_default() += damage;
+        }
+
         if is_hyper_awakening {
             source_entity.damage_stats.hyper_awakening_damage += damage;
         }
 
         target_entity.damage_stats.damage_taken += damage;
 
+        if is_damage_taken_tracked(target_entity.entity_type, source_entity.entity_type, damage) {
+            add_damage_taken(
+                &mut target_entity.damage_stats.damage_taken_by_skill,
+                skill_key,
+                skill,
+                &source_entity.name,
+                damage,
+            );
+
+            if source_entity.entity_type == EntityType::Boss {
+                add_damage_taken(
+                    &mut source_entity.damage_stats.damage_dealt_by_skill,
+                    skill_key,
+                    skill,
+                    &source_entity.name,
+                    damage,
+                );
+            }
+
+            let hits = self.incoming_hits.entry(target_entity.name.clone()).or_default();
+            hits.push_back(DeathRecapHit {
+                time: relative_timestamp as i64,
+                source: source_entity.name.clone(),
+                skill_id: skill_key,
+                skill_name: skill.name.clone(),
+                damage,
+                shield_absorbed: damage_data.shield_damage.unwrap_or_default(),
+                current_hp: damage_data.target_current_hp,
+            });
+
+            while hits
+                .front()
+                .is_some_and(|hit| hit.time < relative_timestamp as i64 - DEATH_RECAP_WINDOW_MS)
+            {
+                hits.pop_front();
+            }
+        }
+
         source_entity.skill_stats.hits += 1;
         skill.hits += 1;
 
@@ -756,6 +914,14 @@ impl EncounterState {
                 .or_default()
                 .push((timestamp, damage));
 
+            self.phase_tracker.on_damage(
+                &self.encounter.current_boss_name,
+                &source_entity.name,
+                damage,
+                timestamp,
+                self.encounter.fight_start,
+            );
+
             let mut is_buffed_by_support = false;
             let mut is_buffed_by_identity = false;
             let mut is_debuffed_by_support = false;
@@ -968,7 +1134,8 @@ impl EncounterState {
                 .current_boss_name
                 .clone_from(&target_entity.name);
             target_entity.id = dmg_target_entity.id;
-            target_entity.npc_id = dmg_target_entity.npc_id;
+            target_entity.npc_id = dmg_target_entity.npc_id();
+            self.stagger_tracker.on_hit(&target_entity.name, &source_entity.name);
 
             let log = self
                 .boss_hp_log
@@ -980,6 +1147,13 @@ impl EncounterState {
             } else {
                 0
             };
+            self.phase_tracker.on_boss_hp(
+                &target_entity.name,
+                current_hp,
+                timestamp,
+                self.encounter.fight_start,
+            );
+
             let hp_percent = if target_entity.max_hp != 0 {
                 current_hp as f32 / target_entity.max_hp as f32
             } else {
@@ -1030,7 +1204,7 @@ impl EncounterState {
         movement: &SkillMoveOptionData,
         timestamp: i64,
     ) {
-        if victim_entity.entity_type != EntityType::Player {
+        if !victim_entity.is_player() {
             // we don't care about npc knockups
             return;
         }
@@ -1178,91 +1352,70 @@ impl EncounterState {
         }
     }
 
-    // pub fn on_identity_gain(&mut self, pkt: &PKTIdentityGaugeChangeNotify) {
-    //     if self.encounter.fight_start == 0 {
-    //         return;
-    //     }
-    //
-    //     if self.encounter.local_player.is_empty() {
-    //         if let Some((_, entity)) = self
-    //             .encounter
-    //             .entities
-    //             .iter()
-    //             .find(|(_, e)| e.id == pkt.player_id)
-    //         {
-    //             self.encounter.local_player.clone_from(&entity.name);
-    //         } else {
-    //             return;
-    //         }
-    //     }
-    //
-    //     if let Some(entity) = self
-    //         .encounter
-    //         .entities
-    //         .get_mut(&self.encounter.local_player)
-    //     {
-    //         self.identity_log
-    //             .entry(entity.name.clone())
-    //             .or_default()
-    //             .push((
-    //                 Utc::now().timestamp_millis(),
-    //                 (
-    //                     pkt.identity_gauge1,
-    //                     pkt.identity_gauge2,
-    //                     pkt.identity_gauge3,
-    //                 ),
-    //             ));
-    //     }
-    // }
-
-    // pub fn on_stagger_change(&mut self, pkt: &PKTParalyzationStateNotify) {
-    //     if self.encounter.current_boss_name.is_empty() || self.encounter.fight_start == 0 {
-    //         return;
-    //     }
-
-    //     if let Some(boss) = self
-    //         .encounter
-    //         .entities
-    //         .get_mut(&self.encounter.current_boss_name)
-    //     {
-    //         let timestamp = Utc::now().timestamp_millis();
-    //         let current_stagger = pkt.paralyzation_point as i32;
-    //         let max_stagger = pkt.paralyzation_max_point as i32;
-    //         if boss.id == pkt.object_id {
-    //             if current_stagger == max_stagger {
-    //                 let staggered_in =
-    //                     (timestamp - self.encounter.encounter_damage_stats.stagger_start) / 1000;
-    //                 self.stagger_intervals
-    //                     .push((staggered_in as i32, max_stagger))
-    //             } else if current_stagger != 0 && self.prev_stagger == 0 {
-    //                 self.encounter.encounter_damage_stats.stagger_start = timestamp;
-    //             }
-
-    //             self.prev_stagger = current_stagger;
-
-    //             let relative_timestamp_s = ((timestamp - self.encounter.fight_start) / 1000) as i32;
-    //             let stagger_percent = (1.0 - (current_stagger as f32 / max_stagger as f32)) * 100.0;
-    //             if let Some(last) = self.stagger_log.last_mut() {
-    //                 if last.0 == relative_timestamp_s {
-    //                     last.1 = stagger_percent;
-    //                 } else {
-    //                     self.stagger_log
-    //                         .push((relative_timestamp_s, stagger_percent));
-    //                 }
-    //             } else {
-    //                 self.stagger_log
-    //                     .push((relative_timestamp_s, stagger_percent));
-    //             }
-
-    //             if max_stagger > self.encounter.encounter_damage_stats.max_stagger {
-    //                 self.encounter.encounter_damage_stats.max_stagger = max_stagger;
-    //             }
-    //         }
-    //     }
-    // }
+    pub fn on_stance_change(&mut self, entity: &Entity, stance: u8) {
+        if !entity.is_player() {
+            return;
+        }
+
+        let log = self.stance_log.entry_ref(&entity.name).or_default();
+
+        if log.last().is_some_and(|(_, last)| *last == stance) {
+            return;
+        }
+
+        log.push((Utc::now().timestamp_millis(), stance));
+    }
+
+    pub fn on_identity_gain(&mut self, pkt: &PKTIdentityGaugeChangeNotify) {
+        if self.encounter.fight_start == 0 {
+            return;
+        }
+
+        if self.encounter.local_player.is_empty() {
+            if let Some((_, entity)) = self
+                .encounter
+                .entities
+                .iter()
+                .find(|(_, e)| e.id == pkt.player_id)
+            {
+                self.encounter.local_player.clone_from(&entity.name);
+            } else {
+                return;
+            }
+        }
+
+        if self.encounter.entities.contains_key(&self.encounter.local_player) {
+            self.identity_log
+                .entry_ref(&self.encounter.local_player)
+                .or_default()
+                .push((
+                    Utc::now().timestamp_millis(),
+                    (
+                        pkt.identity_gauge1,
+                        pkt.identity_gauge2,
+                        pkt.identity_gauge3,
+                    ),
+                ));
+        }
+    }
+
+    pub fn on_stagger_change(&mut self, boss: &Entity, current: i32, max: i32) {
+        if self.encounter.fight_start == 0 || !boss.is_boss() {
+            return;
+        }
+
+        let timestamp = Utc::now().timestamp_millis();
+        self.stagger_tracker.on_stagger_change(
+            &boss.name,
+            current,
+            max,
+            timestamp,
+            self.encounter.fight_start,
+        );
+    }
 
     pub fn on_boss_shield(&mut self, target_entity: &Entity, shield: u64) {
-        if target_entity.entity_type == EntityType::Boss
+        if target_entity.is_boss()
             && target_entity.name == self.encounter.current_boss_name
         {
             self.encounter
@@ -1281,9 +1434,7 @@ impl EncounterState {
         buff_id: u32,
         shield: u64,
     ) {
-        if source_entity.entity_type == EntityType::Player
-            && target_entity.entity_type == EntityType::Player
-        {
+        if source_entity.is_player() && target_entity.is_player() {
             if !self
                 .encounter
                 .encounter_damage_stats
@@ -1376,9 +1527,7 @@ impl EncounterState {
         buff_id: u32,
         shield_removed: u64,
     ) {
-        if source_entity.entity_type == EntityType::Player
-            && target_entity.entity_type == EntityType::Player
-        {
+        if source_entity.is_player() && target_entity.is_player() {
             self.encounter
                 .encounter_damage_stats
                 .total_effective_shielding += shield_removed;
@@ -1465,6 +1614,42 @@ impl EncounterState {
         }
 
         let mut encounter = self.encounter.clone();
+        encounter.encounter_damage_stats.stagger_stats = self.stagger_tracker.stats();
+
+        let phases = self.phase_tracker.phases(encounter.fight_start, encounter.last_combat_packet);
+        if phases.len() > 1 {
+            encounter.encounter_damage_stats.phases = phases;
+        }
+
+        for (name, log) in self.stance_log.iter() {
+            if let Some(entity) = encounter.entities.get_mut(name) {
+                entity.damage_stats.stance_stats = get_stance_stats(
+                    log,
+                    encounter.fight_start,
+                    encounter.last_combat_packet,
+                );
+            }
+        }
+
+        for (name, log) in self.identity_log.iter() {
+            if let Some(entity) = encounter.entities.get_mut(name) {
+                entity.skill_stats.identity_stats = get_identity_stats(
+                    entity.class_id,
+                    log,
+                    encounter.fight_start,
+                    encounter.last_combat_packet,
+                );
+            }
+        }
+
+        if let Some(entity) = encounter.entities.get_mut(&encounter.local_player) {
+            apply_cooldown_efficiency(
+                entity,
+                &self.skill_tracker.skill_cooldowns,
+                encounter.fight_start,
+                encounter.last_combat_packet,
+            );
+        }
 
         let damage_log = self.damage_log.clone();
         let cast_log = self.cast_log.clone();
@@ -1490,22 +1675,34 @@ impl EncounterState {
             raid_clear, self.raid_difficulty, encounter.current_boss_name
         );
 
+        encounter.death_recaps = self.death_recaps.clone();
+
+        if !self.raid_clear {
+            encounter.wipe = Some(WipeInfo {
+                boss_hp: get_boss_hp(&encounter, &self.boss_hp_log),
+            });
+        }
+
+        let gate_name = get_raid_gate(&encounter, self.zone_id, &self.raid_difficulty)
+            .map(|gate| gate.gate);
+
         encounter.current_boss_name = update_current_boss_name(&encounter.current_boss_name);
 
         let client_id = self.client_id.clone();
-        let app_handle = self.app_handle.clone();
-        task::spawn(async move {
-            let stats_api = app_handle.state::<Box<dyn StatsApi>>();
-
+        let emitter = self.emitter.clone();
+        let repository = self.repository.clone();
+        let stats_api = self.stats_api.clone();
+        self.save_tasks.retain(|save| !save.is_finished());
+        let save = task::spawn(async move {
             let player_info = fetch_player_info(
                 stats_api.as_ref(),
                 &encounter,
                 &version,
                 &client_id,
                 &raid_difficulty,
+                gate_name.as_deref(),
             ).await;
 
-            let repository = app_handle.state::<Repository>();
             let args = InsertEncounterArgs {
                 encounter,
                 damage_log,
@@ -1522,16 +1719,23 @@ impl EncounterState {
                 manual,
                 skill_cast_log,
                 skill_cooldowns,
+                gate: gate_name,
             };
 
             let encounter_id = repository.insert_data(args).expect("failed to save encounter");
             info!("saved to db");
 
             if raid_clear {
-                app_handle.emit("clear-encounter", encounter_id)
+                emitter.emit("clear-encounter", encounter_id)
                     .expect("failed to emit clear-encounter");
             }
         });
+        self.save_tasks.push(save);
+    }
+
+    /// Saves which may still be running, to be awaited before shutting down.
+    pub fn take_save_tasks(&mut self) -> Vec<task::JoinHandle<()>> {
+        std::mem::take(&mut self.save_tasks)
     }
 }
 
@@ -1546,6 +1750,7 @@ async fn fetch_player_info(
     version: &str,
     client_id: &str,
     raid_difficulty: &str,
+    gate_name: Option<&str>,
 ) -> Option<HashMap<String, InspectInfo>> {
 
     if raid_difficulty.is_empty()
@@ -1558,11 +1763,6 @@ async fn fetch_player_info(
 
     info!("fetching player info");
 
-    let raid_name = encounter
-        .entities
-        .get(&encounter.current_boss_name)
-        .and_then(|boss| boss_to_raid_map(&encounter.current_boss_name, boss.max_hp));
-
     let players: Vec<String> = encounter
         .entities
         .iter()
@@ -1583,7 +1783,7 @@ async fn fetch_player_info(
         client_id: client_id.to_string(),
         version: version.to_string(),
         region: encounter.region.clone().unwrap_or_default(),
-        raid_name: raid_name.unwrap_or_default(),
+        raid_name: gate_name.unwrap_or_default().to_string(),
         boss: encounter.current_boss_name.clone(),
         characters: players,
         difficulty: encounter.difficulty.clone(),
@@ -1,40 +1,30 @@
 use crate::abstractions::{DamageEncryptionHandler, RegionAcessor};
+use crate::data::meter_data;
 use crate::live::encounter_state::EncounterState;
-use crate::live::entity_tracker::{get_current_and_max_hp, EntityTracker};
-use crate::live::id_tracker::IdTracker;
-use crate::live::party_tracker::PartyTracker;
-use crate::live::status_tracker::{
-    get_status_effect_value, StatusEffectTargetType, StatusEffectType,
-    StatusTracker,
-};
-use crate::live::utils::{get_class_from_id, on_shield_change, parse_pkt, update_party};
+use crate::live::store::entity::get_current_and_max_hp;
+use crate::live::store::status::{get_status_effect_value, StatusEffectType};
+use crate::live::store::StateStore;
+use crate::live::utils::{get_class_from_id, on_shield_change, parse_pkt};
 use crate::local::{LocalInfo, LocalPlayer, LocalPlayerRepository};
 use crate::models::*;
 use chrono::Utc;
 use log::*;
 use crate::abstractions::packets::definitions::*;
 use crate::abstractions::packets::opcodes::Pkt;
-use std::cell::RefCell;
-use std::rc::Rc;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
 
 pub struct HandleArgs<'a, DH: DamageEncryptionHandler> {
     pub op: Pkt,
     pub data: &'a [u8],
     pub state: &'a mut EncounterState,
-    pub id_tracker: Rc<RefCell<IdTracker>>,
-    pub party_tracker: Rc<RefCell<PartyTracker>>,
-    pub status_tracker: Rc<RefCell<StatusTracker>>,
-    pub entity_tracker: &'a mut EntityTracker,
+    pub store: &'a mut StateStore,
     pub damage_handler: &'a mut DH,
     pub local_info: &'a mut LocalInfo,
     pub can_emit_details: bool,
     pub party_freeze: &'a mut bool,
-    pub app_handle: &'a AppHandle,
     pub party_cache: &'a mut Option<Vec<Vec<String>>>,
     pub raid_end_cd: &'a mut Instant,
-    pub local: &'a tauri::State<'a, LocalPlayerRepository>,
+    pub local: &'a LocalPlayerRepository,
     pub region_accessor: &'a Box<dyn RegionAcessor>,
 }
 
@@ -44,15 +34,11 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
         op,
         data,
         state,
-        id_tracker,
-        party_tracker,
-        status_tracker,
-        entity_tracker,
+        store,
         damage_handler,
         local_info,
         can_emit_details,
         party_freeze,
-        app_handle,
         party_cache,
         raid_end_cd,
         local,
@@ -64,16 +50,21 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
             if let Some(pkt) =
                 parse_pkt(&data, PKTCounterAttackNotify::new, "PKTCounterAttackNotify")
             {
-                if let Some(entity) = entity_tracker.entities.get(&pkt.source_id) {
+                if let Some(entity) = store.entities.get_entity_ref(pkt.source_id) {
                     state.on_counterattack(entity);
                 }
             }
         }
         Pkt::DeathNotify => {
             if let Some(pkt) = parse_pkt(&data, PKTDeathNotify::new, "PKTDeathNotify") {
-                if let Some(entity) = entity_tracker.entities.get(&pkt.target_id) {
-                    info!("death: {}, {}, {}", entity.name, entity.entity_type, entity.id);
-                    state.on_death(entity);
+                if let Some(entity) = store.entities.get_entity_ref(pkt.target_id) {
+                    info!("death: {}, {}, {}", entity.name, entity.entity_type(), entity.id);
+                    let status_effects = if entity.is_player() {
+                        store.statuses.get_active_status_effects(entity)
+                    } else {
+                        Vec::new()
+                    };
+                    state.on_death(entity, status_effects);
                 }
             }
         }
@@ -83,8 +74,10 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 PKTIdentityGaugeChangeNotify::new,
                 "PKTIdentityGaugeChangeNotify",
             ) {
+                state.on_identity_gain(&pkt);
+
                 if can_emit_details {
-                    app_handle.emit(
+                    state.emitter.emit(
                         "identity-update",
                         Identity {
                             gauge1: pkt.identity_gauge1,
@@ -95,19 +88,20 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 }
             }
         }
-        // Pkt::IdentityStanceChangeNotify => {
-        //     if let Some(pkt) = parse_pkt(
-        //         &data,
-        //         PKTIdentityStanceChangeNotify::new,
-        //         "PKTIdentityStanceChangeNotify",
-        //     ) {
-        //         if let Some(entity) = entity_tracker.entities.get_mut(&pkt.object_id) {
-        //             if entity.entity_type == EntityType::PLAYER {
-        //                 entity.stance = pkt.stance;
-        //             }
-        //         }
-        //     }
-        // }
+        Pkt::IdentityStanceChangeNotify => {
+            if let Some(pkt) = parse_pkt(
+                &data,
+                PKTIdentityStanceChangeNotify::new,
+                "PKTIdentityStanceChangeNotify",
+            ) {
+                if let Some(entity) = store.entities.entities.get_mut(&pkt.object_id) {
+                    if let Some(player) = entity.as_player_mut() {
+                        player.stance = pkt.stance;
+                    }
+                    state.on_stance_change(entity, pkt.stance);
+                }
+            }
+        }
         Pkt::InitEnv => {
             // three methods of getting local player info
             // 1. MigrationExecute    + InitEnv      + PartyInfo
@@ -116,12 +110,13 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
             // 3. InitPC
 
             if let Some(pkt) = parse_pkt(&data, PKTInitEnv::new, "PKTInitEnv") {
-                party_tracker.borrow_mut().reset_party_mappings();
+                store.parties.reset_party_mappings();
                 state.raid_difficulty = "".to_string();
                 state.raid_difficulty_id = 0;
+                state.zone_id = None;
                 state.damage_is_valid = true;
                 *party_cache = None;
-                let entity = entity_tracker.init_env(pkt);
+                let entity = store.init_env(pkt);
                 state.on_init_env(entity);
                 
                 if let Some(region) = region_accessor.get() {
@@ -135,19 +130,19 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
         Pkt::InitPC => {
             if let Some(pkt) = parse_pkt(&data, PKTInitPC::new, "PKTInitPC") {
                 let (hp, max_hp) = get_current_and_max_hp(&pkt.stat_pairs);
-                let entity = entity_tracker.init_pc(pkt);
+                let entity = store.init_pc(pkt);
                 info!(
                     "local player: {}, {}, {}, eid: {}, id: {}",
                     entity.name,
-                    get_class_from_id(&entity.class_id),
-                    entity.gear_level,
+                    get_class_from_id(&entity.class_id()),
+                    entity.gear_level(),
                     entity.id,
-                    entity.character_id
+                    entity.character_id()
                 );
 
                 local_info
                     .local_players
-                    .entry(entity.character_id)
+                    .entry(entity.character_id())
                     .and_modify(|e| {
                         e.name = entity.name.clone();
                         e.count += 1;
@@ -165,28 +160,28 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
         // Pkt::InitItem => {
         //     if let Some(pkt) = parse_pkt(&data, PKTInitItem::new, "PKTInitItem") {
         //         if pkt.storage_type == 1 || pkt.storage_type == 20 {
-        //             entity_tracker.get_local_player_set_options(pkt.item_data_list);
+        //             store.entities.get_local_player_set_options(pkt.item_data_list);
         //         }
         //     }
         // }
         // Pkt::MigrationExecute => {
         //     if let Some(pkt) = parse_pkt(&data, PKTMigrationExecute::new, "PKTMigrationExecute")
         //     {
-        //         entity_tracker.migration_execute(pkt);
+        //         store.entities.migration_execute(pkt);
         //         get_and_set_region(region_file_path.as_ref(), &mut state);
         //     }
         // }
         Pkt::NewPC => {
             if let Some(pkt) = parse_pkt(&data, PKTNewPC::new, "PKTNewPC") {
                 let (hp, max_hp) = get_current_and_max_hp(&pkt.pc_struct.stat_pairs);
-                let entity = entity_tracker.new_pc(pkt.pc_struct);
+                let entity = store.new_pc(pkt.pc_struct);
                 info!(
                     "new PC: {}, {}, {}, eid: {}, cid: {}",
                     entity.name,
-                    get_class_from_id(&entity.class_id),
-                    entity.gear_level,
+                    get_class_from_id(&entity.class_id()),
+                    entity.gear_level(),
                     entity.id,
-                    entity.character_id
+                    entity.character_id()
                 );
                 state.on_new_pc(entity, hp, max_hp);
             }
@@ -197,14 +192,14 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                     pkt.vehicle_struct.sub_p_k_t_new_vehicle_2_2_397.p_c_struct
                 {
                     let (hp, max_hp) = get_current_and_max_hp(&pc_struct.stat_pairs);
-                    let entity = entity_tracker.new_pc(pc_struct);
+                    let entity = store.new_pc(pc_struct);
                     info!(
                         "new PC from vehicle: {}, {}, {}, eid: {}, cid: {}",
                         entity.name,
-                        get_class_from_id(&entity.class_id),
-                        entity.gear_level,
+                        get_class_from_id(&entity.class_id()),
+                        entity.gear_level(),
                         entity.id,
-                        entity.character_id
+                        entity.character_id()
                     );
                     state.on_new_pc(entity, hp, max_hp);
                 }
@@ -213,29 +208,29 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
         Pkt::NewNpc => {
             if let Some(pkt) = parse_pkt(&data, PKTNewNpc::new, "PKTNewNpc") {
                 let (hp, max_hp) = get_current_and_max_hp(&pkt.npc_struct.stat_pairs);
-                let entity = entity_tracker.new_npc(pkt, max_hp);
+                let entity = store.new_npc(pkt, max_hp);
                 info!(
                     "new {}: {}, eid: {}, id: {}, hp: {}",
-                    entity.entity_type, entity.name, entity.id, entity.npc_id, max_hp
+                    entity.entity_type(), entity.name, entity.id, entity.npc_id(), max_hp
                 );
-                state.on_new_npc(entity, hp, max_hp);
+                state.on_new_npc(entity, hp, max_hp, state.encounter.last_combat_packet);
             }
         }
         Pkt::NewNpcSummon => {
             if let Some(pkt) = parse_pkt(&data, PKTNewNpcSummon::new, "PKTNewNpcSummon") {
                 let (hp, max_hp) = get_current_and_max_hp(&pkt.npc_struct.stat_pairs);
-                let entity = entity_tracker.new_npc_summon(pkt, max_hp);
+                let entity = store.new_npc_summon(pkt, max_hp);
                 info!(
                     "new {}: {}, eid: {}, id: {}, hp: {}",
-                    entity.entity_type, entity.name, entity.id, entity.npc_id, max_hp
+                    entity.entity_type(), entity.name, entity.id, entity.npc_id(), max_hp
                 );
-                state.on_new_npc(entity, hp, max_hp);
+                state.on_new_npc(entity, hp, max_hp, state.encounter.last_combat_packet);
             }
         }
         Pkt::NewProjectile => {
             if let Some(pkt) = parse_pkt(&data, PKTNewProjectile::new, "PKTNewProjectile") {
-                entity_tracker.new_projectile(&pkt);
-                if entity_tracker.id_is_player(pkt.projectile_info.owner_id)
+                store.entities.new_projectile(&pkt);
+                if store.entities.id_is_player(pkt.projectile_info.owner_id)
                     && pkt.projectile_info.skill_id > 0
                 {
                     let key = (pkt.projectile_info.owner_id, pkt.projectile_info.skill_id);
@@ -250,8 +245,8 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
         }
         Pkt::NewTrap => {
             if let Some(pkt) = parse_pkt(&data, PKTNewTrap::new, "PKTNewTrap") {
-                entity_tracker.new_trap(&pkt);
-                if entity_tracker.id_is_player(pkt.trap_struct.owner_id)
+                store.entities.new_trap(&pkt);
+                if store.entities.id_is_player(pkt.trap_struct.owner_id)
                     && pkt.trap_struct.skill_id > 0
                 {
                     let key = (pkt.trap_struct.owner_id, pkt.trap_struct.skill_id);
@@ -264,43 +259,42 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 }
             }
         }
-        // Pkt::ParalyzationStateNotify => {
-        //     if let Some(pkt) = parse_pkt(
-        //         &data,
-        //         PKTParalyzationStateNotify::new,
-        //         "PKTParalyzationStateNotify",
-        //     ) {
-        //         state.on_stagger_change(&pkt);
-        //         if emit_details.load(Ordering::Relaxed) {
-        //             window.emit(
-        //                 "stagger-update",
-        //                 Stagger {
-        //                     current: pkt.paralyzation_point,
-        //                     max: pkt.paralyzation_max_point,
-        //                 },
-        //             )?;
-        //         }
-        //     }
-        // }
+        Pkt::ParalyzationStateNotify => {
+            if let Some(pkt) = parse_pkt(
+                &data,
+                PKTParalyzationStateNotify::new,
+                "PKTParalyzationStateNotify",
+            ) {
+                if let Some(boss) = store.entities.get_entity_ref(pkt.object_id) {
+                    let current = pkt.paralyzation_point as i32;
+                    let max = pkt.paralyzation_max_point as i32;
+                    state.on_stagger_change(boss, current, max);
+
+                    if can_emit_details && boss.is_boss() {
+                        let stagger = Stagger {
+                            boss_name: boss.name.clone(),
+                            current,
+                            max,
+                        };
+
+                        if let Err(err) = state.emitter.emit("stagger-update", stagger) {
+                            warn!("failed to emit stagger-update: {:?}", err);
+                        }
+                    }
+                }
+            }
+        }
         Pkt::RaidBegin => {
             if let Some(pkt) = parse_pkt(&data, PKTRaidBegin::new, "PKTRaidBegin") {
                 info!("raid begin: {}", pkt.raid_id);
-                match pkt.raid_id {
-                    308226 | 308227 | 308239 | 308339 => {
-                        state.raid_difficulty = "Trial".to_string();
-                        state.raid_difficulty_id = 7;
-                    }
-                    308428 | 308429 | 308420 | 308410 | 308411 | 308414 | 308422 | 308424
-                    | 308421 | 308412 | 308423 | 308426 | 308416 | 308419 | 308415 | 308437
-                    | 308417 | 308418 | 308425 | 308430 => {
-                        state.raid_difficulty = "Challenge".to_string();
-                        state.raid_difficulty_id = 8;
-                    }
-                    _ => {
-                        state.raid_difficulty = "".to_string();
-                        state.raid_difficulty_id = 0;
-                    }
-                }
+                let difficulty = meter_data()
+                    .raid_difficulty
+                    .by_raid(pkt.raid_id)
+                    .cloned()
+                    .unwrap_or_default();
+
+                state.raid_difficulty = difficulty.name;
+                state.raid_difficulty_id = difficulty.id;
             }
         }
         Pkt::RaidBossKillNotify => {
@@ -313,7 +307,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
             state.party_info = if let Some(party) = party_cache.take() {
                 party
             } else {
-                update_party(&party_tracker, &entity_tracker)
+                store.party_members()
             };
             state.on_phase_transition(0);
             *raid_end_cd = Instant::now();
@@ -322,19 +316,16 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
         Pkt::RemoveObject => {
             if let Some(pkt) = parse_pkt(&data, PKTRemoveObject::new, "PKTRemoveObject") {
                 for upo in pkt.unpublished_objects {
-                    entity_tracker.entities.remove(&upo.object_id);
-                    status_tracker
-                        .borrow_mut()
-                        .remove_local_object(upo.object_id);
+                    store.remove_object(upo.object_id);
                 }
             }
         }
         Pkt::SkillCastNotify => {
             if let Some(pkt) = parse_pkt(&data, PKTSkillCastNotify::new, "PKTSkillCastNotify") {
-                let mut entity = entity_tracker.get_source_entity(pkt.source_id);
-                entity_tracker.guess_is_player(&mut entity, pkt.skill_id);
+                let mut entity = store.entities.get_source_entity(pkt.source_id);
+                store.entities.guess_is_player(&mut entity, pkt.skill_id);
                 // tracking arcana cards, bard major/minor chords
-                if entity.class_id == 202 || entity.class_id == 204 {
+                if entity.class_id() == 202 || entity.class_id() == 204 {
                     state.on_skill_start(
                         &entity,
                         pkt.skill_id,
@@ -354,8 +345,8 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
         Pkt::SkillStartNotify => {
             if let Some(pkt) = parse_pkt(&data, PKTSkillStartNotify::new, "PKTSkillStartNotify")
             {
-                let mut entity = entity_tracker.get_source_entity(pkt.source_id);
-                entity_tracker.guess_is_player(&mut entity, pkt.skill_id);
+                let mut entity = store.entities.get_source_entity(pkt.source_id);
+                store.entities.guess_is_player(&mut entity, pkt.skill_id);
                 let tripod_index =
                     pkt.skill_option_data
                         .tripod_index
@@ -368,7 +359,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 let (skill_id, summon_source) =
                     state.on_skill_start(&entity, pkt.skill_id, tripod_index, timestamp);
 
-                if entity.entity_type == EntityType::Player && skill_id > 0 {
+                if entity.is_player() && skill_id > 0 {
                     state
                         .skill_tracker
                         .new_cast(entity.id, skill_id, summon_source, timestamp);
@@ -389,10 +380,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 "PKTSkillDamageAbnormalMoveNotify",
             ) {
                 let now = Utc::now().timestamp_millis();
-                let owner = entity_tracker.get_source_entity(pkt.source_id);
-                let local_character_id = id_tracker
-                    .borrow()
-                    .get_local_character_id(entity_tracker.local_entity_id);
+                let owner = store.entities.get_source_entity(pkt.source_id);
                 let target_count = pkt.skill_damage_abnormal_move_events.len() as i32;
                 for mut event in pkt.skill_damage_abnormal_move_events.into_iter() {
                     if !damage_handler.decrypt_damage_event(&mut event.skill_damage_event) {
@@ -400,15 +388,14 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                         continue;
                     }
                     let target_entity =
-                        entity_tracker.get_or_create_entity(event.skill_damage_event.target_id);
-                    let source_entity = entity_tracker.get_or_create_entity(pkt.source_id);
+                        store.entities.get_or_create_entity(event.skill_damage_event.target_id);
+                    let source_entity = store.entities.get_or_create_entity(pkt.source_id);
 
                     // track potential knockdown
                     state.on_abnormal_move(&target_entity, &event.skill_move_option_data, now);
 
-                    let (se_on_source, se_on_target) = status_tracker
-                        .borrow_mut()
-                        .get_status_effects(&owner, &target_entity, local_character_id);
+                    let (se_on_source, se_on_target) =
+                        store.get_status_effects(&owner, &target_entity);
                     let damage_data = DamageData {
                         skill_id: pkt.skill_id,
                         skill_effect_id: pkt.skill_effect_id,
@@ -429,7 +416,6 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                         se_on_source,
                         se_on_target,
                         target_count,
-                        &entity_tracker,
                         now,
                     );
                 }
@@ -445,22 +431,18 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 parse_pkt(&data, PKTSkillDamageNotify::new, "PktSkillDamageNotify")
             {
                 let now = Utc::now().timestamp_millis();
-                let owner = entity_tracker.get_source_entity(pkt.source_id);
-                let local_character_id = id_tracker
-                    .borrow()
-                    .get_local_character_id(entity_tracker.local_entity_id);
+                let owner = store.entities.get_source_entity(pkt.source_id);
                 let target_count = pkt.skill_damage_events.len() as i32;
                 for mut event in pkt.skill_damage_events.into_iter() {
                     if !damage_handler.decrypt_damage_event(&mut event) {
                         state.damage_is_valid = false;
                         continue;
                     }
-                    let target_entity = entity_tracker.get_or_create_entity(event.target_id);
+                    let target_entity = store.entities.get_or_create_entity(event.target_id);
                     // source_entity is to determine battle item
-                    let source_entity = entity_tracker.get_or_create_entity(pkt.source_id);
-                    let (se_on_source, se_on_target) = status_tracker
-                        .borrow_mut()
-                        .get_status_effects(&owner, &target_entity, local_character_id);
+                    let source_entity = store.entities.get_or_create_entity(pkt.source_id);
+                    let (se_on_source, se_on_target) =
+                        store.get_status_effects(&owner, &target_entity);
                     let damage_data = DamageData {
                         skill_id: pkt.skill_id,
                         skill_effect_id: pkt.skill_effect_id.unwrap_or_default(),
@@ -480,7 +462,6 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                         se_on_source,
                         se_on_target,
                         target_count,
-                        &entity_tracker,
                         now,
                     );
                 }
@@ -488,9 +469,9 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
         }
         Pkt::PartyInfo => {
             if let Some(pkt) = parse_pkt(&data, PKTPartyInfo::new, "PKTPartyInfo") {
-                entity_tracker.party_info(pkt, &local_info);
-                let local_player_id = entity_tracker.local_entity_id;
-                if let Some(entity) = entity_tracker.entities.get(&local_player_id) {
+                store.party_info(pkt, &local_info);
+                let local_player_id = store.entities.local_entity_id;
+                if let Some(entity) = store.entities.get_entity_ref(local_player_id) {
                     state.update_local_player(entity);
                 }
                 *party_cache = None;
@@ -499,9 +480,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
         Pkt::PartyLeaveResult => {
             if let Some(pkt) = parse_pkt(&data, PKTPartyLeaveResult::new, "PKTPartyLeaveResult")
             {
-                party_tracker
-                    .borrow_mut()
-                    .remove(pkt.party_instance_id, pkt.name);
+                store.parties.remove(pkt.party_instance_id, pkt.name);
                 *party_cache = None;
             }
         }
@@ -513,19 +492,10 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
             ) {
                 // info!("{:?}", pkt);
                 let shields =
-                    entity_tracker.party_status_effect_add(pkt, &state.encounter.entities);
+                    store.party_status_effect_add(pkt, &state.encounter.entities);
                 for status_effect in shields {
-                    let source = entity_tracker.get_source_entity(status_effect.source_id);
-                    let target_id =
-                        if status_effect.target_type == StatusEffectTargetType::Party {
-                            id_tracker
-                                .borrow()
-                                .get_entity_id(status_effect.target_id)
-                                .unwrap_or_default()
-                        } else {
-                            status_effect.target_id
-                        };
-                    let target = entity_tracker.get_source_entity(target_id);
+                    let source = store.entities.get_source_entity(status_effect.source_id);
+                    let target = store.status_effect_target(&status_effect);
                     // info!("SHIELD SOURCE: {} > TARGET: {}", source.name, target.name);
                     state.on_boss_shield(&target, status_effect.value);
                     state.on_shield_applied(
@@ -544,13 +514,12 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 "PKTPartyStatusEffectRemoveNotify",
             ) {
                 let (is_shield, shields_broken, _effects_removed, _left_workshop) =
-                    entity_tracker.party_status_effect_remove(pkt);
+                    store.party_status_effect_remove(pkt);
                 if is_shield {
                     for status_effect in shields_broken {
                         let change = status_effect.value;
                         on_shield_change(
-                            entity_tracker,
-                            &id_tracker,
+                            store,
                             state,
                             status_effect,
                             change,
@@ -566,7 +535,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 "PKTPartyStatusEffectResultNotify",
             ) {
                 // info!("{:?}", pkt);
-                party_tracker.borrow_mut().add(
+                store.add_party_member(
                     pkt.raid_instance_id,
                     pkt.party_instance_id,
                     pkt.character_id,
@@ -581,7 +550,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 PKTStatusEffectAddNotify::new,
                 "PKTStatusEffectAddNotify",
             ) {
-                let status_effect = entity_tracker.build_and_register_status_effect(
+                let status_effect = store.build_and_register_status_effect(
                     &pkt.status_effect_data,
                     pkt.object_id,
                     Utc::now(),
@@ -589,17 +558,8 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 );
 
                 if status_effect.status_effect_type == StatusEffectType::Shield {
-                    let source = entity_tracker.get_source_entity(status_effect.source_id);
-                    let target_id =
-                        if status_effect.target_type == StatusEffectTargetType::Party {
-                            id_tracker
-                                .borrow()
-                                .get_entity_id(status_effect.target_id)
-                                .unwrap_or_default()
-                        } else {
-                            status_effect.target_id
-                        };
-                    let target = entity_tracker.get_source_entity(target_id);
+                    let source = store.entities.get_source_entity(status_effect.source_id);
+                    let target = store.status_effect_target(&status_effect);
                     state.on_boss_shield(&target, status_effect.value);
                     state.on_shield_applied(
                         &source,
@@ -610,8 +570,8 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 }
 
                 if status_effect.status_effect_type == StatusEffectType::HardCrowdControl {
-                    let target = entity_tracker.get_source_entity(status_effect.target_id);
-                    if target.entity_type == EntityType::Player {
+                    let target = store.entities.get_source_entity(status_effect.target_id);
+                    if target.is_player() {
                         state.on_cc_applied(&target, &status_effect);
                     }
                 }
@@ -623,7 +583,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
         //         PKTStatusEffectDurationNotify::new,
         //         "PKTStatusEffectDurationNotify",
         //     ) {
-        //         status_tracker.borrow_mut().update_status_duration(
+        //         store.statuses.update_status_duration(
         //             pkt.effect_instance_id,
         //             pkt.target_id,
         //             pkt.expiration_tick,
@@ -638,7 +598,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 "PKTStatusEffectRemoveNotify",
             ) {
                 let (is_shield, shields_broken, effects_removed, _left_workshop) =
-                    status_tracker.borrow_mut().remove_status_effects(
+                    store.statuses.remove_status_effects(
                         pkt.object_id,
                         pkt.status_effect_instance_ids,
                         pkt.reason,
@@ -646,14 +606,13 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                     );
                 if is_shield {
                     if shields_broken.is_empty() {
-                        let target = entity_tracker.get_source_entity(pkt.object_id);
+                        let target = store.entities.get_source_entity(pkt.object_id);
                         state.on_boss_shield(&target, 0);
                     } else {
                         for status_effect in shields_broken {
                             let change = status_effect.value;
                             on_shield_change(
-                                entity_tracker,
-                                &id_tracker,
+                                store,
                                 state,
                                 status_effect,
                                 change,
@@ -664,8 +623,8 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 let now = Utc::now().timestamp_millis();
                 for effect_removed in effects_removed {
                     if effect_removed.status_effect_type == StatusEffectType::HardCrowdControl {
-                        let target = entity_tracker.get_source_entity(effect_removed.target_id);
-                        if target.entity_type == EntityType::Player {
+                        let target = store.entities.get_source_entity(effect_removed.target_id);
+                        if target.is_player() {
                             state.on_cc_removed(&target, &effect_removed, now);
                         }
                     }
@@ -680,6 +639,10 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
             {
                 state.on_phase_transition(3);
                 info!("phase: 3 - resetting encounter - TriggerBossBattleStatus");
+            } else {
+                // stamped with the last hit so that boundaries follow the packets, not the wall clock
+                state.on_boss_battle_status(state.encounter.last_combat_packet);
+                info!("phase boundary - TriggerBossBattleStatus");
             }
         }
         Pkt::TriggerStartNotify => {
@@ -692,7 +655,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                         state.party_info = if let Some(party) = party_cache.take() {
                             party
                         } else {
-                            update_party(&party_tracker, &entity_tracker)
+                            store.party_members()
                         };
                         state.raid_clear = true;
                         state.on_phase_transition(2);
@@ -704,7 +667,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                         state.party_info = if let Some(party) = party_cache.take() {
                             party
                         } else {
-                            update_party(&party_tracker, &entity_tracker)
+                            store.party_members()
                         };
                         state.raid_clear = false;
                         state.on_phase_transition(4);
@@ -724,6 +687,8 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 PKTZoneMemberLoadStatusNotify::new,
                 "PKTZoneMemberLoadStatusNotify",
             ) {
+                state.zone_id = Some(pkt.zone_id);
+
                 if state.raid_difficulty_id >= pkt.zone_id && !state.raid_difficulty.is_empty()
                 {
                     return;
@@ -731,32 +696,11 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
 
                 info!("raid zone id: {} level: {}", &pkt.zone_id, &pkt.zone_level);
                 
-                match pkt.zone_level {
-                    0 => {
-                        state.raid_difficulty = "Normal".to_string();
-                        state.raid_difficulty_id = 0;
-                    }
-                    1 => {
-                        state.raid_difficulty = "Hard".to_string();
-                        state.raid_difficulty_id = 1;
-                    }
-                    2 => {
-                        state.raid_difficulty = "Inferno".to_string();
-                        state.raid_difficulty_id = 2;
-                    }
-                    3 => {
-                        state.raid_difficulty = "Challenge".to_string();
-                        state.raid_difficulty_id = 3;
-                    }
-                    4 => {
-                        state.raid_difficulty = "Solo".to_string();
-                        state.raid_difficulty_id = 4;
-                    }
-                    5 => {
-                        state.raid_difficulty = "The First".to_string();
-                        state.raid_difficulty_id = 5;
-                    }
-                    _ => {}
+                if let Some(difficulty) =
+                    meter_data().raid_difficulty.by_zone(pkt.zone_id, u32::from(pkt.zone_level))
+                {
+                    state.raid_difficulty.clone_from(&difficulty.name);
+                    state.raid_difficulty_id = difficulty.id;
                 }
             }
         }
@@ -766,9 +710,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 PKTZoneObjectUnpublishNotify::new,
                 "PKTZoneObjectUnpublishNotify",
             ) {
-                status_tracker
-                    .borrow_mut()
-                    .remove_local_object(pkt.object_id);
+                store.statuses.remove_local_object(pkt.object_id);
             }
         }
         Pkt::StatusEffectSyncDataNotify => {
@@ -778,12 +720,11 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 "PKTStatusEffectSyncDataNotify",
             ) {
                 let (status_effect, old_value) =
-                    status_tracker.borrow_mut().sync_status_effect(
+                    store.sync_status_effect(
                         pkt.status_effect_instance_id,
                         pkt.character_id,
                         pkt.object_id,
                         pkt.value,
-                        entity_tracker.local_character_id,
                     );
                 if let Some(status_effect) = status_effect {
                     if status_effect.status_effect_type == StatusEffectType::Shield {
@@ -791,8 +732,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                             .checked_sub(status_effect.value)
                             .unwrap_or_default();
                         on_shield_change(
-                            entity_tracker,
-                            &id_tracker,
+                            store,
                             state,
                             status_effect,
                             change,
@@ -808,8 +748,8 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                 "PKTTroopMemberUpdateMinNotify",
             ) {
                 // info!("{:?}", pkt);
-                if let Some(object_id) = id_tracker.borrow().get_entity_id(pkt.character_id) {
-                    if let Some(entity) = entity_tracker.get_entity_ref(object_id) {
+                if let Some(object_id) = store.ids.get_entity_id(pkt.character_id) {
+                    if let Some(entity) = store.entities.get_entity_ref(object_id) {
                         state
                             .encounter
                             .entities
@@ -822,12 +762,11 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                     for se in pkt.status_effect_datas.iter() {
                         let val = get_status_effect_value(&se.value.bytearray_0);
                         let (status_effect, old_value) =
-                            status_tracker.borrow_mut().sync_status_effect(
+                            store.sync_status_effect(
                                 se.status_effect_instance_id,
                                 pkt.character_id,
                                 object_id,
                                 val,
-                                entity_tracker.local_character_id,
                             );
                         if let Some(status_effect) = status_effect {
                             if status_effect.status_effect_type == StatusEffectType::Shield {
@@ -835,8 +774,7 @@ pub fn handle<DH: DamageEncryptionHandler>(args: HandleArgs<DH>) {
                                     .checked_sub(status_effect.value)
                                     .unwrap_or_default();
                                 on_shield_change(
-                                    entity_tracker,
-                                    &id_tracker,
+                                    store,
                                     state,
                                     status_effect,
                                     change,
@@ -1,6 +1,11 @@
-use std::sync::{atomic::{AtomicBool, Ordering}, Arc};
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc, RwLock};
+use hashbrown::HashMap;
 use log::*;
-use tauri::{AppHandle, Emitter, Listener};
+use serde::Deserialize;
+use tauri::{AppHandle, Listener};
+
+use crate::abstractions::EventEmitter;
+use crate::constants::{METER_MINI_WINDOW_LABEL, METER_WINDOW_LABEL};
 
 #[derive(Debug)]
 pub enum FlagAction {
@@ -11,97 +16,193 @@ pub enum FlagAction {
     None,
 }
 
+/// What a window receives on every meter update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UpdateLevel {
+    None,
+    /// `encounter-summary`, the top-line dps of each player.
+    Summary,
+    /// `encounter-update` with every entity and skill on every tick.
+    Full,
+    /// `encounter-update` every snapshot interval and `encounter-delta` in between,
+    /// for windows that merge the changes themselves.
+    Delta,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubscriptionRequest {
+    pub window: String,
+    pub level: UpdateLevel,
+}
+
+/// Update level per window label, shared by the listener and the sender.
+#[derive(Debug, Clone)]
+pub struct WindowSubscriptions(Arc<RwLock<HashMap<String, UpdateLevel>>>);
+
+impl Default for WindowSubscriptions {
+    fn default() -> Self {
+        let windows = HashMap::from([
+            (METER_WINDOW_LABEL.to_string(), UpdateLevel::Full),
+            (METER_MINI_WINDOW_LABEL.to_string(), UpdateLevel::Summary),
+        ]);
+
+        Self(Arc::new(RwLock::new(windows)))
+    }
+}
+
+impl WindowSubscriptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, window: &str, level: UpdateLevel) {
+        self.0.write().unwrap().insert(window.to_string(), level);
+    }
+
+    pub fn windows(&self, level: UpdateLevel) -> Vec<String> {
+        self.0.read().unwrap()
+            .iter()
+            .filter(|(_, window_level)| **window_level == level)
+            .map(|(window, _)| window.clone())
+            .collect()
+    }
+}
+
+#[derive(Clone)]
 pub struct AppListener {
-    app_handle: AppHandle,
     reset: Arc<AtomicBool>,
     pause: Arc<AtomicBool>,
     save: Arc<AtomicBool>,
     boss_only_damage: Arc<AtomicBool>,
     emit_details: Arc<AtomicBool>,
+    resync: Arc<AtomicBool>,
+    subscriptions: WindowSubscriptions,
 }
 
 impl AppListener {
-    pub fn new(app_handle: AppHandle, boss_only_damage_flag: bool) -> Self {
+    pub fn new(boss_only_damage_flag: bool) -> Self {
 
         let reset = Arc::new(AtomicBool::new(false));
         let pause = Arc::new(AtomicBool::new(false));
         let save = Arc::new(AtomicBool::new(false));
         let boss_only_damage = Arc::new(AtomicBool::new(false));
         let emit_details = Arc::new(AtomicBool::new(false));
+        let resync = Arc::new(AtomicBool::new(false));
 
         if boss_only_damage_flag {
             boss_only_damage.store(true, Ordering::Relaxed);
             info!("boss only damage enabled")
         }
 
-        let app_handle_clone = app_handle.clone();
+        Self {
+            reset,
+            pause,
+            save,
+            boss_only_damage,
+            emit_details,
+            resync,
+            subscriptions: WindowSubscriptions::new(),
+        }
+    }
+
+    /// Forwards the requests sent by the UI windows to the flags.
+    pub fn listen(&self, app_handle: &AppHandle, emitter: Arc<dyn EventEmitter>) {
+
         app_handle.listen_any("reset-request", {
-            let reset_clone = reset.clone();
-            let app_clone = app_handle_clone.clone();
+            let listener = self.clone();
+            let emitter = emitter.clone();
             move |_event| {
-                reset_clone.store(true, Ordering::Relaxed);
-                info!("resetting meter");
-                app_clone.emit("reset-encounter", "").ok();
+                listener.request_reset();
+                emitter.emit("reset-encounter", "").ok();
             }
         });
 
         app_handle.listen_any("save-request", {
-            let save_clone = save.clone();
-            let app_clone = app_handle_clone.clone();
+            let listener = self.clone();
+            let emitter = emitter.clone();
             move |_event| {
-                save_clone.store(true, Ordering::Relaxed);
-                info!("manual saving encounter");
-                app_clone.emit("save-encounter", "").ok();
+                listener.request_save();
+                emitter.emit("save-encounter", "").ok();
             }
         });
 
         app_handle.listen_any("pause-request", {
-            let pause_clone = pause.clone();
-            let app_clone = app_handle_clone.clone();
+            let listener = self.clone();
+            let emitter = emitter.clone();
             move |_event| {
-                let prev = pause_clone.fetch_xor(true, Ordering::Relaxed);
-                if prev {
-                    info!("unpausing meter");
-                } else {
-                    info!("pausing meter");
-                }
-                app_clone.emit("pause-encounter", "").ok();
+                listener.toggle_pause();
+                emitter.emit("pause-encounter", "").ok();
             }
         });
 
         app_handle.listen_any("boss-only-damage-request", {
-            let boss_only_damage = boss_only_damage.clone();
+            let listener = self.clone();
             move |event| {
-                let bod = event.payload();
-                if bod == "true" {
-                    boss_only_damage.store(true, Ordering::Relaxed);
-                    info!("boss only damage enabled")
-                } else {
-                    boss_only_damage.store(false, Ordering::Relaxed);
-                    info!("boss only damage disabled")
-                }
+                listener.set_boss_only_damage(event.payload() == "true");
             }
         });
 
         app_handle.listen_any("emit-details-request", {
-            let emit_clone = emit_details.clone();
+            let listener = self.clone();
             move |_event| {
-                let prev = emit_clone.fetch_xor(true, Ordering::Relaxed);
-                if prev {
-                    info!("stopped sending details");
-                } else {
-                    info!("sending details");
+                listener.toggle_emit_details();
+            }
+        });
+
+        app_handle.listen_any("subscription-request", {
+            let listener = self.clone();
+            move |event| {
+                match serde_json::from_str::<SubscriptionRequest>(event.payload()) {
+                    Ok(request) => listener.subscribe(request),
+                    Err(err) => warn!("invalid subscription request: {}", err),
                 }
             }
         });
 
-        Self {
-            app_handle,
-            reset,
-            pause,
-            save,
-            boss_only_damage,
-            emit_details
+        app_handle.listen_any("encounter-resync-request", {
+            let listener = self.clone();
+            move |_event| {
+                listener.request_resync();
+            }
+        });
+    }
+
+    pub fn request_reset(&self) {
+        self.reset.store(true, Ordering::Relaxed);
+        info!("resetting meter");
+    }
+
+    pub fn request_save(&self) {
+        self.save.store(true, Ordering::Relaxed);
+        info!("manual saving encounter");
+    }
+
+    pub fn toggle_pause(&self) {
+        let prev = self.pause.fetch_xor(true, Ordering::Relaxed);
+        if prev {
+            info!("unpausing meter");
+        } else {
+            info!("pausing meter");
+        }
+    }
+
+    pub fn set_boss_only_damage(&self, value: bool) {
+        self.boss_only_damage.store(value, Ordering::Relaxed);
+        if value {
+            info!("boss only damage enabled")
+        } else {
+            info!("boss only damage disabled")
+        }
+    }
+
+    pub fn toggle_emit_details(&self) {
+        let prev = self.emit_details.fetch_xor(true, Ordering::Relaxed);
+        if prev {
+            info!("stopped sending details");
+        } else {
+            info!("sending details");
         }
     }
 
@@ -109,6 +210,28 @@ impl AppListener {
         self.emit_details.load(Ordering::Relaxed)
     }
 
+    pub fn subscribe(&self, request: SubscriptionRequest) {
+        info!("{} window subscribed to {:?} updates", request.window, request.level);
+        self.subscriptions.set(&request.window, request.level);
+
+        if request.level == UpdateLevel::Delta {
+            self.request_resync();
+        }
+    }
+
+    pub fn subscriptions(&self) -> WindowSubscriptions {
+        self.subscriptions.clone()
+    }
+
+    pub fn request_resync(&self) {
+        self.resync.store(true, Ordering::Relaxed);
+    }
+
+    /// Set when a window missed an `encounter-delta` and needs a full `encounter-update`.
+    pub fn take_resync_request(&self) -> bool {
+        self.resync.swap(false, Ordering::Relaxed)
+    }
+
     pub fn process_flags(&self) -> FlagAction {
 
         if self.reset.load(Ordering::Relaxed) {
@@ -131,4 +254,4 @@ impl AppListener {
 
         FlagAction::None
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,102 @@
+use hashbrown::HashMap;
+
+use crate::models::*;
+
+/// Gates saved further apart than this are considered separate raid runs.
+pub const RAID_RUN_TIMEOUT_MS: i64 = 30 * 60 * 1000;
+
+/// Splits the encounter into phases and keeps the damage dealt by each player per phase.
+///
+/// Phases are split on boss battle status triggers, when a boss hp bar runs out and on boss swaps.
+#[derive(Debug, Default)]
+pub struct PhaseTracker {
+    phases: Vec<EncounterPhase>,
+    boss_hp: HashMap<String, i64>,
+}
+
+impl PhaseTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_damage(
+        &mut self,
+        boss_name: &str,
+        source_name: &str,
+        damage: i64,
+        timestamp: i64,
+        fight_start: i64,
+    ) {
+        if self.phases.is_empty() {
+            self.phases.push(EncounterPhase {
+                boss_name: boss_name.to_string(),
+                start: (timestamp - fight_start).max(0),
+                ..Default::default()
+            });
+        }
+
+        let phase = self.phases.last_mut().unwrap();
+
+        if phase.boss_name.is_empty() {
+            phase.boss_name = boss_name.to_string();
+        }
+
+        phase.total_damage_dealt += damage;
+        *phase.damage_dealt.entry_ref(source_name).or_default() += damage;
+    }
+
+    pub fn on_boss_hp(&mut self, boss_name: &str, current_hp: i64, timestamp: i64, fight_start: i64) {
+        let previous = self.boss_hp.insert(boss_name.to_string(), current_hp);
+
+        if previous.is_some_and(|hp| hp > 0) && current_hp <= 0 {
+            self.split(PhaseReason::HpThreshold, boss_name, timestamp, fight_start);
+        }
+    }
+
+    /// Closes the current phase and starts a new one, nothing happens before the fight started.
+    pub fn split(&mut self, reason: PhaseReason, boss_name: &str, timestamp: i64, fight_start: i64) {
+        let Some(current) = self.phases.last_mut() else {
+            return;
+        };
+
+        let time = (timestamp - fight_start).max(current.start);
+
+        // no damage was dealt since the last boundary
+        if current.total_damage_dealt == 0 {
+            current.reason = reason;
+            current.boss_name = boss_name.to_string();
+            return;
+        }
+
+        current.end = time;
+        self.phases.push(EncounterPhase {
+            boss_name: boss_name.to_string(),
+            reason,
+            start: time,
+            ..Default::default()
+        });
+    }
+
+    pub fn phases(&self, fight_start: i64, fight_end: i64) -> Vec<EncounterPhase> {
+        let mut phases = self.phases.clone();
+
+        // e.g. the boss hp bar running out at the end of the fight
+        if phases.last().is_some_and(|phase| phase.total_damage_dealt == 0) {
+            phases.pop();
+        }
+
+        if let Some(last) = phases.last_mut() {
+            last.end = (fight_end - fight_start).max(last.start);
+        }
+
+        phases
+    }
+}
+
+/// Splits a gate name from the raid map into the raid and the gate number.
+pub fn parse_gate(gate_name: &str) -> Option<(&str, u32)> {
+    let (raid, gate) = gate_name.rsplit_once(" G")?;
+    let gate = gate.parse().ok()?;
+
+    Some((raid, gate))
+}
@@ -1,61 +1,103 @@
+use crate::abstractions::EventEmitter;
+use crate::database::compute_raid_summary;
+use crate::live::delta::{should_send_entity, DeltaTracker, EncounterDelta};
 use crate::live::encounter_state::EncounterState;
-use crate::live::entity_tracker::EntityTracker;
-use crate::live::party_tracker::PartyTracker;
-use crate::live::utils::update_party;
+use crate::live::listener::{UpdateLevel, WindowSubscriptions};
+use crate::live::store::StateStore;
 use crate::models::*;
+use crate::settings::*;
 use log::*;
-use std::cell::RefCell;
-use std::rc::Rc;
+use serde::Serialize;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
 
 pub struct SendToUiArgs<'a> {
     pub state: &'a mut EncounterState,
     pub party_freeze: bool,
     pub party_cache: &'a mut Option<Vec<Vec<String>>>,
-    pub party_tracker: Rc<RefCell<PartyTracker>>,
-    pub entity_tracker: &'a EntityTracker
+    pub store: &'a StateStore,
 }
 
 pub struct AppSender {
-    app_handle: AppHandle,
+    emitter: Arc<dyn EventEmitter>,
+    subscriptions: WindowSubscriptions,
     last_update: Instant,
     duration: Duration,
     last_party_update: Instant,
     party_duration: Duration,
+    last_snapshot: Instant,
+    snapshot_duration: Duration,
+    last_cooldown_update: Instant,
+    delta_tracker: DeltaTracker,
+}
+
+struct UpdateArgs {
+    emitter: Arc<dyn EventEmitter>,
+    snapshot_windows: Vec<String>,
+    delta_windows: Vec<String>,
+    summary_windows: Vec<String>,
+    snapshot: Option<Encounter>,
+    delta: Option<EncounterDelta>,
+    summary: Option<EncounterSummary>,
+    boss_dead: bool,
+    damage_valid: bool,
+    party_info: Option<Vec<Vec<String>>>,
 }
 
 impl AppSender {
-    pub fn new(app_handle: AppHandle, low_performance_mode: bool) -> Self {
+    pub fn new(
+        emitter: Arc<dyn EventEmitter>,
+        settings: &GeneralSettings,
+        subscriptions: WindowSubscriptions) -> Self {
         let last_update = Instant::now();
-        let mut duration = Duration::from_millis(200);
+        let mut duration = interval(settings.update_interval, default_update_interval());
         let last_party_update = Instant::now();
-        let party_duration = Duration::from_millis(2000);
+        let party_duration = interval(settings.party_update_interval, default_party_update_interval());
+        let last_snapshot = Instant::now();
+        let snapshot_duration = interval(settings.snapshot_interval, default_snapshot_interval());
 
-        if low_performance_mode {
-            duration = Duration::from_millis(1500);
+        if settings.low_performance_mode {
+            duration = interval(
+                settings.low_performance_update_interval,
+                default_low_performance_update_interval());
             info!("low performance mode enabled")
         }
 
+        info!(
+            "meter updates every {}ms, party every {}ms, full snapshot every {}ms",
+            duration.as_millis(),
+            party_duration.as_millis(),
+            snapshot_duration.as_millis()
+        );
+
         Self {
-            app_handle,
+            emitter,
+            subscriptions,
             last_update,
             duration,
             last_party_update,
-            party_duration
+            party_duration,
+            last_snapshot,
+            snapshot_duration,
+            last_cooldown_update: Instant::now(),
+            delta_tracker: DeltaTracker::new(),
         }
     }
 
+    /// The next update carries the whole encounter, e.g. when a window missed a delta.
+    pub fn request_snapshot(&mut self) {
+        self.delta_tracker.invalidate();
+    }
+
     pub fn send_to_ui(&mut self, args: SendToUiArgs) {
 
         let SendToUiArgs {
             state,
             party_freeze,
             party_cache,
-            entity_tracker,
-            party_tracker
+            store
         } = args;
-        
+
         let can_send = self.last_update.elapsed() >= self.duration || state.resetting || state.boss_dead_update;
 
         if !can_send {
@@ -63,14 +105,24 @@ impl AppSender {
         }
 
         let boss_dead = state.boss_dead_update;
-        
+
         if state.boss_dead_update {
             state.boss_dead_update = false;
         }
 
-        let mut clone = state.encounter.clone();
-        let damage_valid = state.damage_is_valid;
-        let app_handle = self.app_handle.clone();
+        if !state.encounter.entities.values().any(should_send_entity) {
+            self.last_update = Instant::now();
+            return;
+        }
+
+        let mut snapshot_windows = self.subscriptions.windows(UpdateLevel::Full);
+        let mut delta_windows = self.subscriptions.windows(UpdateLevel::Delta);
+        let summary_windows = self.subscriptions.windows(UpdateLevel::Summary);
+
+        let full_snapshot = boss_dead
+            || state.resetting
+            || self.delta_tracker.needs_snapshot(&state.encounter)
+            || self.last_snapshot.elapsed() >= self.snapshot_duration;
 
         let party_info: Option<Vec<Vec<String>>> =
             if self.last_party_update.elapsed() >= self.party_duration && !party_freeze {
@@ -79,7 +131,7 @@ impl AppSender {
                 // use cache if available
                 // otherwise get party info
                 party_cache.clone().or_else(|| {
-                    let party = update_party(&party_tracker, &entity_tracker);
+                    let party = store.party_members();
                     if party.len() > 1 {
                         if party.iter().all(|p| p.len() == 4) {
                             *party_cache = Some(party.clone());
@@ -93,45 +145,145 @@ impl AppSender {
                 None
             };
 
-        tokio::task::spawn(async move {
-            if !clone.current_boss_name.is_empty() {
-                let current_boss = clone.entities.get(&clone.current_boss_name).cloned();
-                if let Some(mut current_boss) = current_boss {
-                    if boss_dead {
-                        current_boss.is_dead = true;
-                        current_boss.current_hp = 0;
-                    }
-                    clone.current_boss = Some(current_boss);
-                } else {
-                    clone.current_boss_name = String::new();
-                }
-            }
-            clone.entities.retain(|_, e| {
-                ((e.entity_type == EntityType::Player && e.class_id > 0)
-                    || e.entity_type == EntityType::Esther
-                    || e.entity_type == EntityType::Boss)
-                    && e.damage_stats.damage_dealt > 0
-            });
-
-            if !clone.entities.is_empty() {
-                if !damage_valid {
-                    app_handle
-                        .emit("invalid-damage", "")
-                        .expect("failed to emit invalid-damage");
-                } else {
-                    app_handle
-                        .emit("encounter-update", Some(clone))
-                        .expect("failed to emit encounter-update");
-
-                    if party_info.is_some() {
-                        app_handle
-                            .emit("party-update", party_info)
-                            .expect("failed to emit party-update");
-                    }
-                }
+        let summary_party = party_info
+            .clone()
+            .or_else(|| party_cache.clone())
+            .unwrap_or_else(|| store.party_members());
+
+        // computed once and shared by the snapshot and the delta
+        let mut raid_summary = None;
+
+        if !snapshot_windows.is_empty() || !delta_windows.is_empty() {
+            // walks every cast of the local player, refresh it at the party pace rather than every tick
+            if full_snapshot || self.last_cooldown_update.elapsed() >= self.party_duration {
+                state.update_cooldown_efficiency();
+                self.last_cooldown_update = Instant::now();
             }
+
+            raid_summary = compute_raid_summary(&state.encounter, &summary_party);
+        }
+
+        let delta = if delta_windows.is_empty() {
+            // nobody merges deltas, the next delta subscriber starts from a snapshot
+            self.delta_tracker.invalidate();
+            None
+        } else if full_snapshot {
+            self.delta_tracker.snapshot(&state.encounter);
+            self.last_snapshot = Instant::now();
+            snapshot_windows.append(&mut delta_windows);
+            None
+        } else {
+            let mut delta = self.delta_tracker.delta(&state.encounter);
+            delta.raid_summary = raid_summary.clone();
+            Some(delta)
+        };
+
+        let snapshot = (!snapshot_windows.is_empty()).then(|| Encounter {
+            raid_summary,
+            ..state.encounter.clone()
+        });
+
+        let summary = (!summary_windows.is_empty())
+            .then(|| EncounterSummary::new(&state.encounter));
+
+        let args = UpdateArgs {
+            emitter: self.emitter.clone(),
+            snapshot_windows,
+            delta_windows,
+            summary_windows,
+            snapshot,
+            delta,
+            summary,
+            boss_dead,
+            damage_valid: state.damage_is_valid,
+            party_info,
+        };
+
+        tokio::task::spawn(async move {
+            send_update(args);
         });
 
         self.last_update = Instant::now();
     }
-}
\ No newline at end of file
+}
+
+fn interval(millis: u64, default: u64) -> Duration {
+    if millis == 0 {
+        return Duration::from_millis(default);
+    }
+
+    Duration::from_millis(millis)
+}
+
+fn send_update(args: UpdateArgs) {
+    let UpdateArgs {
+        emitter,
+        snapshot_windows,
+        delta_windows,
+        summary_windows,
+        snapshot,
+        delta,
+        summary,
+        boss_dead,
+        damage_valid,
+        party_info,
+    } = args;
+
+    if !damage_valid {
+        emitter
+            .emit("invalid-damage", "")
+            .expect("failed to emit invalid-damage");
+        return;
+    }
+
+    if let Some(mut clone) = snapshot {
+        if !clone.current_boss_name.is_empty() {
+            let current_boss = clone.entities.get(&clone.current_boss_name).cloned();
+            if let Some(mut current_boss) = current_boss {
+                if boss_dead {
+                    current_boss.is_dead = true;
+                    current_boss.current_hp = 0;
+                }
+                clone.current_boss = Some(current_boss);
+            } else {
+                clone.current_boss_name = String::new();
+            }
+        }
+        clone.entities.retain(|_, e| should_send_entity(e));
+
+        emit_to_windows(&emitter, &snapshot_windows, "encounter-update", Some(clone));
+    }
+
+    if let Some(delta) = delta {
+        if !delta.is_empty() {
+            emit_to_windows(&emitter, &delta_windows, "encounter-delta", delta);
+        }
+    }
+
+    if let Some(summary) = summary {
+        emit_to_windows(&emitter, &summary_windows, "encounter-summary", summary);
+    }
+
+    if party_info.is_some() {
+        emitter
+            .emit("party-update", party_info)
+            .expect("failed to emit party-update");
+    }
+}
+
+fn emit_to_windows<S: Serialize>(emitter: &Arc<dyn EventEmitter>, windows: &[String], event: &str, payload: S) {
+    let payload = match serde_json::to_value(payload) {
+        Ok(payload) => payload,
+        Err(err) => {
+            warn!("failed to serialize {}: {:?}", event, err);
+            return;
+        }
+    };
+
+    // windows come and go, a closed one must not stop the others from updating
+    for window in windows {
+        if let Err(err) = emitter.emit_value_to(window, event, payload.clone()) {
+            warn!("failed to emit {} to {}: {:?}", event, window, err);
+        }
+    }
+}
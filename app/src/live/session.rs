@@ -0,0 +1,186 @@
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::Result;
+use tokio::task::JoinHandle;
+
+use crate::abstractions::{DamageEncryptionHandler, EventEmitter, Pkt, RegionAcessor};
+use crate::api::StatsApi;
+use crate::database::Repository;
+use crate::live::encounter_state::EncounterState;
+use crate::live::handler::{handle, HandleArgs};
+use crate::live::listener::FlagAction;
+use crate::live::sender::{AppSender, SendToUiArgs};
+use crate::live::store::StateStore;
+use crate::local::{LocalInfo, LocalPlayerRepository};
+use crate::models::Encounter;
+
+pub struct LiveSessionArgs<DH: DamageEncryptionHandler> {
+    pub version: String,
+    pub emitter: Arc<dyn EventEmitter>,
+    pub repository: Arc<Repository>,
+    pub stats_api: Arc<dyn StatsApi>,
+    pub local: LocalPlayerRepository,
+    pub region_accessor: Box<dyn RegionAcessor>,
+    pub damage_handler: DH,
+}
+
+/// Owns the state store and the encounter state, and applies packets to them.
+/// This is everything `live::start` does besides reading packets and talking to the UI.
+pub struct LiveSession<DH: DamageEncryptionHandler> {
+    state: EncounterState,
+    store: StateStore,
+    damage_handler: DH,
+    local: LocalPlayerRepository,
+    local_info: LocalInfo,
+    region_accessor: Box<dyn RegionAcessor>,
+    party_freeze: bool,
+    party_cache: Option<Vec<Vec<String>>>,
+    raid_end_cd: Instant,
+}
+
+impl<DH: DamageEncryptionHandler> LiveSession<DH> {
+    pub fn new(args: LiveSessionArgs<DH>) -> Result<Self> {
+
+        let LiveSessionArgs {
+            version,
+            emitter,
+            repository,
+            stats_api,
+            local,
+            region_accessor,
+            damage_handler
+        } = args;
+
+        let local_info = local.read()?;
+
+        let mut state = EncounterState::new(
+            version,
+            local_info.client_id.clone(),
+            emitter,
+            repository,
+            stats_api);
+
+        let region = region_accessor.get();
+        state.region = region.clone();
+        state.encounter.region = region;
+
+        Ok(Self {
+            state,
+            store: StateStore::new(),
+            damage_handler,
+            local,
+            local_info,
+            region_accessor,
+            party_freeze: false,
+            party_cache: None,
+            raid_end_cd: Instant::now(),
+        })
+    }
+
+    pub fn start_damage_handler(&mut self) -> Result<()> {
+        self.damage_handler.start()
+    }
+
+    /// Returns `false` when the packet should be skipped.
+    pub fn apply_action(&mut self, action: FlagAction) -> bool {
+        let state = &mut self.state;
+
+        match action {
+            FlagAction::Reset => state.soft_reset(true),
+            FlagAction::Saved => {
+                state.party_info = self.store.party_members();
+                state.save_to_db(true);
+                state.saved = true;
+                state.resetting = true;
+            },
+            FlagAction::Paused => return false,
+            FlagAction::BossOnlyDamage => {
+                if state.boss_only_damage {
+                    state.boss_only_damage = false;
+                    state.encounter.boss_only_damage = false;
+                }
+                else {
+                    state.boss_only_damage = true;
+                }
+            },
+            _ => {},
+        }
+
+        true
+    }
+
+    pub fn handle(&mut self, op: Pkt, data: &[u8], can_emit_details: bool) {
+        let args = HandleArgs {
+            data,
+            op,
+            state: &mut self.state,
+            store: &mut self.store,
+            damage_handler: &mut self.damage_handler,
+            can_emit_details,
+            local_info: &mut self.local_info,
+            local: &self.local,
+            party_cache: &mut self.party_cache,
+            party_freeze: &mut self.party_freeze,
+            raid_end_cd: &mut self.raid_end_cd,
+            region_accessor: &self.region_accessor
+        };
+
+        handle(args);
+    }
+
+    pub fn send_to_ui(&mut self, sender: &mut AppSender) {
+        let args = SendToUiArgs {
+            state: &mut self.state,
+            party_cache: &mut self.party_cache,
+            party_freeze: self.party_freeze,
+            store: &self.store,
+        };
+
+        sender.send_to_ui(args);
+    }
+
+    /// Set once the encounter has been saved and the next packet should start from a clean state.
+    pub fn is_resetting(&self) -> bool {
+        self.state.resetting
+    }
+
+    pub fn finish_reset(&mut self) {
+        if !self.state.resetting {
+            return;
+        }
+
+        self.state.soft_reset(true);
+        self.state.resetting = false;
+        self.state.saved = false;
+        self.party_freeze = false;
+        self.party_cache = None;
+    }
+
+    /// Saves the encounter which was still in progress when the packets ran out
+    /// and returns the pending saves.
+    pub fn finish(&mut self) -> Vec<JoinHandle<()>> {
+        if !self.state.saved && !self.state.resetting {
+            self.state.party_info = self.store.party_members();
+            self.state.save_to_db(false);
+        }
+
+        self.state.take_save_tasks()
+    }
+
+    pub fn encounter(&self) -> &Encounter {
+        &self.state.encounter
+    }
+
+    pub fn store(&self) -> &StateStore {
+        &self.store
+    }
+
+    pub fn region(&self) -> Option<&String> {
+        self.state.region.as_ref()
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.local_info.client_id
+    }
+}
@@ -0,0 +1,90 @@
+use hashbrown::HashMap;
+
+use crate::models::*;
+
+/// Follows the stagger bar of every boss hit during the encounter.
+///
+/// The stagger packet doesn't say who filled the bar, each increase is credited to the
+/// player who last hit the boss.
+#[derive(Debug, Default)]
+pub struct StaggerTracker {
+    bosses: HashMap<String, BossStaggerState>,
+}
+
+#[derive(Debug, Default)]
+struct BossStaggerState {
+    current: i32,
+    stagger_start: i64,
+    last_hitter: String,
+    stats: BossStagger,
+}
+
+impl StaggerTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn on_hit(&mut self, boss_name: &str, source_name: &str) {
+        let boss = self.bosses.entry_ref(boss_name).or_default();
+
+        if boss.last_hitter != source_name {
+            boss.last_hitter = source_name.to_string();
+        }
+    }
+
+    pub fn on_stagger_change(
+        &mut self,
+        boss_name: &str,
+        current: i32,
+        max: i32,
+        timestamp: i64,
+        fight_start: i64,
+    ) {
+        if max <= 0 {
+            return;
+        }
+
+        let boss = self.bosses.entry_ref(boss_name).or_default();
+        let previous = boss.current;
+        let relative_timestamp_s = ((timestamp - fight_start) / 1000) as i32;
+
+        if current > previous && !boss.last_hitter.is_empty() {
+            *boss.stats.contributions
+                .entry(boss.last_hitter.clone())
+                .or_default() += (current - previous) as i64;
+        }
+
+        if current >= max && previous < max {
+            let duration = ((timestamp - boss.stagger_start) / 1000) as i32;
+            boss.stats.intervals.push(StaggerInterval {
+                time: relative_timestamp_s,
+                duration,
+                max,
+            });
+
+            let total: i64 = boss.stats.intervals.iter().map(|interval| interval.duration as i64).sum();
+            boss.stats.average_time_to_stagger = total as f64 / boss.stats.intervals.len() as f64;
+        } else if current != 0 && previous == 0 {
+            boss.stagger_start = timestamp;
+        }
+
+        boss.current = current;
+        boss.stats.max_stagger = boss.stats.max_stagger.max(max);
+
+        let percent = (current as f32 / max as f32 * 100.0).min(100.0);
+
+        match boss.stats.log.last_mut() {
+            Some(last) if last.time == relative_timestamp_s => last.p = percent,
+            _ => boss.stats.log.push(StaggerLog { time: relative_timestamp_s, p: percent }),
+        }
+    }
+
+    /// Bosses whose stagger bar moved, keyed by name.
+    pub fn stats(&self) -> HashMap<String, BossStagger> {
+        self.bosses
+            .iter()
+            .filter(|(_, boss)| !boss.stats.log.is_empty())
+            .map(|(name, boss)| (name.clone(), boss.stats.clone()))
+            .collect()
+    }
+}
@@ -0,0 +1,339 @@
+use crate::data::*;
+use crate::models::*;
+
+use hashbrown::HashMap;
+use crate::abstractions::packets::definitions::*;
+use crate::abstractions::packets::structures::{NpcStruct, StatPair};
+
+#[derive(Default)]
+pub struct EntityTracker {
+    pub entities: HashMap<u64, Entity>,
+
+    pub local_entity_id: u64,
+    pub local_character_id: u64,
+}
+
+impl EntityTracker {
+    pub fn new() -> Self {
+        Self {
+            entities: HashMap::new(),
+            local_entity_id: 0,
+            local_character_id: 0,
+        }
+    }
+
+    pub fn new_projectile(&mut self, pkt: &PKTNewProjectile) {
+        let projectile = Entity {
+            id: pkt.projectile_info.projectile_id,
+            name: format!("{:x}", pkt.projectile_info.projectile_id),
+            kind: EntityKind::Projectile(ProjectileInfo {
+                owner_id: pkt.projectile_info.owner_id,
+                skill_id: pkt.projectile_info.skill_id,
+                skill_effect_id: pkt.projectile_info.skill_effect,
+            }),
+            ..Default::default()
+        };
+        self.entities.insert(projectile.id, projectile);
+    }
+
+    pub fn new_trap(&mut self, pkt: &PKTNewTrap) {
+        let trap = Entity {
+            id: pkt.trap_struct.object_id,
+            name: format!("{:x}", pkt.trap_struct.object_id),
+            kind: EntityKind::Trap(ProjectileInfo {
+                owner_id: pkt.trap_struct.owner_id,
+                skill_id: pkt.trap_struct.skill_id,
+                skill_effect_id: pkt.trap_struct.skill_effect,
+            }),
+            ..Default::default()
+        };
+        self.entities.insert(trap.id, trap);
+    }
+
+    /// Resolves projectiles, traps and summons to the entity which owns them.
+    pub fn get_source_entity(&mut self, id: u64) -> Entity {
+        let id = self
+            .entities
+            .get(&id)
+            .and_then(|entity| entity.owner_id())
+            .unwrap_or(id);
+
+        self.get_or_create_entity(id)
+    }
+
+    pub fn id_is_player(&mut self, id: u64) -> bool {
+        self.entities
+            .get(&id)
+            .is_some_and(|entity| entity.is_player())
+    }
+
+    pub fn guess_is_player(&mut self, entity: &mut Entity, skill_id: u32) {
+        match &entity.kind {
+            EntityKind::Unknown => {},
+            EntityKind::Player(player) if player.class_id == 0 => {},
+            _ => return,
+        }
+
+        let class_id = get_skill_class_id(&skill_id);
+        if class_id == 0 {
+            return;
+        }
+
+        match &mut entity.kind {
+            EntityKind::Player(player) => player.class_id = class_id,
+            kind => {
+                *kind = EntityKind::Player(PlayerInfo {
+                    class_id,
+                    ..Default::default()
+                });
+            }
+        }
+        self.entities.insert(entity.id, entity.clone());
+    }
+
+    pub fn get_or_create_entity(&mut self, id: u64) -> Entity {
+        if let Some(entity) = self.entities.get(&id) {
+            return entity.clone();
+        }
+
+        let entity = Entity::unknown(id);
+        self.entities.insert(entity.id, entity.clone());
+        entity
+    }
+
+    pub fn get_entity_ref(&self, id: u64) -> Option<&Entity> {
+        self.entities.get(&id)
+    }
+
+    pub fn insert(&mut self, entity: Entity) {
+        self.entities.insert(entity.id, entity);
+    }
+
+    pub fn remove(&mut self, id: u64) {
+        self.entities.remove(&id);
+    }
+}
+
+pub fn get_current_and_max_hp(stat_pair: &Vec<StatPair>) -> (i64, i64) {
+    let mut hp: Option<i64> = None;
+    let mut max_hp: Option<i64> = None;
+
+    for pair in stat_pair {
+        match pair.stat_type as u32 {
+            1 => hp = Some(pair.value),
+            27 => max_hp = Some(pair.value),
+            _ => {}
+        }
+        if hp.is_some() && max_hp.is_some() {
+            break;
+        }
+    }
+
+    (hp.unwrap_or_default(), max_hp.unwrap_or_default())
+}
+
+/// Classifies a freshly spawned npc as a boss, an esther or a regular npc.
+pub fn get_npc_kind_and_name(npc: &NpcStruct, max_hp: i64) -> (EntityKind, String) {
+    if let Some(esther) = get_esther_from_npc_id(npc.type_id) {
+        return (EntityKind::Esther(EstherInfo { npc_id: npc.type_id }), esther.name);
+    }
+
+    let mut info = NpcInfo {
+        npc_id: npc.type_id,
+        level: npc.level,
+        grade: "none".to_string(),
+        push_immune: false,
+    };
+
+    let assets = meter_data();
+
+    if let Some(npc_info) = assets.npc.get(&npc.type_id) {
+        let npc_name = npc_info.name.clone().unwrap_or_default();
+        info.grade = npc_info.grade.clone();
+
+        if (npc_info.grade == "Boss"
+            || npc_info.grade == "raid"
+            || npc_info.grade == "epic_raid"
+            || npc_info.grade == "commander")
+            && max_hp > 10_000
+            && !npc_name.is_empty()
+            && !npc_name.contains('_')
+            && npc_name.is_ascii()
+        {
+            info.push_immune = true;
+            (EntityKind::Boss(info), npc_name)
+        } else {
+            (EntityKind::Npc(info), npc_name)
+        }
+    } else {
+        (EntityKind::Npc(info), format!("{:x}", npc.object_id))
+    }
+}
+
+fn get_esther_from_npc_id(npc_id: u32) -> Option<Esther> {
+    meter_data()
+        .esther
+        .iter()
+        .find(|esther| esther.npc_ids.contains(&npc_id))
+        .cloned()
+}
+
+pub fn get_skill_class_id(skill_id: &u32) -> u32 {
+    if let Some(skill) = meter_data().skill.get(skill_id) {
+        skill.class_id
+    } else {
+        0
+    }
+}
+
+pub fn truncate_gear_level(gear_level: f32) -> f32 {
+    f32::trunc(gear_level * 100.) / 100.
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Entity {
+    pub id: u64,
+    pub name: String,
+    pub stats: HashMap<u8, i64>,
+    pub kind: EntityKind,
+}
+
+#[derive(Debug, Default, Clone)]
+pub enum EntityKind {
+    /// Referenced by a packet before we saw it spawn.
+    #[default]
+    Unknown,
+    Player(PlayerInfo),
+    Npc(NpcInfo),
+    Boss(NpcInfo),
+    Summon(SummonInfo),
+    Projectile(ProjectileInfo),
+    Trap(ProjectileInfo),
+    Esther(EstherInfo),
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct PlayerInfo {
+    pub character_id: u64,
+    pub class_id: u32,
+    pub gear_level: f32,
+    pub stance: u8,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct NpcInfo {
+    pub npc_id: u32,
+    pub level: u16,
+    pub grade: String,
+    pub push_immune: bool,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SummonInfo {
+    pub owner_id: u64,
+    pub npc: NpcInfo,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ProjectileInfo {
+    pub owner_id: u64,
+    pub skill_id: u32,
+    pub skill_effect_id: u32,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct EstherInfo {
+    pub npc_id: u32,
+}
+
+impl Entity {
+    pub fn unknown(id: u64) -> Self {
+        Self {
+            id,
+            name: format!("{:x}", id),
+            ..Default::default()
+        }
+    }
+
+    pub fn player(id: u64, name: String, info: PlayerInfo) -> Self {
+        Self {
+            id,
+            name,
+            kind: EntityKind::Player(info),
+            ..Default::default()
+        }
+    }
+
+    /// Type reported to the meter and stored with the encounter, traps count as projectiles.
+    pub fn entity_type(&self) -> EntityType {
+        match self.kind {
+            EntityKind::Unknown => EntityType::Unknown,
+            EntityKind::Player(_) => EntityType::Player,
+            EntityKind::Npc(_) => EntityType::Npc,
+            EntityKind::Boss(_) => EntityType::Boss,
+            EntityKind::Summon(_) => EntityType::Summon,
+            EntityKind::Projectile(_) | EntityKind::Trap(_) => EntityType::Projectile,
+            EntityKind::Esther(_) => EntityType::Esther,
+        }
+    }
+
+    pub fn is_player(&self) -> bool {
+        matches!(self.kind, EntityKind::Player(_))
+    }
+
+    pub fn is_boss(&self) -> bool {
+        matches!(self.kind, EntityKind::Boss(_))
+    }
+
+    pub fn as_player(&self) -> Option<&PlayerInfo> {
+        match &self.kind {
+            EntityKind::Player(player) => Some(player),
+            _ => None,
+        }
+    }
+
+    pub fn as_player_mut(&mut self) -> Option<&mut PlayerInfo> {
+        match &mut self.kind {
+            EntityKind::Player(player) => Some(player),
+            _ => None,
+        }
+    }
+
+    /// Projectiles and traps, the objects which carry the skill effect of a hit.
+    pub fn as_projectile(&self) -> Option<&ProjectileInfo> {
+        match &self.kind {
+            EntityKind::Projectile(projectile) | EntityKind::Trap(projectile) => Some(projectile),
+            _ => None,
+        }
+    }
+
+    /// The entity credited for damage done by this one.
+    pub fn owner_id(&self) -> Option<u64> {
+        match &self.kind {
+            EntityKind::Summon(summon) => Some(summon.owner_id),
+            EntityKind::Projectile(projectile) | EntityKind::Trap(projectile) => Some(projectile.owner_id),
+            _ => None,
+        }
+    }
+
+    pub fn character_id(&self) -> u64 {
+        self.as_player().map_or(0, |player| player.character_id)
+    }
+
+    pub fn class_id(&self) -> u32 {
+        self.as_player().map_or(0, |player| player.class_id)
+    }
+
+    pub fn gear_level(&self) -> f32 {
+        self.as_player().map_or(0.0, |player| player.gear_level)
+    }
+
+    pub fn npc_id(&self) -> u32 {
+        match &self.kind {
+            EntityKind::Npc(npc) | EntityKind::Boss(npc) => npc.npc_id,
+            EntityKind::Summon(summon) => summon.npc.npc_id,
+            EntityKind::Esther(esther) => esther.npc_id,
+            _ => 0,
+        }
+    }
+}
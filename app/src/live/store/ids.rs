@@ -1,5 +1,6 @@
 use hashbrown::HashMap;
 
+#[derive(Default)]
 pub struct IdTracker {
     character_id_to_entity_id: HashMap<u64, u64>,
     entity_id_to_character_id: HashMap<u64, u64>,
@@ -0,0 +1,450 @@
+pub mod entity;
+pub mod ids;
+pub mod party;
+pub mod status;
+
+use crate::live::store::entity::{
+    get_npc_kind_and_name, truncate_gear_level, Entity, EntityKind, EntityTracker, PlayerInfo,
+    SummonInfo,
+};
+use crate::live::store::ids::IdTracker;
+use crate::live::store::party::PartyTracker;
+use crate::live::store::status::{
+    build_status_effect, StatusEffectDetails, StatusEffectTargetType, StatusEffectType,
+    StatusTracker,
+};
+use crate::local::{LocalInfo, LocalPlayer};
+use crate::models::*;
+
+use chrono::{DateTime, Utc};
+use hashbrown::HashMap;
+use log::{info, warn};
+use crate::abstractions::packets::definitions::*;
+use crate::abstractions::packets::structures::{PCStruct, StatusEffectData};
+
+/// Single owner of everything known about the objects in the current zone.
+///
+/// The sub-stores don't hold references to each other, whenever an update spans
+/// several of them it goes through a method on this type instead.
+#[derive(Default)]
+pub struct StateStore {
+    pub ids: IdTracker,
+    pub parties: PartyTracker,
+    pub statuses: StatusTracker,
+    pub entities: EntityTracker,
+}
+
+impl StateStore {
+    pub fn new() -> Self {
+        Self {
+            ids: IdTracker::new(),
+            parties: PartyTracker::new(),
+            statuses: StatusTracker::new(),
+            entities: EntityTracker::new(),
+        }
+    }
+
+    pub fn init_env(&mut self, pkt: PKTInitEnv) -> Entity {
+        let local_entity_id = self.entities.local_entity_id;
+
+        if local_entity_id != 0 {
+            if let Some(party_id) = self.parties.entity_id_to_party_id.remove(&local_entity_id) {
+                self.parties
+                    .entity_id_to_party_id
+                    .insert(pkt.player_id, party_id);
+            }
+        }
+
+        let mut local_player = self
+            .entities
+            .get_entity_ref(local_entity_id)
+            .cloned()
+            .unwrap_or_else(|| Entity::player(
+                0,
+                "You".to_string(),
+                PlayerInfo {
+                    character_id: self.entities.local_character_id,
+                    ..Default::default()
+                },
+            ));
+
+        info!("init env: eid: {}->{}", local_entity_id, pkt.player_id);
+
+        local_player.id = pkt.player_id;
+        self.entities.local_entity_id = pkt.player_id;
+
+        self.entities.entities.clear();
+        self.entities.insert(local_player.clone());
+        self.ids.clear();
+        self.statuses.clear();
+        let character_id = local_player.character_id();
+        if character_id > 0 {
+            self.ids.add_mapping(character_id, local_player.id);
+            self.parties.complete_entry(character_id, local_player.id);
+        }
+        local_player
+    }
+
+    pub fn init_pc(&mut self, pkt: PKTInitPC) -> Entity {
+        let player = Entity {
+            id: pkt.player_id,
+            name: pkt.name,
+            stats: pkt
+                .stat_pairs
+                .iter()
+                .map(|sp| (sp.stat_type, sp.value))
+                .collect(),
+            kind: EntityKind::Player(PlayerInfo {
+                character_id: pkt.character_id,
+                class_id: pkt.class_id as u32,
+                gear_level: truncate_gear_level(pkt.gear_level),
+                ..Default::default()
+            }),
+        };
+
+        self.entities.local_entity_id = player.id;
+        self.entities.local_character_id = pkt.character_id;
+        self.entities.entities.clear();
+        self.entities.insert(player.clone());
+        self.ids.add_mapping(pkt.character_id, player.id);
+        self.parties.set_name(player.name.clone());
+        self.parties.complete_entry(pkt.character_id, player.id);
+        self.statuses.remove_local_object(player.id);
+        self.build_and_register_status_effects(pkt.status_effect_datas, player.id);
+        player
+    }
+
+    pub fn new_pc(&mut self, pc_struct: PCStruct) -> Entity {
+        let entity = Entity {
+            id: pc_struct.player_id,
+            name: pc_struct.name.clone(),
+            stats: pc_struct
+                .stat_pairs
+                .iter()
+                .map(|sp| (sp.stat_type, sp.value))
+                .collect(),
+            kind: EntityKind::Player(PlayerInfo {
+                character_id: pc_struct.character_id,
+                class_id: pc_struct.class_id as u32,
+                gear_level: truncate_gear_level(pc_struct.max_item_level), // todo?
+                ..Default::default()
+            }),
+        };
+
+        self.entities.insert(entity.clone());
+        if let Some(old_entity_id) = self.ids.get_entity_id(pc_struct.character_id) {
+            self.parties.change_entity_id(old_entity_id, entity.id);
+        }
+        self.ids
+            .add_mapping(pc_struct.character_id, pc_struct.player_id);
+        self.parties
+            .complete_entry(pc_struct.character_id, pc_struct.player_id);
+        let local_character_id = self.local_character_id();
+        self.statuses
+            .new_pc(&self.parties, pc_struct, local_character_id);
+        entity
+    }
+
+    pub fn new_npc(&mut self, pkt: PKTNewNpc, max_hp: i64) -> Entity {
+        let (kind, name) = get_npc_kind_and_name(&pkt.npc_struct, max_hp);
+        let npc = Entity {
+            id: pkt.npc_struct.object_id,
+            name,
+            stats: pkt
+                .npc_struct
+                .stat_pairs
+                .iter()
+                .map(|sp| (sp.stat_type, sp.value))
+                .collect(),
+            kind,
+        };
+        self.entities.insert(npc.clone());
+        self.statuses.remove_local_object(npc.id);
+        self.build_and_register_status_effects(pkt.npc_struct.status_effect_datas, npc.id);
+        npc
+    }
+
+    pub fn new_npc_summon(&mut self, pkt: PKTNewNpcSummon, max_hp: i64) -> Entity {
+        let (kind, name) = get_npc_kind_and_name(&pkt.npc_struct, max_hp);
+        // bosses and esthers keep their own kind, only regular npcs are credited to the owner
+        let kind = match kind {
+            EntityKind::Npc(npc) => EntityKind::Summon(SummonInfo {
+                owner_id: pkt.owner_id,
+                npc,
+            }),
+            kind => kind,
+        };
+        let npc = Entity {
+            id: pkt.npc_struct.object_id,
+            name,
+            stats: pkt
+                .npc_struct
+                .stat_pairs
+                .iter()
+                .map(|sp| (sp.stat_type, sp.value))
+                .collect(),
+            kind,
+        };
+        self.entities.insert(npc.clone());
+        self.statuses.remove_local_object(npc.id);
+        self.build_and_register_status_effects(pkt.npc_struct.status_effect_datas, npc.id);
+        npc
+    }
+
+    /// Removes an object which left the zone along with the status effects tracked on it.
+    pub fn remove_object(&mut self, object_id: u64) {
+        self.entities.remove(object_id);
+        self.statuses.remove_local_object(object_id);
+    }
+
+    pub fn party_status_effect_add(
+        &mut self,
+        pkt: PKTPartyStatusEffectAddNotify,
+        entities: &HashMap<String, EncounterEntity>,
+    ) -> Vec<StatusEffectDetails> {
+        let timestamp = Utc::now();
+        let mut shields: Vec<StatusEffectDetails> = Vec::new();
+        for sed in pkt.status_effect_datas {
+            let entity = self.entities.get_source_entity(sed.source_id);
+            let encounter_entity = entities.get(&entity.name);
+            let status_effect = build_status_effect(
+                sed,
+                pkt.character_id,
+                entity.id,
+                StatusEffectTargetType::Party,
+                timestamp,
+                encounter_entity,
+            );
+            if status_effect.status_effect_type == StatusEffectType::Shield {
+                shields.push(status_effect.clone());
+            }
+            self.statuses.register_status_effect(status_effect);
+        }
+        shields
+    }
+
+    pub fn party_status_effect_remove(
+        &mut self,
+        pkt: PKTPartyStatusEffectRemoveNotify,
+    ) -> (
+        bool,
+        Vec<StatusEffectDetails>,
+        Vec<StatusEffectDetails>,
+        bool,
+    ) {
+        self.statuses.remove_status_effects(
+            pkt.character_id,
+            pkt.status_effect_instance_ids,
+            pkt.reason,
+            StatusEffectTargetType::Party,
+        )
+    }
+
+    pub fn party_info(&mut self, pkt: PKTPartyInfo, local_info: &LocalInfo) {
+        let local_entity_id = self.entities.local_entity_id;
+        let mut unknown_local = if let Some(local_player) = self.entities.get_entity_ref(local_entity_id)
+        {
+            local_player.name.is_empty()
+                || local_player.name == "You"
+                || local_player.name.starts_with('0')
+        } else {
+            true
+        };
+
+        self.parties.remove_party_mappings(pkt.party_instance_id);
+
+        let most_likely_local_name = if unknown_local {
+            let party_members = pkt
+                .party_member_datas
+                .iter()
+                .map(|m| m.character_id)
+                .collect::<Vec<u64>>();
+            let mut party_locals = local_info
+                .local_players
+                .iter()
+                .filter_map(|(k, v)| {
+                    if party_members.contains(k) {
+                        Some(v)
+                    } else {
+                        None
+                    }
+                })
+                .collect::<Vec<&LocalPlayer>>();
+            party_locals.sort_by(|a, b| b.count.cmp(&a.count));
+            party_locals
+                .first()
+                .map_or_else(String::new, |p| p.name.clone())
+        } else {
+            "".to_string()
+        };
+
+        for member in pkt.party_member_datas {
+            if unknown_local && member.name == most_likely_local_name {
+                if let Some(local_player) = self.entities.entities.get_mut(&local_entity_id) {
+                    unknown_local = false;
+                    warn!(
+                        "Unknown local player, inferring from cache: {}",
+                        member.name
+                    );
+                    local_player.kind = EntityKind::Player(PlayerInfo {
+                        character_id: member.character_id,
+                        class_id: member.class_id as u32,
+                        gear_level: truncate_gear_level(member.gear_level),
+                        ..Default::default()
+                    });
+                    local_player.name.clone_from(&member.name);
+                    self.ids.add_mapping(member.character_id, local_entity_id);
+                    self.parties.set_name(member.name.clone());
+                }
+            }
+
+            let entity_id = self.ids.get_entity_id(member.character_id);
+
+            if let Some(entity_id) = entity_id {
+                if let Some(entity) = self.entities.entities.get_mut(&entity_id) {
+                    if entity.name == member.name {
+                        if let Some(player) = entity.as_player_mut() {
+                            player.gear_level = truncate_gear_level(member.gear_level);
+                            player.class_id = member.class_id as u32;
+                        }
+                    }
+                }
+            }
+
+            self.parties.add(
+                &self.ids,
+                pkt.raid_instance_id,
+                pkt.party_instance_id,
+                member.character_id,
+                entity_id.unwrap_or_default(),
+                Some(member.name.clone()),
+            );
+        }
+    }
+
+    pub fn build_and_register_status_effect(
+        &mut self,
+        sed: &StatusEffectData,
+        target_id: u64,
+        timestamp: DateTime<Utc>,
+        entities: Option<&HashMap<String, EncounterEntity>>,
+    ) -> StatusEffectDetails {
+        let source_entity = self.entities.get_source_entity(sed.source_id);
+        let source_encounter_entity =
+            entities.and_then(|entities| entities.get(&source_entity.name));
+        let status_effect = build_status_effect(
+            sed.clone(),
+            target_id,
+            source_entity.id,
+            StatusEffectTargetType::Local,
+            timestamp,
+            source_encounter_entity,
+        );
+
+        self.statuses.register_status_effect(status_effect.clone());
+
+        status_effect
+    }
+
+    fn build_and_register_status_effects(&mut self, seds: Vec<StatusEffectData>, target_id: u64) {
+        let timestamp = Utc::now();
+        for sed in seds.into_iter() {
+            self.build_and_register_status_effect(&sed, target_id, timestamp, None);
+        }
+    }
+
+    /// Status effects active on the source and on the target at the time of a hit.
+    pub fn get_status_effects(
+        &mut self,
+        source_entity: &Entity,
+        target_entity: &Entity,
+    ) -> (Vec<StatusEffectDetails>, Vec<StatusEffectDetails>) {
+        let local_character_id = self.ids.get_local_character_id(self.entities.local_entity_id);
+        self.statuses
+            .get_status_effects(&self.parties, source_entity, target_entity, local_character_id)
+    }
+
+    pub fn sync_status_effect(
+        &mut self,
+        instance_id: u32,
+        character_id: u64,
+        object_id: u64,
+        value: u64,
+    ) -> (Option<StatusEffectDetails>, u64) {
+        self.statuses.sync_status_effect(
+            &self.parties,
+            instance_id,
+            character_id,
+            object_id,
+            value,
+            self.entities.local_character_id,
+        )
+    }
+
+    pub fn add_party_member(
+        &mut self,
+        raid_instance_id: u32,
+        party_id: u32,
+        character_id: u64,
+        entity_id: u64,
+        name: Option<String>,
+    ) {
+        self.parties.add(
+            &self.ids,
+            raid_instance_id,
+            party_id,
+            character_id,
+            entity_id,
+            name,
+        );
+    }
+
+    /// Party status effects are keyed by character id, everything else by entity id.
+    pub fn status_effect_target(&mut self, status_effect: &StatusEffectDetails) -> Entity {
+        let target_id = if status_effect.target_type == StatusEffectTargetType::Party {
+            self.ids
+                .get_entity_id(status_effect.target_id)
+                .unwrap_or_default()
+        } else {
+            status_effect.target_id
+        };
+        self.entities.get_source_entity(target_id)
+    }
+
+    pub fn local_character_id(&self) -> u64 {
+        if self.entities.local_character_id != 0 {
+            self.entities.local_character_id
+        } else {
+            self.ids.get_local_character_id(self.entities.local_entity_id)
+        }
+    }
+
+    /// Names of the players in each party, ordered by party id.
+    pub fn party_members(&self) -> Vec<Vec<String>> {
+        let mut party_info = HashMap::new();
+
+        for (entity_id, party_id) in &self.parties.entity_id_to_party_id {
+            let members = party_info.entry(*party_id).or_insert_with(Vec::new);
+            if let Some(entity) = self.entities.get_entity_ref(*entity_id) {
+                if entity.character_id() > 0
+                    && entity.class_id() > 0
+                    && entity
+                        .name
+                        .chars()
+                        .next()
+                        .unwrap_or_default()
+                        .is_uppercase()
+                {
+                    members.push(entity.name.clone());
+                }
+            }
+        }
+
+        let mut sorted_parties = party_info.into_iter().collect::<Vec<(u32, Vec<String>)>>();
+        sorted_parties.sort_unstable_by_key(|&(party_id, _)| party_id);
+        sorted_parties
+            .into_iter()
+            .map(|(_, members)| members)
+            .collect()
+    }
+}
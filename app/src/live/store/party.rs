@@ -1,11 +1,8 @@
-use crate::live::id_tracker::IdTracker;
+use crate::live::store::ids::IdTracker;
 use hashbrown::{HashMap, HashSet};
-use std::cell::RefCell;
-use std::rc::Rc;
 
+#[derive(Default)]
 pub struct PartyTracker {
-    id_tracker: Rc<RefCell<IdTracker>>,
-
     pub character_id_to_party_id: HashMap<u64, u32>,
     pub entity_id_to_party_id: HashMap<u64, u32>,
     raid_instance_to_party_ids: HashMap<u32, HashSet<u32>>,
@@ -13,9 +10,8 @@ pub struct PartyTracker {
 }
 
 impl PartyTracker {
-    pub fn new(id_tracker: Rc<RefCell<IdTracker>>) -> Self {
+    pub fn new() -> Self {
         Self {
-            id_tracker,
             character_id_to_party_id: HashMap::new(),
             entity_id_to_party_id: HashMap::new(),
             raid_instance_to_party_ids: HashMap::new(),
@@ -29,6 +25,7 @@ impl PartyTracker {
 
     pub fn add(
         &mut self,
+        id_tracker: &IdTracker,
         raid_instance_id: u32,
         party_id: u32,
         mut character_id: u64,
@@ -39,15 +36,11 @@ impl PartyTracker {
             return;
         }
         if character_id > 0 && entity_id == 0 {
-            entity_id = self
-                .id_tracker
-                .borrow()
+            entity_id = id_tracker
                 .get_entity_id(character_id)
                 .unwrap_or(0);
         } else if character_id == 0 && entity_id > 0 {
-            character_id = self
-                .id_tracker
-                .borrow()
+            character_id = id_tracker
                 .get_character_id(entity_id)
                 .unwrap_or(0);
         }
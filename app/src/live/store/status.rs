@@ -1,18 +1,16 @@
 #![allow(dead_code)]
 
-use crate::data::SKILL_BUFF_DATA;
-use crate::live::entity_tracker::Entity;
-use crate::live::party_tracker::PartyTracker;
-use crate::live::status_tracker::StatusEffectBuffCategory::{BattleItem, Bracelet, Elixir, Etc};
-use crate::live::status_tracker::StatusEffectCategory::Debuff;
-use crate::live::status_tracker::StatusEffectShowType::All;
+use crate::data::meter_data;
+use crate::live::store::entity::Entity;
+use crate::live::store::party::PartyTracker;
+use crate::live::store::status::StatusEffectBuffCategory::{BattleItem, Bracelet, Elixir, Etc};
+use crate::live::store::status::StatusEffectCategory::Debuff;
+use crate::live::store::status::StatusEffectShowType::All;
 use crate::live::utils::get_new_id;
-use crate::models::{EncounterEntity, EntityType};
+use crate::models::EncounterEntity;
 use chrono::{DateTime, Duration, Utc};
 use hashbrown::HashMap;
 use crate::abstractions::packets::structures::{PCStruct, StatusEffectData};
-use std::cell::RefCell;
-use std::rc::Rc;
 
 // expire buff after 1 min delay
 const TIMEOUT_DELAY_MS: i64 = 60_000;
@@ -20,24 +18,23 @@ const WORKSHOP_BUFF_ID: u32 = 9701;
 
 pub type StatusEffectRegistry = HashMap<u32, StatusEffectDetails>;
 
+#[derive(Default)]
 pub struct StatusTracker {
-    party_tracker: Rc<RefCell<PartyTracker>>,
     local_status_effect_registry: HashMap<u64, StatusEffectRegistry>,
     party_status_effect_registry: HashMap<u64, StatusEffectRegistry>,
 }
 
 impl StatusTracker {
-    pub fn new(party_tracker: Rc<RefCell<PartyTracker>>) -> Self {
+    pub fn new() -> Self {
         Self {
-            party_tracker,
             local_status_effect_registry: HashMap::new(),
             party_status_effect_registry: HashMap::new(),
         }
     }
 
-    pub fn new_pc(&mut self, pc_struct: PCStruct, local_character_id: u64) {
+    pub fn new_pc(&mut self, party_tracker: &PartyTracker, pc_struct: PCStruct, local_character_id: u64) {
         let use_party_status_effects =
-            self.should_use_party_status_effect(pc_struct.character_id, local_character_id);
+            should_use_party_status_effect(party_tracker, pc_struct.character_id, local_character_id);
         if use_party_status_effects {
             self.remove_party_object(pc_struct.character_id);
         } else {
@@ -159,13 +156,14 @@ impl StatusTracker {
 
     pub fn sync_status_effect(
         &mut self,
+        party_tracker: &PartyTracker,
         instance_id: u32,
         character_id: u64,
         object_id: u64,
         value: u64,
         local_character_id: u64,
     ) -> (Option<StatusEffectDetails>, u64) {
-        let use_party = self.should_use_party_status_effect(character_id, local_character_id);
+        let use_party = should_use_party_status_effect(party_tracker, character_id, local_character_id);
         let (target_id, sett) = if use_party {
             (character_id, StatusEffectTargetType::Party)
         } else {
@@ -197,20 +195,21 @@ impl StatusTracker {
 
     pub fn get_status_effects(
         &mut self,
+        party_tracker: &PartyTracker,
         source_entity: &Entity,
         target_entity: &Entity,
         local_character_id: u64,
     ) -> (Vec<StatusEffectDetails>, Vec<StatusEffectDetails>) {
         let timestamp = Utc::now();
 
-        let use_party_for_source = if source_entity.entity_type == EntityType::Player {
-            self.should_use_party_status_effect(source_entity.character_id, local_character_id)
+        let use_party_for_source = if source_entity.is_player() {
+            should_use_party_status_effect(party_tracker, source_entity.character_id(), local_character_id)
         } else {
             false
         };
         // println!("use_party_for_source: {:?}", use_party_for_source);
         let (source_id, source_type) = if use_party_for_source {
-            (source_entity.character_id, StatusEffectTargetType::Party)
+            (source_entity.character_id(), StatusEffectTargetType::Party)
         } else {
             (source_entity.id, StatusEffectTargetType::Local)
         };
@@ -219,34 +218,34 @@ impl StatusTracker {
         let status_effects_on_source =
             self.actually_get_status_effects(source_id, source_type, timestamp);
 
-        let use_party_for_target = if source_entity.entity_type == EntityType::Player {
-            self.should_use_party_status_effect(target_entity.character_id, local_character_id)
+        let use_party_for_target = if source_entity.is_player() {
+            should_use_party_status_effect(party_tracker, target_entity.character_id(), local_character_id)
         } else {
             false
         };
         // println!("use_party_for_target: {:?}", use_party_for_target);
-        let source_party_id = self
-            .party_tracker
-            .borrow()
+        let source_party_id = party_tracker
             .entity_id_to_party_id
             .get(&source_entity.id)
             .cloned();
         // println!("use_party_for_target: {:?}, source_party_id: {:?}", use_party_for_target, source_party_id);
         let mut status_effects_on_target = match (use_party_for_target, source_party_id) {
             (true, Some(source_party_id)) => self.get_status_effects_from_party(
-                target_entity.character_id,
+                party_tracker,
+                target_entity.character_id(),
                 StatusEffectTargetType::Party,
                 &source_party_id,
                 timestamp,
             ),
             (false, Some(source_party_id)) => self.get_status_effects_from_party(
+                party_tracker,
                 target_entity.id,
                 StatusEffectTargetType::Local,
                 &source_party_id,
                 timestamp,
             ),
             (true, None) => self.actually_get_status_effects(
-                target_entity.character_id,
+                target_entity.character_id(),
                 StatusEffectTargetType::Party,
                 timestamp,
             ),
@@ -291,6 +290,7 @@ impl StatusTracker {
 
     pub fn get_status_effects_from_party(
         &mut self,
+        party_tracker: &PartyTracker,
         target_id: u64,
         sett: StatusEffectTargetType,
         party_id: &u32,
@@ -308,7 +308,6 @@ impl StatusTracker {
 
         // println!("ser before: {:?}", ser);
         ser.retain(|_, se| se.expire_at.is_none_or(|expire_at| expire_at > timestamp));
-        let party_tracker = self.party_tracker.borrow();
         ser.values()
             .filter(|x| {
                 is_valid_for_raid(x)
@@ -323,26 +322,21 @@ impl StatusTracker {
             .collect()
     }
 
-    fn should_use_party_status_effect(&self, character_id: u64, local_character_id: u64) -> bool {
-        let party_tracker = self.party_tracker.borrow();
-        let local_player_party_id = party_tracker
-            .character_id_to_party_id
-            .get(&local_character_id);
-        let affected_player_party_id = party_tracker.character_id_to_party_id.get(&character_id);
-        // println!("party character_id_to_party_id: {:?}", party_tracker.character_id_to_party_id);
-        // println!("character_id: {}, local_character_id: {}", character_id, local_character_id);
-        // println!(
-        //     "local_player_party_id: {:?}, affected_player_party_id: {:?}",
-        //     local_player_party_id, affected_player_party_id);
-
-        match (
-            local_player_party_id,
-            affected_player_party_id,
-            character_id == local_character_id,
-        ) {
-            (Some(local_party), Some(affected_party), false) => local_party == affected_party,
-            _ => false,
+    /// Every unexpired status effect on a player, tracked locally or through the party.
+    pub fn get_active_status_effects(&mut self, entity: &Entity) -> Vec<StatusEffectDetails> {
+        let timestamp = Utc::now();
+        let mut status_effects =
+            self.actually_get_status_effects(entity.id, StatusEffectTargetType::Local, timestamp);
+
+        if entity.character_id() > 0 {
+            status_effects.extend(self.actually_get_status_effects(
+                entity.character_id(),
+                StatusEffectTargetType::Party,
+                timestamp,
+            ));
         }
+
+        status_effects
     }
 
     pub fn clear(&mut self) {
@@ -351,6 +345,27 @@ impl StatusTracker {
     }
 }
 
+fn should_use_party_status_effect(party_tracker: &PartyTracker, character_id: u64, local_character_id: u64) -> bool {
+    let local_player_party_id = party_tracker
+        .character_id_to_party_id
+        .get(&local_character_id);
+    let affected_player_party_id = party_tracker.character_id_to_party_id.get(&character_id);
+    // println!("party character_id_to_party_id: {:?}", party_tracker.character_id_to_party_id);
+    // println!("character_id: {}, local_character_id: {}", character_id, local_character_id);
+    // println!(
+    //     "local_player_party_id: {:?}, affected_player_party_id: {:?}",
+    //     local_player_party_id, affected_player_party_id);
+
+    match (
+        local_player_party_id,
+        affected_player_party_id,
+        character_id == local_character_id,
+    ) {
+        (Some(local_party), Some(affected_party), false) => local_party == affected_party,
+        _ => false,
+    }
+}
+
 fn is_valid_for_raid(status_effect: &StatusEffectDetails) -> bool {
     (status_effect.buff_category == BattleItem
         || status_effect.buff_category == Bracelet
@@ -376,7 +391,9 @@ pub fn build_status_effect(
     let mut name = "Unknown".to_string();
     let mut db_target_type = "".to_string();
     let mut custom_id = 0;
-    if let Some(effect) = SKILL_BUFF_DATA.get(&se_data.status_effect_id) {
+    let assets = meter_data();
+
+    if let Some(effect) = assets.skill_buff.get(&se_data.status_effect_id) {
         name = effect.name.clone().unwrap_or_default();
         if effect.category.as_str() == "debuff" {
             status_effect_category = Debuff
@@ -2,36 +2,34 @@
 
 use crate::data::*;
 use crate::live::encounter_state::EncounterState;
-use crate::live::entity_tracker::{Entity, EntityTracker};
-use crate::live::id_tracker::IdTracker;
-use crate::live::party_tracker::PartyTracker;
 use crate::live::skill_tracker::{CastEvent, SkillTracker};
+use crate::live::store::entity::Entity;
+use crate::live::store::status::{StatusEffectCategory, StatusEffectDetails};
+use crate::live::store::StateStore;
 use log::*;
-use crate::live::status_tracker::{StatusEffectDetails, StatusEffectTargetType};
 use crate::models::*;
+use chrono::{DateTime, Utc};
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use hashbrown::HashMap;
 use serde::Serialize;
-use std::cell::RefCell;
 use std::cmp::Ordering;
 use std::io::Write;
-use std::rc::Rc;
 
 pub fn encounter_entity_from_entity(entity: &Entity) -> EncounterEntity {
     let mut e = EncounterEntity {
         id: entity.id,
         name: entity.name.clone(),
-        entity_type: entity.entity_type,
-        npc_id: entity.npc_id,
-        class_id: entity.class_id,
-        class: get_class_from_id(&entity.class_id),
-        gear_score: entity.gear_level,
+        entity_type: entity.entity_type(),
+        npc_id: entity.npc_id(),
+        class_id: entity.class_id(),
+        class: get_class_from_id(&entity.class_id()),
+        gear_score: entity.gear_level(),
         ..Default::default()
     };
 
-    if entity.character_id > 0 {
-        e.character_id = entity.character_id;
+    if entity.character_id() > 0 {
+        e.character_id = entity.character_id();
     }
 
     e
@@ -39,11 +37,11 @@ pub fn encounter_entity_from_entity(entity: &Entity) -> EncounterEntity {
 
 pub fn update_player_entity(old: &mut EncounterEntity, new: &Entity) {
     old.id = new.id;
-    old.character_id = new.character_id;
+    old.character_id = new.character_id();
     old.name.clone_from(&new.name);
-    old.class_id = new.class_id;
-    old.class = get_class_from_id(&new.class_id);
-    old.gear_score = new.gear_level;
+    old.class_id = new.class_id();
+    old.class = get_class_from_id(&new.class_id());
+    old.gear_score = new.gear_level();
 }
 
 pub fn is_support(entity: &EncounterEntity) -> bool {
@@ -66,7 +64,8 @@ pub fn is_support_spec(spec: &str) -> bool {
 }
 
 pub fn is_battle_item(skill_effect_id: &u32, _item_type: &str) -> bool {
-    if let Some(item) = SKILL_EFFECT_DATA.get(skill_effect_id) {
+    let assets = meter_data();
+    if let Some(item) = assets.skill_effect.get(skill_effect_id) {
         if let Some(category) = item.item_type.as_ref() {
             return category == "useup";
         }
@@ -75,7 +74,8 @@ pub fn is_battle_item(skill_effect_id: &u32, _item_type: &str) -> bool {
 }
 
 pub fn get_status_effect_data(buff_id: u32, source_skill: Option<u32>) -> Option<StatusEffect> {
-    let buff = SKILL_BUFF_DATA.get(&buff_id);
+    let assets = meter_data();
+    let buff = assets.skill_buff.get(&buff_id);
     if buff.is_none() || buff.unwrap().icon_show_type.clone().unwrap_or_default() == "none" {
         return None;
     }
@@ -118,15 +118,15 @@ pub fn get_status_effect_data(buff_id: u32, source_skill: Option<u32>) -> Option
     {
         if let Some(buff_source_skills) = buff.source_skills.as_ref() {
             if let Some(source_skill) = source_skill {
-                let skill = SKILL_DATA.get(&source_skill);
-                get_summon_source_skill(skill, &mut status_effect);
+                let skill = assets.skill.get(&source_skill);
+                get_summon_source_skill(&assets, skill, &mut status_effect);
             } else {
                 // get the first skill that has a name, fall back to first if none
                 let source_skill = {
                     let mut first_any = None;
                     let mut first_named = None;
                     for id in buff_source_skills {
-                        if let Some(skill) = SKILL_DATA.get(id) {
+                        if let Some(skill) = assets.skill.get(id) {
                             if first_any.is_none() {
                                 first_any = Some(skill);
                             }
@@ -138,21 +138,21 @@ pub fn get_status_effect_data(buff_id: u32, source_skill: Option<u32>) -> Option
                     }
                     first_named.or(first_any)
                 };
-                get_summon_source_skill(source_skill, &mut status_effect);
+                get_summon_source_skill(&assets, source_skill, &mut status_effect);
             }
-        } else if let Some(buff_source_skill) = SKILL_DATA.get(&(buff_id / 10)) {
+        } else if let Some(buff_source_skill) = assets.skill.get(&(buff_id / 10)) {
             status_effect.source.skill = Some(buff_source_skill.clone());
-        } else if let Some(buff_source_skill) = SKILL_DATA.get(&((buff_id / 100) * 10)) {
+        } else if let Some(buff_source_skill) = assets.skill.get(&((buff_id / 100) * 10)) {
             status_effect.source.skill = Some(buff_source_skill.clone());
         } else {
             let skill_id = buff.unique_group / 10;
-            let buff_source_skill = SKILL_DATA.get(&skill_id);
+            let buff_source_skill = assets.skill.get(&skill_id);
             status_effect.source.skill = buff_source_skill.cloned();
         }
     } else if buff_category == "set" && buff.set_name.is_some() {
         status_effect.source.set_name.clone_from(&buff.set_name);
     } else if buff_category == "battleitem" {
-        if let Some(buff_source_item) = SKILL_EFFECT_DATA.get(&buff_id) {
+        if let Some(buff_source_item) = assets.skill_effect.get(&buff_id) {
             if let Some(item_name) = buff_source_item.item_name.as_ref() {
                 status_effect.source.name.clone_from(item_name);
             }
@@ -168,12 +168,12 @@ pub fn get_status_effect_data(buff_id: u32, source_skill: Option<u32>) -> Option
     Some(status_effect)
 }
 
-fn get_summon_source_skill(skill: Option<&SkillData>, status_effect: &mut StatusEffect) {
+fn get_summon_source_skill(assets: &MeterData, skill: Option<&SkillData>, status_effect: &mut StatusEffect) {
     if let Some(skill) = skill {
         if let Some(summon_skills) = skill.summon_source_skills.as_ref() {
             let summon_source_skill = summon_skills.first().unwrap_or(&0);
             if *summon_source_skill > 0 {
-                if let Some(summon_skill) = SKILL_DATA.get(summon_source_skill) {
+                if let Some(summon_skill) = assets.skill.get(summon_source_skill) {
                     status_effect.source.skill = Some(summon_skill.clone());
                 }
             }
@@ -184,6 +184,7 @@ fn get_summon_source_skill(skill: Option<&SkillData>, status_effect: &mut Status
 }
 
 pub fn get_status_effect_buff_type_flags(buff: &SkillBuffData) -> u32 {
+    let assets = meter_data();
     let dmg_buffs = [
         "weaken_defense",
         "weaken_resistance",
@@ -219,7 +220,7 @@ pub fn get_status_effect_buff_type_flags(buff: &SkillBuffData) -> u32 {
             let key_stat_str = option.key_stat.as_str();
             let option_type = option.option_type.as_str();
             if option_type == "stat" {
-                let stat = STAT_TYPE_MAP.get(key_stat_str);
+                let stat = assets.stat_type.get(key_stat_str);
                 if stat.is_none() {
                     continue;
                 }
@@ -258,7 +259,7 @@ pub fn get_status_effect_buff_type_flags(buff: &SkillBuffData) -> u32 {
                 .contains(&key_stat_str)
                 {
                     buff_type |= StatusEffectBuffTypeFlags::HP;
-                } else if STAT_TYPE_MAP["def"] <= stat && stat <= STAT_TYPE_MAP["magical_inc_rate"]
+                } else if assets.stat_type["def"] <= stat && stat <= assets.stat_type["magical_inc_rate"]
                     || ["endurance", "endurance_x"].contains(&option.key_stat.as_str())
                 {
                     if buff.category == "buff" && option.value >= 0
@@ -268,8 +269,8 @@ pub fn get_status_effect_buff_type_flags(buff: &SkillBuffData) -> u32 {
                     } else {
                         buff_type |= StatusEffectBuffTypeFlags::DEFENSE;
                     }
-                } else if STAT_TYPE_MAP["move_speed"] <= stat
-                    && stat <= STAT_TYPE_MAP["vehicle_move_speed_rate"]
+                } else if assets.stat_type["move_speed"] <= stat
+                    && stat <= assets.stat_type["vehicle_move_speed_rate"]
                 {
                     buff_type |= StatusEffectBuffTypeFlags::MOVESPEED;
                 }
@@ -286,10 +287,10 @@ pub fn get_status_effect_buff_type_flags(buff: &SkillBuffData) -> u32 {
                     .contains(&key_stat_str)
                 {
                     buff_type |= StatusEffectBuffTypeFlags::CRIT;
-                } else if STAT_TYPE_MAP["attack_power_sub_rate_1"] <= stat
-                    && stat <= STAT_TYPE_MAP["skill_damage_sub_rate_2"]
-                    || STAT_TYPE_MAP["fire_dam_rate"] <= stat
-                        && stat <= STAT_TYPE_MAP["elements_dam_rate"]
+                } else if assets.stat_type["attack_power_sub_rate_1"] <= stat
+                    && stat <= assets.stat_type["skill_damage_sub_rate_2"]
+                    || assets.stat_type["fire_dam_rate"] <= stat
+                        && stat <= assets.stat_type["elements_dam_rate"]
                     || [
                         "str",
                         "agi",
@@ -344,7 +345,7 @@ pub fn get_status_effect_buff_type_flags(buff: &SkillBuffData) -> u32 {
             } else if ["skill_mana_reduction", "mana_reduction"].contains(&option_type) {
                 buff_type |= StatusEffectBuffTypeFlags::RESOURCE;
             } else if option_type == "combat_effect" {
-                if let Some(combat_effect) = COMBAT_EFFECT_DATA.get(&option.key_index) {
+                if let Some(combat_effect) = assets.combat_effect.get(&option.key_index) {
                     for effect in combat_effect.effects.iter() {
                         for action in effect.actions.iter() {
                             if [
@@ -387,6 +388,8 @@ pub fn get_skill_name_and_icon(
     skill_tracker: &SkillTracker,
     entity_id: u64,
 ) -> SkillDetails {
+    let assets = meter_data();
+
     if (skill_id == 0) && (skill_effect_id == 0) {
         (
             "Bleed".to_string(),
@@ -396,7 +399,7 @@ pub fn get_skill_name_and_icon(
             false,
         )
     } else if (skill_effect_id != 0) && (skill_id == 0) {
-        return if let Some(effect) = SKILL_EFFECT_DATA.get(&skill_effect_id) {
+        return if let Some(effect) = assets.skill_effect.get(&skill_effect_id) {
             // if ValueJ is greater than 1,
             // 1 = esther, 2 = fixed, 3 = not used, 4 = orb power
             // these effects are not affected by crits or buffs
@@ -412,7 +415,7 @@ pub fn get_skill_name_and_icon(
                 );
             }
             if let Some(source_skill) = effect.source_skills.as_ref() {
-                if let Some(skill) = SKILL_DATA.get(source_skill.iter().min().unwrap_or(&0)) {
+                if let Some(skill) = assets.skill.get(source_skill.iter().min().unwrap_or(&0)) {
                     return (
                         skill.name.clone().unwrap_or(skill.id.to_string()),
                         skill.icon.clone().unwrap_or_default(),
@@ -421,7 +424,7 @@ pub fn get_skill_name_and_icon(
                         skill.is_hyper_awakening,
                     );
                 }
-            } else if let Some(skill) = SKILL_DATA.get(&(skill_effect_id / 10)) {
+            } else if let Some(skill) = assets.skill.get(&(skill_effect_id / 10)) {
                 return (
                     skill.name.clone().unwrap_or(skill.id.to_string()),
                     skill.icon.clone().unwrap_or_default(),
@@ -440,7 +443,7 @@ pub fn get_skill_name_and_icon(
             (skill_id.to_string(), "".to_string(), None, false, false)
         };
     } else {
-        return if let Some(skill) = SKILL_DATA.get(&skill_id) {
+        return if let Some(skill) = assets.skill.get(&skill_id) {
             if let Some(summon_source_skill) = skill.summon_source_skills.as_ref() {
                 for source in summon_source_skill {
                     if skill_tracker
@@ -448,7 +451,7 @@ pub fn get_skill_name_and_icon(
                         .get(&(entity_id, *source))
                         .is_some()
                     {
-                        if let Some(skill) = SKILL_DATA.get(source) {
+                        if let Some(skill) = assets.skill.get(source) {
                             return (
                                 skill.name.clone().unwrap_or(skill.id.to_string()) + " (Summon)",
                                 skill.icon.clone().unwrap_or_default(),
@@ -459,7 +462,7 @@ pub fn get_skill_name_and_icon(
                         }
                     }
                 }
-                if let Some(skill) = SKILL_DATA.get(summon_source_skill.iter().min().unwrap_or(&0))
+                if let Some(skill) = assets.skill.get(summon_source_skill.iter().min().unwrap_or(&0))
                 {
                     (
                         skill.name.clone().unwrap_or(skill.id.to_string()) + " (Summon)",
@@ -472,7 +475,7 @@ pub fn get_skill_name_and_icon(
                     (skill_id.to_string(), "".to_string(), None, false, false)
                 }
             } else if let Some(source_skill) = skill.source_skills.as_ref() {
-                if let Some(skill) = SKILL_DATA.get(source_skill.iter().min().unwrap_or(&0)) {
+                if let Some(skill) = assets.skill.get(source_skill.iter().min().unwrap_or(&0)) {
                     (
                         skill.name.clone().unwrap_or(skill.id.to_string()),
                         skill.icon.clone().unwrap_or_default(),
@@ -492,7 +495,7 @@ pub fn get_skill_name_and_icon(
                     skill.is_hyper_awakening,
                 )
             }
-        } else if let Some(skill) = SKILL_DATA.get(&(skill_id - (skill_id % 10))) {
+        } else if let Some(skill) = assets.skill.get(&(skill_id - (skill_id % 10))) {
             (
                 skill.name.clone().unwrap_or(skill.id.to_string()),
                 skill.icon.clone().unwrap_or_default(),
@@ -637,10 +640,11 @@ pub fn get_engravings(engraving_ids: &Option<Vec<u32>>) -> Option<Vec<String>> {
         Some(engravings) => engravings,
         None => return None,
     };
+    let assets = meter_data();
     let mut engravings: Vec<String> = Vec::new();
 
     for engraving_id in ids.iter() {
-        if let Some(engraving_data) = ENGRAVING_DATA.get(engraving_id) {
+        if let Some(engraving_data) = assets.engraving.get(engraving_id) {
             engravings.push(engraving_data.name.clone().unwrap_or("Unknown".to_string()));
         }
     }
@@ -780,6 +784,22 @@ where
     e.finish().expect("unable to compress json")
 }
 
+/// Remaining hp of the current boss in the `0..=1` range, taken from the boss hp log
+/// and falling back to the boss entity.
+pub fn get_boss_hp(encounter: &Encounter, boss_hp_log: &HashMap<String, Vec<BossHpLog>>) -> Option<f32> {
+    let boss_name = &encounter.current_boss_name;
+
+    if let Some(last) = boss_hp_log.get(boss_name).and_then(|log| log.last()) {
+        return Some(last.p);
+    }
+
+    encounter
+        .entities
+        .get(boss_name)
+        .filter(|boss| boss.max_hp > 0)
+        .map(|boss| boss.current_hp.max(0) as f32 / boss.max_hp as f32)
+}
+
 pub fn update_current_boss_name(boss_name: &str) -> String {
     match boss_name {
         "Chaos Lightning Dragon Jade" => "Argeos",
@@ -795,294 +815,114 @@ pub fn get_player_spec(
     player: &EncounterEntity,
     buffs: &HashMap<u32, StatusEffect>,
     skip_min_check: bool,
-) -> String {
-    if !skip_min_check && player.skills.len() < 8 {
-        return "Unknown".to_string();
+) -> SpecMatch {
+    let assets = meter_data();
+
+    // meter-data failed to load
+    if !assets.is_loaded() {
+        return SpecMatch::unknown();
     }
 
-    match player.class.as_str() {
-        "Berserker" => {
-            // if has bloody rush
-            if player.skills.contains_key(&16140)
-                || player.skills.contains_key(&16145)
-                || player.skills.contains_key(&16146)
-                || player.skills.contains_key(&16147)
-            {
-                "Berserker Technique"
-            } else {
-                "Mayhem"
-            }
-        }
-        "Destroyer" => {
-            if player.skills.contains_key(&18090) {
-                "Gravity Training"
-            } else {
-                "Rage Hammer"
-            }
-        }
-        "Gunlancer" => {
-            if player.skills.contains_key(&17200) && player.skills.contains_key(&17210) {
-                "Lone Knight"
-            } else if player.skills.contains_key(&17140) && player.skills.contains_key(&17110) {
-                "Combat Readiness"
-            } else {
-                "Princess"
-            }
-        }
-        "Paladin" => {
-            // if has execution of judgement, judgement blade, or flash slash strength release tripod
-            if player.skills.contains_key(&36250)
-                || player.skills.contains_key(&36270)
-                || player
-                    .skills
-                    .get(&36090)
-                    .is_some_and(|s| s.tripod_index.is_some_and(|t| t.second == 3))
-            {
-                "Judgment"
-            } else if player.skills.contains_key(&36200)
-                || player.skills.contains_key(&36170)
-                || player.skills.contains_key(&36800)
-            {
-                // if has heavenly blessing, wrath of god, or holy aura
-                "Blessed Aura"
-            } else {
-                "Unknown"
-            }
-        }
-        "Slayer" => {
-            if player.skills.contains_key(&45004) {
-                "Punisher"
-            } else {
-                "Predator"
-            }
-        }
-        "Valkyrie" => {
-            if player.skills.contains_key(&48060)
-                || player.skills.contains_key(&48070)
-                || player.skills.contains_key(&48500)
-                || player.skills.contains_key(&48100)
-            {
-                // shining knight, final splendor, cataclysm, foresight slash
-                "Shining Knight"
-            } else if player.skills.contains_key(&48250)
-                || player.skills.contains_key(&48270)
-                || player.skills.contains_key(&48230)
-                || player.skills.contains_key(&48220)
-                || player.skills.contains_key(&48040)
-                || player.skills.contains_key(&48041)
-                || player.skills.contains_key(&48042)
-            {
-                // seraphic oath, seraphic leap,
-                // circle of truth, truth's decree
-                // release light
-                "Liberator"
-            } else {
-                "Unknown"
-            }
-        }
-        "Arcanist" => {
-            if player.skills.contains_key(&19282) {
-                "Order of the Emperor"
-            } else {
-                "Grace of the Empress"
-            }
-        }
-        "Summoner" => {
-            if player
-                .skills
-                .iter()
-                .any(|(_, skill)| skill.name.contains("Kelsion"))
-            {
-                "Communication Overflow"
-            } else {
-                "Master Summoner"
-            }
-        }
-        "Bard" => {
-            // if has tempest skill, or vivace, or heavenly tune with crit tripod
-            if (player.skills.contains_key(&21147)
-                || player.skills.contains_key(&21148)
-                || player.skills.contains_key(&21149))
-                || player.skills.contains_key(&21310)
-                || player
-                    .skills
-                    .get(&21160)
-                    .is_some_and(|s| s.tripod_index.is_some_and(|t| t.third == 2))
-            {
-                return "True Courage".to_string();
-            } else if player
-                .skills
-                .get(&21160)
-                .is_some_and(|s| s.tripod_index.is_some_and(|t| t.third == 1))
-            {
-                // if heavenly tune has atk pwr tripod
-                return "Desperate Salvation".to_string();
-            }
+    detect_spec(&assets.spec_rules, player, buffs, skip_min_check)
+}
 
-            "Unknown"
-        }
-        "Sorceress" => {
-            // if has arcane rupture
-            if player.skills.contains_key(&37100) || player.skills.contains_key(&37101) {
-                "Igniter"
-            } else {
-                "Reflux"
-            }
-        }
-        "Wardancer" => {
-            if player.skills.contains_key(&22340) {
-                "Esoteric Skill Enhancement"
-            } else {
-                "First Intention"
-            }
-        }
-        "Scrapper" => {
-            if player.skills.contains_key(&23230) {
-                "Ultimate Skill: Taijutsu"
-            } else {
-                "Shock Training"
-            }
-        }
-        "Soulfist" => {
-            if player.skills.contains_key(&24200) {
-                "Energy Overflow"
-            } else {
-                "Robust Spirit"
-            }
-        }
-        "Glaivier" => {
-            if player.skills.contains_key(&34590) {
-                "Pinnacle"
-            } else {
-                "Control"
-            }
-        }
-        "Striker" => {
-            if player.skills.contains_key(&39290) {
-                "Deathblow"
-            } else {
-                "Esoteric Flurry"
-            }
-        }
-        "Breaker" => {
-            if player.skills.contains_key(&47020) {
-                "Asura's Path"
-            } else {
-                "Brawl King Storm"
-            }
-        }
-        "Deathblade" => {
-            if player.skills.contains_key(&25038) {
-                "Surge"
-            } else {
-                "Remaining Energy"
-            }
-        }
-        "Shadowhunter" => {
-            if player.skills.contains_key(&27860) {
-                "Demonic Impulse"
-            } else {
-                "Perfect Suppression"
-            }
-        }
-        "Reaper" => {
-            let buff_names = get_buff_names(player, buffs);
-            if buff_names.iter().any(|s| s.contains("Lunar Voice")) {
-                "Lunar Voice"
-            } else {
-                "Hunger"
-            }
-        }
-        "Souleater" => {
-            if player.skills.contains_key(&46250) {
-                "Night's Edge"
-            } else {
-                "Full Moon Harvester"
-            }
-        }
-        "Sharpshooter" => {
-            let buff_names = get_buff_names(player, buffs);
-            if buff_names
-                .iter()
-                .any(|s| s.contains("Loyal Companion") || s.contains("Hawk Support"))
-            {
-                "Loyal Companion"
-            } else {
-                "Death Strike"
-            }
-        }
-        "Deadeye" => {
-            if player.skills.contains_key(&29300) {
-                "Enhanced Weapon"
-            } else {
-                "Pistoleer"
-            }
-        }
-        "Artillerist" => {
-            if player.skills.contains_key(&30260) {
-                "Barrage Enhancement"
-            } else {
-                "Firepower Enhancement"
-            }
-        }
-        "Machinist" => {
-            let buff_names = get_buff_names(player, buffs);
-            if buff_names
-                .iter()
-                .any(|s| s.contains("Combat Mode") || s.contains("Evolutionary Legacy"))
-            {
-                "Evolutionary Legacy"
-            } else {
-                "Arthetinean Skill"
-            }
-        }
-        "Gunslinger" => {
-            if player.skills.contains_key(&38110) {
-                "Peacemaker"
-            } else {
-                "Time to Hunt"
-            }
+/// Scores every spec of the player's class by the weight of its matching conditions.
+///
+/// The confidence is the share of the best score among all matching specs,
+/// or the class default confidence if nothing matched.
+pub fn detect_spec(
+    rules: &SpecRules,
+    player: &EncounterEntity,
+    buffs: &HashMap<u32, StatusEffect>,
+    skip_min_check: bool,
+) -> SpecMatch {
+    if !skip_min_check && player.skills.len() < rules.min_skills {
+        return SpecMatch::unknown();
+    }
+
+    let Some(class) = rules.classes.get(&player.class) else {
+        return SpecMatch::unknown();
+    };
+
+    let buff_names = get_buff_names(player, buffs);
+    let mut best: Option<(&str, f32)> = None;
+    let mut total = 0.0;
+
+    for rule in &class.specs {
+        let score: f32 = rule
+            .conditions
+            .iter()
+            .filter(|condition| matches_spec_condition(&condition.kind, player, &buff_names))
+            .map(|condition| condition.weight)
+            .sum();
+
+        if score <= 0.0 {
+            continue;
         }
-        "Artist" => {
-            // dps if has cattle drive or shattering strike or rising moon
-            // or sunsketch with crit tripod
-            if player.skills.contains_key(&31940)
-                || player.skills.contains_key(&31060)
-                || player.skills.contains_key(&31145)
-                || player
-                    .skills
-                    .get(&31400)
-                    .is_some_and(|s| s.tripod_index.is_some_and(|t| t.third == 2))
-            {
-                return "Recurrence".to_string();
-            } else if player
-                .skills
-                .get(&31400)
-                .is_some_and(|s| s.tripod_index.is_some_and(|t| t.third == 1))
-            {
-                // if sunsketch has atk pwr tripod
-                return "Full Bloom".to_string();
-            }
 
-            "Unknown"
+        total += score;
+
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((rule.spec.as_str(), score));
         }
-        "Aeromancer" => {
-            if player.skills.contains_key(&32250) && player.skills.contains_key(&32260) {
-                "Wind Fury"
-            } else {
-                "Drizzle"
-            }
+    }
+
+    match (best, &class.default) {
+        (Some((spec, score)), _) => SpecMatch {
+            spec: spec.to_string(),
+            confidence: score / total,
+        },
+        (None, Some(default)) => SpecMatch {
+            spec: default.clone(),
+            confidence: class.default_confidence,
+        },
+        (None, None) => SpecMatch::unknown(),
+    }
+}
+
+fn matches_spec_condition(
+    kind: &SpecConditionKind,
+    player: &EncounterEntity,
+    buff_names: &[String],
+) -> bool {
+    match kind {
+        SpecConditionKind::AnySkill { skills } => {
+            skills.iter().any(|id| player.skills.contains_key(id))
         }
-        "Wildsoul" => {
-            if player.skills.contains_key(&33400) || player.skills.contains_key(&33410) {
-                "Ferality"
-            } else {
-                "Phantom Beast Awakening"
-            }
+        SpecConditionKind::AllSkills { skills } => {
+            !skills.is_empty() && skills.iter().all(|id| player.skills.contains_key(id))
         }
-        _ => "Unknown",
+        SpecConditionKind::Tripod { skill, tier, index } => player
+            .skills
+            .get(skill)
+            .and_then(|skill| skill.tripod_index.as_ref())
+            .is_some_and(|tripods| {
+                let picked = match tier {
+                    1 => tripods.first,
+                    2 => tripods.second,
+                    3 => tripods.third,
+                    _ => return false,
+                };
+
+                picked == *index
+            }),
+        SpecConditionKind::SkillName { contains } => player
+            .skills
+            .values()
+            .any(|skill| skill.name.contains(contains.as_str())),
+        SpecConditionKind::Buff { names } => buff_names
+            .iter()
+            .any(|buff| names.iter().any(|name| buff.contains(name.as_str()))),
+        SpecConditionKind::ArkPassive { nodes } => player
+            .ark_passive_data
+            .as_ref()
+            .and_then(|data| data.enlightenment.as_ref())
+            .is_some_and(|tree| tree.iter().any(|node| nodes.contains(&node.id))),
+        SpecConditionKind::Engraving { names } => player
+            .engraving_data
+            .as_ref()
+            .is_some_and(|engravings| engravings.iter().any(|engraving| names.contains(engraving))),
     }
-    .to_string()
 }
 
 fn get_buff_names(player: &EncounterEntity, buffs: &HashMap<u32, StatusEffect>) -> Vec<String> {
@@ -1096,84 +936,23 @@ fn get_buff_names(player: &EncounterEntity, buffs: &HashMap<u32, StatusEffect>)
     names
 }
 
-pub fn get_spec_from_ark_passive(node: &ArkPassiveNode) -> String {
-    match node.id {
-        2160000 => "Berserker Technique",
-        2160010 => "Mayhem",
-        2170000 => "Lone Knight",
-        2170010 => "Combat Readiness",
-        2180000 => "Rage Hammer",
-        2180010 => "Gravity Training",
-        2360000 => "Judgment",
-        2360010 => "Blessed Aura",
-        2450000 => "Punisher",
-        2450010 => "Predator",
-        2480000 => "Shining Knight",
-        2480100 => "Liberator",
-        2230000 => "Ultimate Skill: Taijutsu",
-        2230100 => "Shock Training",
-        2220000 => "First Intention",
-        2220100 => "Esoteric Skill Enhancement",
-        2240000 => "Energy Overflow",
-        2240100 => "Robust Spirit",
-        2340000 => "Control",
-        2340100 => "Pinnacle",
-        2470000 => "Brawl King Storm",
-        2470100 => "Asura's Path",
-        2390000 => "Esoteric Flurry",
-        2390010 => "Deathblow",
-        2300000 => "Barrage Enhancement",
-        2300100 => "Firepower Enhancement",
-        2290000 => "Enhanced Weapon",
-        2290100 => "Pistoleer",
-        2280000 => "Death Strike",
-        2280100 => "Loyal Companion",
-        2350000 => "Evolutionary Legacy",
-        2350100 => "Arthetinean Skill",
-        2380000 => "Peacemaker",
-        2380100 => "Time to Hunt",
-        2370000 => "Igniter",
-        2370100 => "Reflux",
-        2190000 => "Grace of the Empress",
-        2190100 => "Order of the Emperor",
-        2200000 => "Communication Overflow",
-        2200100 => "Master Summoner",
-        2210000 => "Desperate Salvation",
-        2210100 => "True Courage",
-        2270000 => "Demonic Impulse",
-        2270600 => "Perfect Suppression",
-        2250000 => "Surge",
-        2250600 => "Remaining Energy",
-        2260000 => "Lunar Voice",
-        2260600 => "Hunger",
-        2460000 => "Full Moon Harvester",
-        2460600 => "Night's Edge",
-        2320000 => "Wind Fury",
-        2320600 => "Drizzle",
-        2310000 => "Full Bloom",
-        2310600 => "Recurrence",
-        2330000 => "Ferality",
-        2330100 => "Phantom Beast Awakening",
-        _ => "Unknown",
-    }
-    .to_string()
-}
+/// Looks up the gate of the current boss in the raid catalog.
+pub fn get_raid_gate(encounter: &Encounter, zone_id: Option<u32>, difficulty: &str) -> Option<RaidGate> {
+    let boss = encounter.entities.get(&encounter.current_boss_name)?;
 
-pub fn boss_to_raid_map(boss: &str, max_hp: i64) -> Option<String> {
-    match boss {
-        "Phantom Legion Commander Brelshaza" => {
-            if max_hp > 100_000_000_000 {
-                Some("Act 2: Brelshaza G2".to_string())
-            } else {
-                Some("Brelshaza G6".to_string())
-            }
-        }
-        _ => RAID_MAP.get(boss).cloned(),
-    }
+    let query = RaidQuery {
+        boss_name: &encounter.current_boss_name,
+        npc_id: boss.npc_id,
+        max_hp: boss.max_hp,
+        zone_id,
+        difficulty,
+    };
+
+    meter_data().raid_catalog.find(&query).cloned()
 }
 
 pub fn get_total_available_time(
-    skill_cooldown: &Vec<CastEvent>,
+    skill_cooldown: &[CastEvent],
     encounter_start: i64,
     encounter_end: i64,
 ) -> i64 {
@@ -1196,6 +975,139 @@ pub fn get_total_available_time(
     total_available_time
 }
 
+/// Compares the casts of a skill with the casts possible if it had been used whenever it came off cooldown.
+///
+/// The cooldown of each cast already includes reductions, the theoretical casts use their average.
+pub fn get_cooldown_efficiency(
+    skill_cooldown: &[CastEvent],
+    encounter_start: i64,
+    encounter_end: i64,
+) -> Option<CooldownEfficiency> {
+    if skill_cooldown.is_empty() || encounter_end <= encounter_start {
+        return None;
+    }
+
+    let casts = skill_cooldown.len() as i64;
+    let average_cooldown = skill_cooldown
+        .iter()
+        .map(|event| event.cooldown_duration_ms)
+        .sum::<i64>()
+        / casts;
+
+    if average_cooldown <= 0 {
+        return None;
+    }
+
+    let idle_time = get_total_available_time(skill_cooldown, encounter_start, encounter_end);
+    let max_casts = casts + idle_time / average_cooldown;
+
+    let delays: Vec<i64> = skill_cooldown
+        .windows(2)
+        .map(|pair| {
+            let cooldown_end = pair[0].timestamp + pair[0].cooldown_duration_ms;
+            (pair[1].timestamp - cooldown_end).max(0)
+        })
+        .collect();
+
+    let average_delay = match delays.is_empty() {
+        true => 0,
+        false => delays.iter().sum::<i64>() / delays.len() as i64,
+    };
+
+    Some(CooldownEfficiency {
+        casts,
+        max_casts,
+        efficiency: casts as f64 / max_casts as f64,
+        idle_time,
+        average_delay,
+        max_delay: delays.iter().copied().max().unwrap_or_default(),
+    })
+}
+
+/// Fills in the cooldown stats of the skills of the local player, the only one with cooldown events.
+pub fn apply_cooldown_efficiency(
+    entity: &mut EncounterEntity,
+    skill_cooldowns: &HashMap<u32, Vec<CastEvent>>,
+    fight_start: i64,
+    fight_end: i64,
+) {
+    for (skill_id, events) in skill_cooldowns.iter() {
+        if let Some(skill) = entity.skills.get_mut(skill_id) {
+            skill.time_available = Some(get_total_available_time(events, fight_start, fight_end));
+            skill.cooldown_efficiency = get_cooldown_efficiency(events, fight_start, fight_end);
+        }
+    }
+}
+
+/// Adds a hit from an npc skill to a damage taken breakdown.
+/// Only hits of enemies on players are broken down by skill.
+pub fn is_damage_taken_tracked(target: EntityType, source: EntityType, damage: i64) -> bool {
+    target == EntityType::Player
+        && damage > 0
+        && matches!(
+            source,
+            EntityType::Boss | EntityType::Guardian | EntityType::Monster | EntityType::Npc
+        )
+}
+
+pub fn add_damage_taken(
+    breakdown: &mut HashMap<u32, SkillDamageTaken>,
+    skill_id: u32,
+    skill: &Skill,
+    source: &str,
+    damage: i64,
+) {
+    let stats = breakdown
+        .entry(skill_id)
+        .or_insert_with(|| SkillDamageTaken {
+            name: skill.name.clone(),
+            icon: skill.icon.clone(),
+            source: source.to_string(),
+            ..Default::default()
+        });
+
+    stats.damage += damage;
+    stats.hits += 1;
+}
+
+/// Blames a player death on the skill of the last hit they took.
+pub fn add_killing_blow(
+    entities: &mut HashMap<String, EncounterEntity>,
+    name: &str,
+    last_hit: &DeathRecapHit,
+) {
+    if let Some(stats) = entities
+        .get_mut(name)
+        .and_then(|entity| entity.damage_stats.damage_taken_by_skill.get_mut(&last_hit.skill_id))
+    {
+        stats.deaths += 1;
+    }
+
+    if let Some(stats) = entities
+        .get_mut(&last_hit.source)
+        .and_then(|boss| boss.damage_stats.damage_dealt_by_skill.get_mut(&last_hit.skill_id))
+    {
+        stats.deaths += 1;
+    }
+}
+
+/// Snapshot of a status effect on a player at the moment they died.
+pub fn get_death_recap_status_effect(
+    status_effect: &StatusEffectDetails,
+    now: DateTime<Utc>,
+) -> DeathRecapStatusEffect {
+    DeathRecapStatusEffect {
+        id: status_effect.status_effect_id,
+        name: status_effect.name.clone(),
+        debuff: status_effect.category == StatusEffectCategory::Debuff,
+        stacks: status_effect.stack_count,
+        value: status_effect.value,
+        remaining: status_effect
+            .expire_at
+            .map(|expire_at| (expire_at - now).num_milliseconds().max(0)),
+    }
+}
+
 pub fn get_damage_without_hyper_or_special(e: &EncounterEntity) -> i64 {
     let hyper = e.damage_stats.hyper_awakening_damage;
     let special = e
@@ -1207,47 +1119,136 @@ pub fn get_damage_without_hyper_or_special(e: &EncounterEntity) -> i64 {
     e.damage_stats.damage_dealt - hyper - special
 }
 
-pub struct SupportBuffs {
-    pub brand: f64,
-    pub buff: f64,
-    pub identity: f64,
-    pub hyper: f64,
-}
+/// Summarizes the identity gauge log of a player into the json stored in `SkillStats::identity_stats`.
+///
+/// The gauge is kept in the class specific units sent by the game, as the capacity of each
+/// class gauge is not known here.
+pub fn get_identity_stats(
+    class_id: u32,
+    log: &IdentityLog,
+    fight_start: i64,
+    fight_end: i64,
+) -> Option<String> {
+    if log.is_empty() || fight_end <= fight_start {
+        return None;
+    }
 
-pub fn update_party(
-    party_tracker: &Rc<RefCell<PartyTracker>>,
-    entity_tracker: &EntityTracker,
-) -> Vec<Vec<String>> {
-    let mut party_info = HashMap::new();
-
-    for (entity_id, party_id) in &party_tracker.borrow().entity_id_to_party_id {
-        let members = party_info.entry(*party_id).or_insert_with(Vec::new);
-        if let Some(entity) = entity_tracker.entities.get(entity_id) {
-            if entity.character_id > 0
-                && entity.class_id > 0
-                && entity
-                    .name
-                    .chars()
-                    .next()
-                    .unwrap_or_default()
-                    .is_uppercase()
-            {
-                members.push(entity.name.clone());
+    let relative_time = |timestamp: i64| ((timestamp - fight_start).max(0) / 1000) as i32;
+
+    // each value holds until the next change
+    let mut weighted_total = 0.0;
+    for (idx, (timestamp, (gauge, _, _))) in log.iter().enumerate() {
+        let start = (*timestamp).max(fight_start);
+        let end = log
+            .get(idx + 1)
+            .map_or(fight_end, |(next, _)| *next)
+            .min(fight_end);
+
+        if end > start {
+            weighted_total += *gauge as f64 * (end - start) as f64;
+        }
+    }
+    let average = weighted_total / (fight_end - fight_start) as f64;
+
+    let stats = match class_id {
+        // arcanist, gauge 2 and 3 hold the cards in hand
+        202 => {
+            let mut card_draws: HashMap<u32, u32> = HashMap::new();
+            let mut hand: Vec<u32> = Vec::new();
+
+            for (_, (_, card1, card2)) in log.iter() {
+                let mut previous = hand.clone();
+
+                for card in [*card1, *card2].into_iter().filter(|card| *card != 0) {
+                    match previous.iter().position(|held| *held == card) {
+                        Some(idx) => {
+                            previous.swap_remove(idx);
+                        },
+                        None => *card_draws.entry(card).or_default() += 1,
+                    }
+                }
+
+                hand = vec![*card1, *card2];
             }
+
+            serde_json::to_string(&IdentityArcanist {
+                log: log
+                    .iter()
+                    .map(|(timestamp, (gauge, card1, card2))| {
+                        (relative_time(*timestamp), (*gauge, *card1, *card2))
+                    })
+                    .collect(),
+                average,
+                card_draws,
+            })
+        },
+        // bard and artist, gauge 2 holds the bubbles
+        204 | 602 => serde_json::to_string(&IdentityArtistBard {
+            log: log
+                .iter()
+                .map(|(timestamp, (gauge, bubbles, _))| {
+                    (relative_time(*timestamp), (*gauge, *bubbles))
+                })
+                .collect(),
+            average,
+        }),
+        _ => serde_json::to_string(&IdentityGeneric {
+            log: log
+                .iter()
+                .map(|(timestamp, (gauge, _, _))| (relative_time(*timestamp), *gauge))
+                .collect(),
+            average,
+        }),
+    };
+
+    stats.ok()
+}
+
+/// Builds the stance timeline and uptime of a player from the logged stance changes.
+///
+/// The stance held before the fight started counts from the start of the fight.
+pub fn get_stance_stats(
+    log: &[(i64, u8)],
+    fight_start: i64,
+    fight_end: i64,
+) -> Option<StanceStats> {
+    if fight_end <= fight_start {
+        return None;
+    }
+
+    let mut timeline: Vec<(i64, u8)> = Vec::new();
+    for (timestamp, stance) in log.iter().filter(|(timestamp, _)| *timestamp <= fight_end) {
+        let time = (timestamp - fight_start).max(0);
+
+        match timeline.last_mut() {
+            Some(last) if last.0 == time => last.1 = *stance,
+            _ => timeline.push((time, *stance)),
         }
     }
 
-    let mut sorted_parties = party_info.into_iter().collect::<Vec<(u32, Vec<String>)>>();
-    sorted_parties.sort_unstable_by_key(|&(party_id, _)| party_id);
-    sorted_parties
-        .into_iter()
-        .map(|(_, members)| members)
-        .collect()
+    if timeline.is_empty() {
+        return None;
+    }
+
+    let duration = fight_end - fight_start;
+    let mut uptime: HashMap<u8, f64> = HashMap::new();
+    for (idx, (time, stance)) in timeline.iter().enumerate() {
+        let end = timeline.get(idx + 1).map_or(duration, |(next, _)| *next);
+        *uptime.entry(*stance).or_default() += (end - time) as f64 / duration as f64;
+    }
+
+    Some(StanceStats { timeline, uptime })
+}
+
+pub struct SupportBuffs {
+    pub brand: f64,
+    pub buff: f64,
+    pub identity: f64,
+    pub hyper: f64,
 }
 
 pub fn on_shield_change(
-    entity_tracker: &mut EntityTracker,
-    id_tracker: &Rc<RefCell<IdTracker>>,
+    store: &mut StateStore,
     state: &mut EncounterState,
     status_effect: StatusEffectDetails,
     change: u64,
@@ -1255,16 +1256,8 @@ pub fn on_shield_change(
     if change == 0 {
         return;
     }
-    let source = entity_tracker.get_source_entity(status_effect.source_id);
-    let target_id = if status_effect.target_type == StatusEffectTargetType::Party {
-        id_tracker
-            .borrow()
-            .get_entity_id(status_effect.target_id)
-            .unwrap_or_default()
-    } else {
-        status_effect.target_id
-    };
-    let target = entity_tracker.get_source_entity(target_id);
+    let source = store.entities.get_source_entity(status_effect.source_id);
+    let target = store.status_effect_target(&status_effect);
     state.on_boss_shield(&target, status_effect.value);
     state.on_shield_used(&source, &target, status_effect.status_effect_id, change);
 }
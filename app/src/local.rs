@@ -18,6 +18,7 @@ pub struct LocalPlayer {
     pub count: i32,
 }
 
+#[derive(Clone)]
 pub struct LocalPlayerRepository(PathBuf);
 
 impl LocalPlayerRepository {
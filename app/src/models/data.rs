@@ -129,3 +129,201 @@ pub struct Esther {
     #[serde(alias = "npcs")]
     pub npc_ids: Vec<u32>,
 }
+
+/// Raid difficulties shipped in `meter-data/RaidDifficulty.json`.
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RaidDifficultyData {
+    /// Keyed by the raid id of `PKTRaidBegin`.
+    pub raids: HashMap<u32, RaidDifficulty>,
+    /// Keyed by the zone id of `PKTZoneMemberLoadStatusNotify`, takes precedence over the zone level.
+    pub zones: HashMap<u32, RaidDifficulty>,
+    /// Keyed by the zone level of `PKTZoneMemberLoadStatusNotify`.
+    pub zone_levels: HashMap<u32, RaidDifficulty>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, PartialEq)]
+pub struct RaidDifficulty {
+    pub id: u32,
+    pub name: String,
+}
+
+impl RaidDifficultyData {
+    pub fn by_raid(&self, raid_id: u32) -> Option<&RaidDifficulty> {
+        self.raids.get(&raid_id)
+    }
+
+    pub fn by_zone(&self, zone_id: u32, zone_level: u32) -> Option<&RaidDifficulty> {
+        self.zones.get(&zone_id).or_else(|| self.zone_levels.get(&zone_level))
+    }
+}
+
+/// Spec detection rules shipped in `meter-data/SpecRules.json`.
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SpecRules {
+    /// Players with fewer skills are not detected, unless the check is skipped.
+    pub min_skills: usize,
+    /// Keyed by class name.
+    pub classes: HashMap<String, ClassSpecRules>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ClassSpecRules {
+    /// Spec used when none of the rules match.
+    pub default: Option<String>,
+    pub default_confidence: f32,
+    /// In order of precedence when scores are tied.
+    pub specs: Vec<SpecRule>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecRule {
+    pub spec: String,
+    pub conditions: Vec<SpecCondition>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecCondition {
+    #[serde(flatten)]
+    pub kind: SpecConditionKind,
+    #[serde(default = "default_spec_weight")]
+    pub weight: f32,
+}
+
+fn default_spec_weight() -> f32 {
+    1.0
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum SpecConditionKind {
+    /// Any of the skills was used.
+    AnySkill { skills: Vec<u32> },
+    /// All of the skills were used.
+    AllSkills { skills: Vec<u32> },
+    /// Tripod `index` picked in `tier` (1-3) of the skill.
+    Tripod { skill: u32, tier: u8, index: u8 },
+    /// Any skill name contains the text.
+    SkillName { contains: String },
+    /// The player was buffed by a status effect whose source name contains any of the names.
+    Buff { names: Vec<String> },
+    /// Any of the nodes is in the enlightenment tree.
+    ArkPassive { nodes: Vec<u32> },
+    /// Any of the engravings is equipped.
+    Engraving { names: Vec<String> },
+}
+
+#[derive(Debug, Default, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpecMatch {
+    pub spec: String,
+    /// Between 0 and 1, 0 for an unknown spec.
+    pub confidence: f32,
+}
+
+impl SpecMatch {
+    pub fn unknown() -> Self {
+        Self {
+            spec: "Unknown".to_string(),
+            confidence: 0.0,
+        }
+    }
+}
+
+/// Raid gates shipped in `meter-data/RaidCatalog.json`.
+#[derive(Debug, Default, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RaidCatalog {
+    pub gates: Vec<RaidGate>,
+}
+
+#[derive(Debug, Default, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RaidGate {
+    pub raid: String,
+    /// e.g. `Valtan G2`
+    pub gate: String,
+    pub bosses: Vec<String>,
+    pub npc_ids: Vec<u32>,
+    /// Empty if the gate is not limited to specific zones, same for the fields below.
+    pub zone_ids: Vec<u32>,
+    pub difficulties: Vec<String>,
+    pub min_hp: Option<i64>,
+    pub max_hp: Option<i64>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct RaidQuery<'a> {
+    pub boss_name: &'a str,
+    pub npc_id: u32,
+    pub max_hp: i64,
+    pub zone_id: Option<u32>,
+    pub difficulty: &'a str,
+}
+
+impl RaidCatalog {
+    /// Gate of the boss, the one with the most matching constraints wins if the boss name is shared.
+    ///
+    /// A constraint which is set but does not match rules the gate out,
+    /// unknown zones and difficulties are not held against a gate.
+    pub fn find(&self, query: &RaidQuery) -> Option<&RaidGate> {
+        let mut best: Option<(&RaidGate, u32)> = None;
+
+        for gate in &self.gates {
+            let Some(score) = gate.score(query) else {
+                continue;
+            };
+
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((gate, score));
+            }
+        }
+
+        best.map(|(gate, _)| gate)
+    }
+}
+
+impl RaidGate {
+    fn score(&self, query: &RaidQuery) -> Option<u32> {
+        let mut score = 0;
+
+        if self.npc_ids.contains(&query.npc_id) {
+            score += 2;
+        } else if !self.bosses.iter().any(|boss| boss == query.boss_name) {
+            return None;
+        }
+
+        if self.min_hp.is_some() || self.max_hp.is_some() {
+            let above_min = self.min_hp.is_none_or(|min_hp| query.max_hp >= min_hp);
+            let below_max = self.max_hp.is_none_or(|max_hp| query.max_hp <= max_hp);
+
+            if !above_min || !below_max {
+                return None;
+            }
+
+            score += 1;
+        }
+
+        if let Some(zone_id) = query.zone_id.filter(|_| !self.zone_ids.is_empty()) {
+            if !self.zone_ids.contains(&zone_id) {
+                return None;
+            }
+
+            score += 1;
+        }
+
+        if !self.difficulties.is_empty() && !query.difficulty.is_empty() {
+            if !self.difficulties.iter().any(|difficulty| difficulty == query.difficulty) {
+                return None;
+            }
+
+            score += 1;
+        }
+
+        Some(score)
+    }
+}
@@ -25,6 +25,243 @@ pub struct Encounter {
     pub sync: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub region: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raid_summary: Option<RaidSummary>,
+    /// Previous gate of the same raid run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<i64>,
+    /// Following gates of the same raid run.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub child_ids: Vec<i64>,
+    /// Attempt number on the boss and difficulty, starting at 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempt: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wipe: Option<WipeInfo>,
+    /// Player deaths in order, saved in the `death_recaps` column.
+    /// Also the death timeline of a wipe.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub death_recaps: Vec<DeathRecap>,
+}
+
+/// How an uncleared attempt ended, saved in the `wipe` column.
+/// The deaths leading up to it are the encounter's `death_recaps`.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WipeInfo {
+    /// Remaining hp of the boss in the `0..=1` range.
+    pub boss_hp: Option<f32>,
+}
+
+/// What a player took in the last seconds before dying.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeathRecap {
+    /// ms since fight start
+    pub time: i64,
+    pub name: String,
+    pub class_id: u32,
+    /// Oldest first.
+    pub hits: Vec<DeathRecapHit>,
+    /// Status effects on the player when they died.
+    pub status_effects: Vec<DeathRecapStatusEffect>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeathRecapHit {
+    /// ms since fight start
+    pub time: i64,
+    pub source: String,
+    pub skill_id: u32,
+    pub skill_name: String,
+    /// Includes the part absorbed by shields.
+    pub damage: i64,
+    pub shield_absorbed: i64,
+    /// Hp left after the hit.
+    pub current_hp: i64,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DeathRecapStatusEffect {
+    pub id: u32,
+    pub name: String,
+    pub debuff: bool,
+    pub stacks: u8,
+    /// Remaining shield for shields.
+    pub value: u64,
+    /// ms until it would have expired, `None` for effects without a duration.
+    pub remaining: Option<i64>,
+}
+
+/// Party and raid aggregates, evaluated on the backend so the meter doesn't have to scan every entity.
+/// Shares and buff values are fractions in the `0..=1` range.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RaidSummary {
+    pub total_damage_dealt: i64,
+    pub dps: i64,
+    pub rdps_damage_given: i64,
+    pub parties: Vec<PartySummary>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PartySummary {
+    pub party_id: i32,
+    pub damage_dealt: i64,
+    pub dps: i64,
+    pub damage_share: f64,
+    pub rdps_damage_given: i64,
+    pub rdps_damage_received: i64,
+    pub members: Vec<PartyMemberSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub support: Option<PartySupportSummary>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PartyMemberSummary {
+    pub name: String,
+    pub class_id: u32,
+    pub damage_dealt: i64,
+    pub dps: i64,
+    /// Share of the party damage.
+    pub party_damage_share: f64,
+    /// Share of the raid damage.
+    pub raid_damage_share: f64,
+    pub rdps_damage_given: i64,
+    pub rdps_damage_received: i64,
+}
+
+/// Damage weighted uptime of the party support's buffs, excluding hyper awakening and special skills.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PartySupportSummary {
+    pub name: String,
+    pub brand: f64,
+    pub buff: f64,
+    pub identity: f64,
+    pub hyper: f64,
+}
+
+/// Trimmed encounter for windows which only show the top-line dps, e.g. the mini meter.
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EncounterSummary {
+    pub fight_start: i64,
+    pub last_combat_packet: i64,
+    pub local_player: String,
+    pub current_boss_name: String,
+    pub total_damage_dealt: i64,
+    pub dps: i64,
+    pub current_boss: Option<BossSummary>,
+    pub players: Vec<PlayerSummary>,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BossSummary {
+    pub name: String,
+    pub current_hp: i64,
+    pub max_hp: i64,
+    pub current_shield: u64,
+    pub is_dead: bool,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayerSummary {
+    pub name: String,
+    pub class_id: u32,
+    pub class: String,
+    pub gear_score: f32,
+    pub is_dead: bool,
+    pub damage_dealt: i64,
+    pub dps: i64,
+    pub damage_share: f64,
+    /// Shares of the buffable damage, i.e. without special skills and hyper awakening.
+    pub support_buff: f64,
+    pub support_brand: f64,
+    pub support_identity: f64,
+}
+
+impl EncounterSummary {
+    pub fn new(encounter: &Encounter) -> Self {
+        let duration_seconds = ((encounter.last_combat_packet - encounter.fight_start) / 1000).max(1);
+
+        let mut players: Vec<PlayerSummary> = encounter
+            .entities
+            .values()
+            .filter(|entity| {
+                ((entity.entity_type == EntityType::Player && entity.class_id > 0)
+                    || entity.entity_type == EntityType::Esther)
+                    && entity.damage_stats.damage_dealt > 0
+            })
+            .map(|entity| {
+                let stats = &entity.damage_stats;
+                let special_damage: i64 = entity
+                    .skills
+                    .values()
+                    .filter(|skill| skill.special.unwrap_or_default())
+                    .map(|skill| skill.total_damage)
+                    .sum();
+                let buffable_damage = stats.damage_dealt - special_damage - stats.hyper_awakening_damage;
+                let share = |damage: i64| {
+                    if buffable_damage > 0 {
+                        damage as f64 / buffable_damage as f64
+                    } else {
+                        0.0
+                    }
+                };
+
+                PlayerSummary {
+                    name: entity.name.clone(),
+                    class_id: entity.class_id,
+                    class: entity.class.clone(),
+                    gear_score: entity.gear_score,
+                    is_dead: entity.is_dead,
+                    damage_dealt: stats.damage_dealt,
+                    dps: stats.damage_dealt / duration_seconds,
+                    damage_share: 0.0,
+                    support_buff: share(stats.buffed_by_support),
+                    support_brand: share(stats.debuffed_by_support),
+                    support_identity: share(stats.buffed_by_identity),
+                }
+            })
+            .collect();
+
+        players.sort_by(|a, b| b.damage_dealt.cmp(&a.damage_dealt));
+
+        let total_damage_dealt: i64 = players.iter().map(|player| player.damage_dealt).sum();
+
+        if total_damage_dealt > 0 {
+            for player in players.iter_mut() {
+                player.damage_share = player.damage_dealt as f64 / total_damage_dealt as f64;
+            }
+        }
+
+        Self {
+            fight_start: encounter.fight_start,
+            last_combat_packet: encounter.last_combat_packet,
+            local_player: encounter.local_player.clone(),
+            current_boss_name: encounter.current_boss_name.clone(),
+            total_damage_dealt,
+            dps: total_damage_dealt / duration_seconds,
+            current_boss: encounter
+                .entities
+                .get(&encounter.current_boss_name)
+                .map(|boss| BossSummary {
+                    name: boss.name.clone(),
+                    current_hp: boss.current_hp,
+                    max_hp: boss.max_hp,
+                    current_shield: boss.current_shield,
+                    is_dead: boss.is_dead,
+                }),
+            players,
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Clone, Default)]
@@ -45,6 +282,66 @@ pub struct EncounterDamageStats {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub misc: Option<EncounterMisc>,
     pub boss_hp_log: HashMap<String, Vec<BossHpLog>>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub stagger_stats: HashMap<String, BossStagger>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub phases: Vec<EncounterPhase>,
+}
+
+/// Part of the fight between two phase boundaries, saved in the `phases` column.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EncounterPhase {
+    pub boss_name: String,
+    pub reason: PhaseReason,
+    /// ms since fight start
+    pub start: i64,
+    pub end: i64,
+    pub total_damage_dealt: i64,
+    /// Damage dealt by each player during the phase.
+    pub damage_dealt: HashMap<String, i64>,
+}
+
+/// What started a phase.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PhaseReason {
+    #[default]
+    Start,
+    BattleStatus,
+    HpThreshold,
+    BossSwap,
+}
+
+/// Stagger of a boss, saved in the `stagger_log` column keyed by boss name.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BossStagger {
+    pub max_stagger: i32,
+    /// Seconds from the first stagger damage until the bar was full.
+    pub average_time_to_stagger: f64,
+    pub log: Vec<StaggerLog>,
+    pub intervals: Vec<StaggerInterval>,
+    /// Stagger points credited to each player.
+    pub contributions: HashMap<String, i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StaggerLog {
+    pub time: i32,
+    /// Filled percentage of the stagger bar.
+    pub p: f32,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StaggerInterval {
+    /// Seconds since fight start when the boss got staggered.
+    pub time: i32,
+    /// Seconds it took to fill the bar.
+    pub duration: i32,
+    pub max: i32,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -74,6 +371,8 @@ pub struct EncounterEntity {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub spec: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub spec_confidence: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub loadout_hash: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub combat_power: Option<f32>,
@@ -130,6 +429,16 @@ pub struct DamageStats {
     pub rdps_damage_given: i64,
     #[serde(default)]
     pub incapacitations: Vec<IncapacitatedEvent>,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub damage_by_stance: HashMap<u8, i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stance_stats: Option<StanceStats>,
+    /// Damage a player took from each npc skill.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub damage_taken_by_skill: HashMap<u32, SkillDamageTaken>,
+    /// Damage a boss dealt to players with each of its skills.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub damage_dealt_by_skill: HashMap<u32, SkillDamageTaken>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -182,13 +491,21 @@ pub enum IncapacitationEventType {
     CROWD_CONTROL,
 }
 
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Stagger {
+    pub boss_name: String,
+    pub current: i32,
+    pub max: i32,
+}
+
 pub type IdentityLog = Vec<(i64, (u32, u32, u32))>;
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IdentityArcanist {
-    // timestamp, (percentage, card, card)
-    pub log: Vec<(i32, (f32, u32, u32))>,
+    // timestamp, (gauge, card, card)
+    pub log: Vec<(i32, (u32, u32, u32))>,
     pub average: f64,
     pub card_draws: HashMap<u32, u32>,
 }
@@ -196,19 +513,42 @@ pub struct IdentityArcanist {
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IdentityArtistBard {
-    // timestamp, (percentage, bubble)
-    pub log: Vec<(i32, (f32, u32))>,
+    // timestamp, (gauge, bubble)
+    pub log: Vec<(i32, (u32, u32))>,
     pub average: f64,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IdentityGeneric {
-    // timestamp, percentage
-    pub log: Vec<(i32, f32)>,
+    // timestamp, gauge
+    pub log: Vec<(i32, u32)>,
     pub average: f64,
 }
 
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StanceStats {
+    /// (ms since fight start, stance)
+    pub timeline: Vec<(i64, u8)>,
+    /// Share of the fight spent in each stance.
+    pub uptime: HashMap<u8, f64>,
+}
+
+/// Damage players took from one boss pattern.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SkillDamageTaken {
+    pub name: String,
+    pub icon: String,
+    /// Name of the npc using the skill.
+    pub source: String,
+    pub damage: i64,
+    pub hits: i64,
+    /// Deaths where this skill was the last hit.
+    pub deaths: i64,
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase", default)]
 #[serde_as]
@@ -231,6 +571,8 @@ pub struct EncounterMisc {
     pub ntp_fight_start: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub manual_save: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raid_summary: Option<RaidSummary>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
@@ -19,6 +19,8 @@ pub struct EncounterPreview {
     pub support_brand: Option<f32>,
     pub support_identity: Option<f32>,
     pub support_hyper: Option<f32>,
+    /// Raid gate from the raid catalog, e.g. `Brelshaza G6`.
+    pub gate: Option<String>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
@@ -28,6 +30,72 @@ pub struct EncountersOverview {
     pub total_encounters: i32,
 }
 
+/// Consecutive gates of a raid cleared by the same party.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RaidRun {
+    pub id: i64,
+    pub raid: String,
+    pub difficulty: Option<String>,
+    pub started_on: i64,
+    pub ended_on: i64,
+    /// Sum of the cleared attempts in ms.
+    pub clear_time: i64,
+    pub gates: Vec<RaidRunGate>,
+    pub players: Vec<RaidRunPlayer>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RaidRunGate {
+    pub gate: String,
+    pub boss_name: String,
+    pub attempts: i32,
+    pub wipes: i32,
+    pub cleared: bool,
+    pub clear_time: i64,
+    pub encounter_ids: Vec<i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RaidRunPlayer {
+    pub name: String,
+    pub class_id: u32,
+    pub damage_dealt: i64,
+    pub dps: i64,
+}
+
+/// Every attempt on a boss and difficulty, oldest first.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AttemptHistory {
+    pub boss_name: String,
+    pub difficulty: String,
+    pub attempts: Vec<EncounterAttempt>,
+    /// Lowest boss hp reached in the `0..=1` range, `0` once cleared.
+    pub best_boss_hp: Option<f32>,
+    pub first_clear: Option<i64>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EncounterAttempt {
+    pub id: i64,
+    pub attempt: Option<i32>,
+    pub fight_start: i64,
+    pub duration: i64,
+    pub cleared: bool,
+    pub boss_hp: Option<f32>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RaidRunsOverview {
+    pub runs: Vec<RaidRun>,
+    pub total_runs: i32,
+}
+
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase", default)]
 pub struct SearchFilter {
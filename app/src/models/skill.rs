@@ -43,6 +43,8 @@ pub struct Skill {
     pub rdps_damage_received: i64,
     pub rdps_damage_received_support: i64,
     pub rdps_damage_given: i64,
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub damage_by_stance: HashMap<u8, i64>,
     pub skill_cast_log: Vec<SkillCast>,
 
     #[serde(default)]
@@ -57,6 +59,27 @@ pub struct Skill {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub time_available: Option<i64>, // total time skill was available to cast
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cooldown_efficiency: Option<CooldownEfficiency>,
+}
+
+/// How closely a skill was used on cooldown, only tracked for the local player.
+///
+/// Times are in milliseconds.
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CooldownEfficiency {
+    pub casts: i64,
+    /// Casts if the skill had been used every time it came off cooldown.
+    pub max_casts: i64,
+    /// `casts / max_casts`
+    pub efficiency: f64,
+    /// Time the skill was off cooldown without being used.
+    pub idle_time: i64,
+    /// Time between coming off cooldown and the next cast.
+    pub average_delay: i64,
+    pub max_delay: i64,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
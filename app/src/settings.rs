@@ -26,7 +26,10 @@ impl Default for Settings {
 #[serde(rename_all = "camelCase")]
 pub struct EnvironmentSettings {
     pub hearbeat_api_url: String,
-    pub stats_api_url: String
+    pub stats_api_url: String,
+    /// Base url of the meter-data packages, updates are disabled if empty.
+    #[serde(default)]
+    pub meter_data_url: String,
 }
 
 #[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -45,6 +48,17 @@ pub struct GeneralSettings {
     pub hide_meter_on_start: bool,
     pub hide_logs_on_start: bool,
     pub mini: bool,
+    pub record_packets: bool,
+    /// How often the meter windows are updated, in milliseconds.
+    #[serde(default = "default_update_interval")]
+    pub update_interval: u64,
+    #[serde(default = "default_low_performance_update_interval")]
+    pub low_performance_update_interval: u64,
+    #[serde(default = "default_party_update_interval")]
+    pub party_update_interval: u64,
+    /// How often windows subscribed to deltas get a full encounter, in milliseconds.
+    #[serde(default = "default_snapshot_interval")]
+    pub snapshot_interval: u64,
     #[serde(flatten)]
     pub extra: Map<String, Value>,
 }
@@ -53,6 +67,22 @@ fn default_true() -> bool {
     true
 }
 
+pub fn default_update_interval() -> u64 {
+    200
+}
+
+pub fn default_low_performance_update_interval() -> u64 {
+    1500
+}
+
+pub fn default_party_update_interval() -> u64 {
+    2000
+}
+
+pub fn default_snapshot_interval() -> u64 {
+    5000
+}
+
 pub struct SettingsManager(PathBuf);
 
 impl SettingsManager {
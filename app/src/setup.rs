@@ -3,7 +3,7 @@ use std::error::Error;
 use log::*;
 use tauri::{App, AppHandle, Manager};
 
-use crate::{background::{BackgroundWorker, BackgroundWorkerArgs}, constants::DEFAULT_PORT, context::AppContext, settings::*, shell::ShellManager, ui::{setup_tray, AppHandleExtensions, WindowExtensions}, updater::setup_updater};
+use crate::{background::{BackgroundWorker, BackgroundWorkerArgs}, constants::DEFAULT_PORT, context::AppContext, data_updater::setup_data_updater, settings::*, shell::ShellManager, ui::{setup_tray, AppHandleExtensions, WindowExtensions}, updater::setup_updater};
 
 pub fn setup(app: &mut App) -> Result<(), Box<dyn Error>> {
 
@@ -19,6 +19,7 @@ pub fn setup(app: &mut App) -> Result<(), Box<dyn Error>> {
     setup_updater(app_handle);
 
     let settings = settings_manager.read().expect("Could not read settings");
+    setup_data_updater(app_handle, &context, &settings);
 
     let port = initialize_windows_and_settings(
         &app_handle,
@@ -35,6 +36,7 @@ pub fn setup(app: &mut App) -> Result<(), Box<dyn Error>> {
         port,
         settings,
         region_file_path: context.region_file_path.clone(),
+        recordings_path: context.recordings_path.clone(),
         version: context.version.clone()
     };
 
@@ -0,0 +1,151 @@
+use std::{fs::OpenOptions, io::Write, path::PathBuf};
+
+use sayafushi_lib::abstractions::capture::*;
+
+fn capture_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("sayafushi_capture_{}.{}", name, CAPTURE_EXTENSION));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn write_capture(path: &PathBuf, frames: &[(u16, Vec<u8>)]) {
+    let mut writer = CaptureWriter::create(path.clone()).unwrap();
+
+    for (opcode, data) in frames {
+        writer.write(*opcode, data).unwrap();
+    }
+
+    assert_eq!(writer.frames(), frames.len() as u64);
+}
+
+fn read_capture(path: &PathBuf) -> Vec<(u16, Vec<u8>)> {
+    let mut reader = CaptureReader::open(path).unwrap();
+    let mut frames = Vec::new();
+
+    while let Some(frame) = reader.next_frame().unwrap() {
+        frames.push((frame.opcode, frame.data));
+    }
+
+    frames
+}
+
+#[test]
+fn should_read_back_written_frames() {
+    let path = capture_path("round_trip");
+    let frames = vec![(1, vec![1, 2, 3]), (2, Vec::new()), (65_535, vec![7; 4096])];
+
+    write_capture(&path, &frames);
+
+    assert_eq!(read_capture(&path), frames);
+}
+
+#[test]
+fn should_stop_at_truncated_trailing_frame() {
+    let path = capture_path("truncated");
+    let frames = vec![(1, vec![1, 2, 3]), (2, vec![4, 5])];
+
+    write_capture(&path, &frames);
+
+    // the app went down halfway through the next frame
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&0u64.to_le_bytes()).unwrap();
+    file.write_all(&3u16.to_le_bytes()).unwrap();
+    file.write_all(&100u32.to_le_bytes()).unwrap();
+    file.write_all(&[9; 10]).unwrap();
+    drop(file);
+
+    assert_eq!(read_capture(&path), frames);
+
+    // and halfway through a frame header
+    let length = std::fs::metadata(&path).unwrap().len();
+    let file = OpenOptions::new().write(true).open(&path).unwrap();
+    file.set_len(length - 10 - CAPTURE_FRAME_HEADER_SIZE as u64 + 5).unwrap();
+    drop(file);
+
+    assert_eq!(read_capture(&path), frames);
+}
+
+#[test]
+fn should_reject_files_which_are_not_captures() {
+    let path = capture_path("invalid");
+    std::fs::write(&path, b"not a capture").unwrap();
+
+    assert!(CaptureReader::open(&path).is_err());
+}
+
+#[cfg(feature = "meter-core-fake")]
+#[test]
+fn should_replay_captured_packets() {
+    use sayafushi_lib::abstractions::{PacketReceiver, PacketSource, Pkt, ReplayPacketSource, ReplaySpeed};
+
+    let path = capture_path("replay");
+    let frames = vec![
+        (Pkt::RaidBossKillNotify as u16, vec![1, 2, 3]),
+        (Pkt::TriggerStartNotify as u16, vec![4, 5]),
+        (Pkt::RaidResult as u16, Vec::new()),
+    ];
+
+    write_capture(&path, &frames);
+
+    // a truncated trailing frame ends the replay without an error
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(&[0; CAPTURE_FRAME_HEADER_SIZE - 1]).unwrap();
+    drop(file);
+
+    let source = ReplayPacketSource::new(path, ReplaySpeed::Unbounded);
+    let mut receiver = source.start(0).unwrap();
+    let mut replayed = Vec::new();
+
+    while let Ok((op, data)) = receiver.recv() {
+        replayed.push((op as u16, data));
+    }
+
+    assert_eq!(replayed, frames);
+}
+
+#[cfg(feature = "meter-core-fake")]
+#[test]
+fn should_not_leave_empty_captures_behind() {
+    use std::time::Duration;
+    use sayafushi_lib::abstractions::{FakePacketSource, PacketReceiver, PacketSource, Pkt, RecordingPacketSource, ScriptedPacket};
+
+    let directory = std::env::temp_dir().join("sayafushi_capture_recorder");
+    let _ = std::fs::remove_dir_all(&directory);
+
+    let packet = |op| ScriptedPacket { delay: Duration::ZERO, op, data: vec![1, 2] };
+    let inner = FakePacketSource::from_script(vec![
+        packet(Pkt::InitEnv),
+        packet(Pkt::RaidBossKillNotify),
+        packet(Pkt::InitEnv),
+        packet(Pkt::InitEnv),
+        packet(Pkt::RaidResult),
+    ]);
+
+    let source = RecordingPacketSource::new(inner, Some(directory.clone()));
+    let mut receiver = source.start(0).unwrap();
+
+    while receiver.recv().is_ok() {}
+    drop(receiver);
+
+    let captures = list_captures(&directory).unwrap();
+    let frames: Vec<usize> = captures.iter().map(|capture| read_capture(capture).len()).collect();
+
+    assert_eq!(frames, vec![2, 1, 2]);
+}
+
+#[cfg(feature = "meter-core-fake")]
+#[test]
+fn should_reject_invalid_replays() {
+    use sayafushi_lib::abstractions::{PacketSource, ReplayPacketSource, ReplaySpeed};
+
+    let directory = std::env::temp_dir().join("sayafushi_capture_empty");
+    let _ = std::fs::remove_dir_all(&directory);
+    std::fs::create_dir_all(&directory).unwrap();
+
+    assert!(ReplayPacketSource::new(directory, ReplaySpeed::Unbounded).start(0).is_err());
+
+    let path = capture_path("zero_speed");
+    write_capture(&path, &[(1, vec![1])]);
+
+    assert!(ReplayPacketSource::new(path, ReplaySpeed::Accelerated(0.0)).start(0).is_err());
+}
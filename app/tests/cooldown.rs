@@ -0,0 +1,59 @@
+#![cfg(feature = "meter-core-fake")]
+
+use hashbrown::HashMap;
+use sayafushi_lib::{live::{skill_tracker::CastEvent, utils::*}, models::*};
+
+fn cast(timestamp: i64, cooldown_duration_ms: i64) -> CastEvent {
+    CastEvent {
+        timestamp,
+        cooldown_duration_ms,
+    }
+}
+
+#[test]
+fn should_count_missed_casts_and_delays() {
+    let events = vec![cast(0, 10_000), cast(12_000, 10_000), cast(30_000, 10_000)];
+
+    let efficiency = get_cooldown_efficiency(&events, 0, 60_000).unwrap();
+
+    // idle for 2s, 8s and the last 20s
+    assert_eq!(efficiency.idle_time, 30_000);
+    assert_eq!(efficiency.casts, 3);
+    assert_eq!(efficiency.max_casts, 6);
+    assert_eq!(efficiency.efficiency, 0.5);
+    assert_eq!(efficiency.average_delay, 5_000);
+    assert_eq!(efficiency.max_delay, 8_000);
+}
+
+#[test]
+fn should_report_full_efficiency_when_used_on_cooldown() {
+    let events = vec![cast(1_000, 5_000), cast(6_000, 5_000)];
+
+    let efficiency = get_cooldown_efficiency(&events, 1_000, 11_000).unwrap();
+
+    assert_eq!(efficiency.idle_time, 0);
+    assert_eq!(efficiency.max_casts, 2);
+    assert_eq!(efficiency.efficiency, 1.0);
+    assert_eq!(efficiency.max_delay, 0);
+
+    assert!(get_cooldown_efficiency(&[], 0, 10_000).is_none());
+    assert!(get_cooldown_efficiency(&[cast(0, 0)], 0, 10_000).is_none());
+}
+
+#[test]
+fn should_apply_efficiency_to_known_skills() {
+    let mut entity = EncounterEntity::default();
+    entity.skills.insert(1, Skill { id: 1, ..Default::default() });
+
+    let cooldowns = HashMap::from([
+        (1, vec![cast(0, 10_000)]),
+        (2, vec![cast(0, 10_000)]),
+    ]);
+
+    apply_cooldown_efficiency(&mut entity, &cooldowns, 0, 20_000);
+
+    let skill = &entity.skills[&1];
+    assert_eq!(skill.time_available, Some(10_000));
+    assert_eq!(skill.cooldown_efficiency.as_ref().map(|e| e.max_casts), Some(2));
+    assert!(!entity.skills.contains_key(&2));
+}
@@ -0,0 +1,61 @@
+use hashbrown::HashMap;
+use sayafushi_lib::{live::utils::*, models::*};
+
+fn entity(name: &str, entity_type: EntityType) -> EncounterEntity {
+    EncounterEntity {
+        name: name.to_string(),
+        entity_type,
+        ..Default::default()
+    }
+}
+
+fn hit(source: &str, skill_id: u32, damage: i64) -> DeathRecapHit {
+    DeathRecapHit {
+        source: source.to_string(),
+        skill_id,
+        damage,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn should_only_track_enemy_hits_on_players() {
+    assert!(is_damage_taken_tracked(EntityType::Player, EntityType::Boss, 100));
+    assert!(is_damage_taken_tracked(EntityType::Player, EntityType::Monster, 100));
+    assert!(!is_damage_taken_tracked(EntityType::Player, EntityType::Boss, 0));
+    assert!(!is_damage_taken_tracked(EntityType::Player, EntityType::Player, 100));
+    assert!(!is_damage_taken_tracked(EntityType::Boss, EntityType::Player, 100));
+}
+
+#[test]
+fn should_break_down_damage_taken_by_skill() {
+    let slam = Skill { name: "Slam".to_string(), ..Default::default() };
+    let wave = Skill { name: "Wave".to_string(), ..Default::default() };
+    let mut player = entity("Berserker", EntityType::Player);
+    let mut boss = entity("Thaemine", EntityType::Boss);
+
+    for (skill_id, skill, damage) in [(1, &slam, 100_000), (2, &wave, 50_000), (1, &slam, 350_000)] {
+        add_damage_taken(&mut player.damage_stats.damage_taken_by_skill, skill_id, skill, &boss.name, damage);
+        add_damage_taken(&mut boss.damage_stats.damage_dealt_by_skill, skill_id, skill, &boss.name, damage);
+    }
+
+    let mut entities = HashMap::from([
+        (player.name.clone(), player),
+        (boss.name.clone(), boss),
+    ]);
+
+    add_killing_blow(&mut entities, "Berserker", &hit("Thaemine", 1, 350_000));
+
+    let taken = &entities["Berserker"].damage_stats.damage_taken_by_skill;
+    assert_eq!(taken.len(), 2);
+    assert_eq!(taken[&1].name, "Slam");
+    assert_eq!(taken[&1].source, "Thaemine");
+    assert_eq!(taken[&1].damage, 450_000);
+    assert_eq!(taken[&1].hits, 2);
+    assert_eq!(taken[&1].deaths, 1);
+    assert_eq!(taken[&2].deaths, 0);
+
+    let dealt = &entities["Thaemine"].damage_stats.damage_dealt_by_skill;
+    assert_eq!(dealt[&1].deaths, 1);
+    assert_eq!(dealt[&2].hits, 1);
+}
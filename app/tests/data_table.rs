@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+
+use sayafushi_lib::{data::table::LazyTable, models::*};
+
+const NPC: &str = r#"{
+    "485000": { "id": 485000, "name": "Thaemine", "grade": "raid", "type": "boss" },
+    "-1": { "id": -1, "name": "Dummy", "grade": "normal", "type": "monster" },
+    "620": { "id": 620, "name": "Kamen", "grade": "commander", "type": "boss" }
+}"#;
+
+fn meter_data_dir(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("sayafushi_data_table_{}", name));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+#[test]
+fn should_build_table_and_look_up_lazily() {
+    let path = meter_data_dir("build");
+    std::fs::write(path.join("Npc.json"), NPC).unwrap();
+
+    let table: LazyTable<u32, Npc> = LazyTable::load(&path, "Npc.json").unwrap();
+
+    assert!(path.join("Npc.bin").exists());
+    assert_eq!(table.len(), 3);
+    assert_eq!(table.get(&485000).and_then(|npc| npc.name.as_deref()), Some("Thaemine"));
+    assert_eq!(table.get(&620).map(|npc| npc.grade.as_str()), Some("commander"));
+    assert!(table.get(&1).is_none());
+
+    let table: LazyTable<i32, Npc> = LazyTable::load(&path, "Npc.json").unwrap();
+    assert_eq!(table.get(&-1).map(|npc| npc.npc_type.as_str()), Some("monster"));
+}
+
+#[test]
+fn should_rebuild_table_when_json_changes() {
+    let path = meter_data_dir("rebuild");
+    std::fs::write(path.join("Npc.json"), NPC).unwrap();
+
+    let table: LazyTable<u32, Npc> = LazyTable::load(&path, "Npc.json").unwrap();
+    assert!(table.get(&1).is_none());
+
+    std::fs::write(path.join("Npc.json"), r#"{ "1": { "id": 1, "name": "Kamen", "grade": "raid", "type": "boss" } }"#).unwrap();
+
+    let table: LazyTable<u32, Npc> = LazyTable::load(&path, "Npc.json").unwrap();
+    assert_eq!(table.len(), 1);
+    assert_eq!(table.get(&1).and_then(|npc| npc.name.as_deref()), Some("Kamen"));
+}
+
+#[test]
+fn should_open_shipped_table_without_json() {
+    let path = meter_data_dir("shipped");
+    std::fs::write(path.join("Npc.json"), NPC).unwrap();
+    LazyTable::<u32, Npc>::load(&path, "Npc.json").unwrap();
+    std::fs::remove_file(path.join("Npc.json")).unwrap();
+
+    let table: LazyTable<u32, Npc> = LazyTable::load(&path, "Npc.json").unwrap();
+    assert_eq!(table.len(), 3);
+    assert_eq!(table.get(&620).and_then(|npc| npc.name.as_deref()), Some("Kamen"));
+    drop(table);
+
+    std::fs::write(path.join("Npc.bin"), b"SFMD").unwrap();
+    let err = LazyTable::<u32, Npc>::load(&path, "Npc.json").err().unwrap();
+    assert!(format!("{:#}", err).starts_with("malformed"));
+}
+
+#[test]
+fn should_keep_table_in_memory_when_binary_can_not_be_written() {
+    let path = meter_data_dir("unwritable");
+    std::fs::write(path.join("Npc.json"), NPC).unwrap();
+    // the temporary file can not be created over a folder
+    std::fs::create_dir(path.join("Npc.bin.tmp")).unwrap();
+
+    let table: LazyTable<u32, Npc> = LazyTable::load(&path, "Npc.json").unwrap();
+
+    assert!(!path.join("Npc.bin").exists());
+    assert_eq!(table.len(), 3);
+    assert_eq!(table.get(&620).and_then(|npc| npc.name.as_deref()), Some("Kamen"));
+    assert!(table.get(&1).is_none());
+}
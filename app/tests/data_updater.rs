@@ -0,0 +1,221 @@
+use std::{io::{BufRead, BufReader, Write}, net::TcpListener, path::PathBuf, thread};
+
+use flate2::{write::GzEncoder, Compression};
+use hashbrown::HashMap;
+use sha2::{Digest, Sha256};
+use sayafushi_lib::data_updater::*;
+
+const STAT_TYPES: &str = r#"{
+    "def": 1, "magical_inc_rate": 2, "move_speed": 3, "vehicle_move_speed_rate": 4,
+    "attack_power_sub_rate_1": 5, "skill_damage_sub_rate_2": 6, "fire_dam_rate": 7, "elements_dam_rate": 8
+}"#;
+
+const NPC: &str = r#"{ "1": { "id": 1, "name": "Thaemine", "grade": "boss", "type": "boss" } }"#;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("sayafushi_data_updater_{}", name));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+fn create_bundled(path: &PathBuf) {
+    let files = [
+        ("version.json", r#"{ "version": "1" }"#),
+        ("CombatEffect.json", "{}"),
+        ("Ability.json", "{}"),
+        ("SkillBuff.json", r#"{ "1": { "id": 1, "duration": 0, "category": "buff", "type": "stat", "target": "self", "uniqueGroup": 0, "overlap": 0, "perLevelData": {} } }"#),
+        ("Skill.json", r#"{ "1": { "id": 1, "cooldown": 0, "classId": 0, "grade": "normal" } }"#),
+        ("SkillEffect.json", "{}"),
+        ("StatType.json", STAT_TYPES),
+        ("Esther.json", "[]"),
+        ("Npc.json", r#"{ "1": { "id": 1, "name": "Kamen", "grade": "boss", "type": "boss" } }"#),
+        ("GemSkillGroup.json", "{}"),
+        ("encounters.json", "{}"),
+        ("RaidDifficulty.json", "{}"),
+        ("SpecRules.json", "{}"),
+        ("RaidCatalog.json", "{}"),
+    ];
+
+    for (name, content) in files {
+        std::fs::write(path.join(name), content).unwrap();
+    }
+}
+
+fn gzip(data: &str) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data.as_bytes()).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Serves the given files over http until the test ends.
+fn serve(files: HashMap<String, Vec<u8>>) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut request_line = String::new();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            reader.read_line(&mut request_line).unwrap();
+
+            loop {
+                let mut header = String::new();
+                if reader.read_line(&mut header).unwrap() == 0 || header == "\r\n" {
+                    break;
+                }
+            }
+
+            let path = request_line.split_whitespace().nth(1).unwrap_or_default();
+            let (status, body) = match files.get(path.trim_start_matches('/')) {
+                Some(body) => ("200 OK", body.clone()),
+                None => ("404 Not Found", Vec::new()),
+            };
+
+            let header = format!("HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, body.len());
+            stream.write_all(header.as_bytes()).unwrap();
+            stream.write_all(&body).unwrap();
+        }
+    });
+
+    format!("http://{}", address)
+}
+
+fn serve_package(version: &str, npc: &[u8], sha256: String) -> String {
+    let manifest = DataManifest {
+        version: version.to_string(),
+        files: vec![DataManifestFile {
+            name: "Npc.json".to_string(),
+            path: "Npc.json.gz".to_string(),
+            sha256,
+        }],
+    };
+
+    let files = HashMap::from([
+        ("manifest.json".to_string(), serde_json::to_vec(&manifest).unwrap()),
+        ("Npc.json.gz".to_string(), npc.to_vec()),
+    ]);
+
+    serve(files)
+}
+
+#[tokio::test]
+async fn should_install_package_next_to_bundled_data() {
+    let root = temp_dir("install");
+    let bundled = root.join("meter-data");
+    let packages = root.join("meter-data-packages");
+    std::fs::create_dir_all(&bundled).unwrap();
+    create_bundled(&bundled);
+
+    let npc = gzip(NPC);
+    let sha256 = format!("{:x}", Sha256::digest(&npc));
+    let url = serve_package("2", &npc, sha256);
+
+    let updater = DataPackageUpdater::new(HttpDataPackageApi::new(url), packages.clone(), bundled.clone());
+    assert_eq!(updater.installed_version().as_deref(), Some("1"));
+    assert!(current_package(&packages).is_none());
+
+    let version = updater.update().await.unwrap();
+    assert_eq!(version.as_deref(), Some("2"));
+    assert_eq!(updater.installed_version().as_deref(), Some("2"));
+
+    let package = packages.join("2");
+    assert_eq!(current_package(&packages), Some(package.clone()));
+    assert_eq!(std::fs::read_to_string(package.join("Npc.json")).unwrap(), NPC);
+    assert!(package.join("Skill.json").exists());
+    assert!(!packages.join("2.staging").exists());
+
+    assert!(updater.update().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn should_keep_bundled_data_on_checksum_mismatch() {
+    let root = temp_dir("checksum");
+    let bundled = root.join("meter-data");
+    let packages = root.join("meter-data-packages");
+    std::fs::create_dir_all(&bundled).unwrap();
+    create_bundled(&bundled);
+
+    let url = serve_package("2", &gzip(NPC), "0".repeat(64));
+
+    let updater = DataPackageUpdater::new(HttpDataPackageApi::new(url), packages.clone(), bundled.clone());
+    let err = updater.update().await.unwrap_err();
+
+    assert!(format!("{:#}", err).contains("checksum mismatch"));
+    assert!(current_package(&packages).is_none());
+    assert!(!packages.join("2").exists());
+    assert!(!packages.join("2.staging").exists());
+    assert_eq!(updater.installed_version().as_deref(), Some("1"));
+}
+
+#[tokio::test]
+async fn should_reject_versions_which_are_not_folder_names() {
+    let root = temp_dir("invalid_version");
+    let bundled = root.join("meter-data");
+    let packages = root.join("meter-data-packages");
+    std::fs::create_dir_all(&bundled).unwrap();
+    create_bundled(&bundled);
+
+    let npc = gzip(NPC);
+    let sha256 = format!("{:x}", Sha256::digest(&npc));
+    let url = serve_package("../2", &npc, sha256);
+
+    let updater = DataPackageUpdater::new(HttpDataPackageApi::new(url), packages.clone(), bundled.clone());
+    let err = updater.update().await.unwrap_err();
+
+    assert!(format!("{:#}", err).contains("invalid version"));
+    assert!(!packages.exists());
+}
+
+#[tokio::test]
+async fn should_remove_replaced_packages_on_start() {
+    let root = temp_dir("cleanup");
+    let bundled = root.join("meter-data");
+    let packages = root.join("meter-data-packages");
+    std::fs::create_dir_all(&bundled).unwrap();
+    create_bundled(&bundled);
+
+    let npc = gzip(NPC);
+    let sha256 = format!("{:x}", Sha256::digest(&npc));
+    let url = serve_package("2", &npc, sha256);
+
+    let updater = DataPackageUpdater::new(HttpDataPackageApi::new(url), packages.clone(), bundled.clone());
+    updater.update().await.unwrap();
+
+    // a package replaced during the last run and an interrupted download
+    std::fs::create_dir_all(packages.join("1.5")).unwrap();
+    std::fs::create_dir_all(packages.join("3.staging")).unwrap();
+
+    let package = prepare_packages(&packages, &bundled).unwrap();
+
+    assert_eq!(package, Some(packages.join("2")));
+    assert!(!packages.join("1.5").exists());
+    assert!(!packages.join("3.staging").exists());
+    assert_eq!(updater.installed_version().as_deref(), Some("2"));
+}
+
+#[tokio::test]
+async fn should_drop_package_when_bundled_data_is_updated() {
+    let root = temp_dir("bundled_update");
+    let bundled = root.join("meter-data");
+    let packages = root.join("meter-data-packages");
+    std::fs::create_dir_all(&bundled).unwrap();
+    create_bundled(&bundled);
+
+    let npc = gzip(NPC);
+    let sha256 = format!("{:x}", Sha256::digest(&npc));
+    let url = serve_package("2", &npc, sha256);
+
+    let updater = DataPackageUpdater::new(HttpDataPackageApi::new(url), packages.clone(), bundled.clone());
+    updater.update().await.unwrap();
+
+    // an app update ships newer bundled data than the published package
+    std::fs::write(bundled.join("version.json"), r#"{ "version": "3" }"#).unwrap();
+
+    assert!(prepare_packages(&packages, &bundled).unwrap().is_none());
+    assert!(current_package(&packages).is_none());
+    assert!(!packages.join("2").exists());
+    assert_eq!(updater.installed_version().as_deref(), Some("3"));
+    assert!(updater.update().await.unwrap().is_none());
+}
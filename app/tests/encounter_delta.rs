@@ -0,0 +1,150 @@
+#![cfg(feature = "meter-core-fake")]
+
+use hashbrown::HashMap;
+use sayafushi_lib::{
+    live::{delta::DeltaTracker, listener::{UpdateLevel, WindowSubscriptions}},
+    models::*,
+};
+
+fn player(name: &str, damage_dealt: i64) -> EncounterEntity {
+    let skills: HashMap<u32, Skill> = (1..=3)
+        .map(|id| (id, Skill { id, total_damage: damage_dealt / 3, hits: 1, casts: 1, ..Default::default() }))
+        .collect();
+
+    EncounterEntity {
+        name: name.to_string(),
+        entity_type: EntityType::Player,
+        class_id: 102,
+        skills,
+        damage_stats: DamageStats {
+            damage_dealt,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+fn encounter(entities: Vec<EncounterEntity>) -> Encounter {
+    Encounter {
+        fight_start: 1000,
+        entities: entities.into_iter().map(|e| (e.name.clone(), e)).collect(),
+        ..Default::default()
+    }
+}
+
+fn hit(encounter: &mut Encounter, name: &str, skill_id: u32, damage: i64) {
+    let entity = encounter.entities.get_mut(name).unwrap();
+    let skill = entity.skills.get_mut(&skill_id).unwrap();
+
+    skill.total_damage += damage;
+    skill.hits += 1;
+    entity.damage_stats.damage_dealt += damage;
+    entity.skill_stats.hits += 1;
+}
+
+#[test]
+fn should_send_only_changed_entities_and_skills() {
+    let mut tracker = DeltaTracker::new();
+    let mut encounter = encounter(vec![player("Berserker", 900), player("Bard", 300)]);
+
+    assert!(tracker.needs_snapshot(&encounter));
+    tracker.snapshot(&encounter);
+    assert!(!tracker.needs_snapshot(&encounter));
+
+    let delta = tracker.delta(&encounter);
+    assert!(delta.is_empty());
+    assert_eq!(delta.sequence, 0);
+
+    hit(&mut encounter, "Berserker", 2, 500);
+
+    let delta = tracker.delta(&encounter);
+    assert_eq!(delta.sequence, 1);
+    assert_eq!(delta.entities.len(), 1);
+
+    let entity = &delta.entities[0];
+    assert_eq!(entity.name, "Berserker");
+    assert_eq!(entity.damage_stats.damage_dealt, 1400);
+    assert_eq!(entity.skills.keys().copied().collect::<Vec<_>>(), vec![2]);
+    assert_eq!(entity.skills[&2].total_damage, 800);
+
+    assert!(tracker.delta(&encounter).is_empty());
+}
+
+#[test]
+fn should_send_new_and_removed_entities() {
+    let mut tracker = DeltaTracker::new();
+    let mut encounter = encounter(vec![player("Berserker", 900)]);
+
+    tracker.snapshot(&encounter);
+
+    encounter.entities.insert("Bard".to_string(), player("Bard", 300));
+    encounter.entities.insert("Npc".to_string(), EncounterEntity {
+        name: "Npc".to_string(),
+        entity_type: EntityType::Npc,
+        ..player("Npc", 100)
+    });
+
+    let delta = tracker.delta(&encounter);
+    assert_eq!(delta.entities.len(), 1);
+    assert_eq!(delta.entities[0].name, "Bard");
+    assert_eq!(delta.entities[0].skills.len(), 3);
+
+    encounter.entities.remove("Berserker");
+
+    let delta = tracker.delta(&encounter);
+    assert_eq!(delta.sequence, 2);
+    assert!(delta.entities.is_empty());
+    assert_eq!(delta.removed, vec!["Berserker".to_string()]);
+}
+
+#[test]
+fn should_require_snapshot_for_new_encounter() {
+    let mut tracker = DeltaTracker::new();
+    let mut encounter = encounter(vec![player("Berserker", 900)]);
+
+    tracker.snapshot(&encounter);
+    encounter.fight_start = 2000;
+    assert!(tracker.needs_snapshot(&encounter));
+
+    tracker.snapshot(&encounter);
+    tracker.invalidate();
+    assert!(tracker.needs_snapshot(&encounter));
+}
+
+#[test]
+fn should_summarize_top_line_dps() {
+    let mut encounter = encounter(vec![player("Bard", 300), player("Berserker", 900)]);
+    encounter.last_combat_packet = 11_000;
+    encounter.entities.get_mut("Berserker").unwrap().damage_stats.buffed_by_support = 450;
+
+    let summary = EncounterSummary::new(&encounter);
+
+    assert_eq!(summary.total_damage_dealt, 1200);
+    assert_eq!(summary.dps, 120);
+    assert_eq!(summary.players[0].name, "Berserker");
+    assert_eq!(summary.players[0].dps, 90);
+    assert_eq!(summary.players[0].damage_share, 0.75);
+    assert_eq!(summary.players[0].support_buff, 0.5);
+    assert!(summary.current_boss.is_none());
+    assert_eq!(summary.players[1].name, "Bard");
+}
+
+#[test]
+fn should_track_window_subscriptions() {
+    let subscriptions = WindowSubscriptions::new();
+
+    assert_eq!(subscriptions.windows(UpdateLevel::Full), vec!["main".to_string()]);
+    assert_eq!(subscriptions.windows(UpdateLevel::Summary), vec!["mini".to_string()]);
+
+    subscriptions.set("mini", UpdateLevel::Full);
+
+    assert_eq!(subscriptions.windows(UpdateLevel::Full).len(), 2);
+    assert!(subscriptions.windows(UpdateLevel::Summary).is_empty());
+
+    subscriptions.set("mini", UpdateLevel::Summary);
+
+    subscriptions.set("main", UpdateLevel::Delta);
+
+    assert!(subscriptions.windows(UpdateLevel::Full).is_empty());
+    assert_eq!(subscriptions.windows(UpdateLevel::Delta), vec!["main".to_string()]);
+}
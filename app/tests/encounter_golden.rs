@@ -0,0 +1,310 @@
+#![cfg(feature = "meter-core-fake")]
+
+use std::{fs, path::{Path, PathBuf}, sync::{Arc, OnceLock}};
+
+use sayafushi_lib::{
+    abstractions::{DefaultDamageEncryptionHandler, FakeRegionAccessor, MemoryEventEmitter, PacketReceiver, PacketScript, PacketSource, Pkt, ReplayPacketSource, ReplaySpeed, ScriptedNpc, ScriptedPlayer, ScriptedRaidBuilder},
+    api::FakeStatsApi,
+    data::AssetPreloader,
+    database::Database,
+    live::{delta::should_send_entity, session::{LiveSession, LiveSessionArgs}},
+    local::LocalPlayerRepository,
+    models::*,
+};
+use serde_json::Value;
+use std::time::Duration;
+
+/// Keys which depend on wall clock time rather than on the packets.
+const VOLATILE_KEYS: &[&str] = &[
+    "fightStart",
+    "lastCombatPacket",
+    "duration",
+    "dps",
+    "dpsAverage",
+    "dpsRolling10sAvg",
+    "deathTime",
+    "bossHpLog",
+    "castLog",
+    "skillCastLog",
+    "timestamp",
+    "ntpFightStart",
+    "region",
+    // phase boundaries, relative to the fight start
+    "start",
+    "end",
+];
+
+/// Loads `tests/meter-data`, which only has the skills, buffs and npcs used by the scripted raids.
+fn require_assets() {
+    static LOADED: OnceLock<bool> = OnceLock::new();
+
+    let loaded = *LOADED.get_or_init(|| AssetPreloader::new(tests_dir().join("meter-data")).wait().is_ok());
+    assert!(loaded, "could not load tests/meter-data");
+}
+
+fn tests_dir() -> PathBuf {
+    std::env::current_dir().unwrap().join("tests")
+}
+
+fn create_session(name: &str) -> LiveSession<DefaultDamageEncryptionHandler> {
+    let current_dir = std::env::current_dir().unwrap();
+    let version = "0.0.1";
+
+    let database = Database::memory(
+        current_dir.join(format!("{}.db", name)),
+        &current_dir.join("migrations"),
+        version,
+    ).unwrap();
+
+    let local_path = std::env::temp_dir().join(format!("sayafushi_{}_local_players.json", name));
+    let _ = fs::remove_file(&local_path);
+    let local = LocalPlayerRepository::new(local_path).unwrap();
+
+    LiveSession::new(LiveSessionArgs {
+        version: version.to_string(),
+        emitter: Arc::new(MemoryEventEmitter::new()),
+        repository: Arc::new(database.create_repository()),
+        stats_api: Arc::new(FakeStatsApi::new()),
+        local,
+        region_accessor: Box::new(FakeRegionAccessor::new("EUC".into())),
+        damage_handler: DefaultDamageEncryptionHandler::new(),
+    }).unwrap()
+}
+
+/// Mirrors the filtering done before an `encounter-update` is sent to the meter.
+fn snapshot(encounter: &Encounter) -> Value {
+    let mut encounter = encounter.clone();
+
+    encounter.entities.retain(|_, e| should_send_entity(e));
+    encounter.current_boss = None;
+
+    let mut value = serde_json::to_value(&encounter).unwrap();
+    strip_volatile(&mut value);
+    value
+}
+
+fn strip_volatile(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            map.retain(|key, _| !VOLATILE_KEYS.contains(&key.as_str()));
+            map.values_mut().for_each(strip_volatile);
+        },
+        Value::Array(items) => items.iter_mut().for_each(strip_volatile),
+        _ => {}
+    }
+}
+
+/// Runs packets through the session and returns one snapshot per saved encounter,
+/// followed by the encounter which was still in progress when the packets ran out.
+fn run_packets(name: &str, packets: impl IntoIterator<Item = (Pkt, Vec<u8>)>) -> Value {
+    let mut session = create_session(name);
+    let mut snapshots = Vec::new();
+
+    for (op, data) in packets {
+        session.handle(op, &data, false);
+
+        if session.is_resetting() {
+            snapshots.push(snapshot(session.encounter()));
+            session.finish_reset();
+        }
+    }
+
+    let encounter = session.encounter();
+
+    if encounter.entities.values().any(|e| e.damage_stats.damage_dealt > 0) {
+        snapshots.push(snapshot(encounter));
+    }
+
+    Value::Array(snapshots)
+}
+
+fn read_capture(path: &Path) -> Vec<(Pkt, Vec<u8>)> {
+    let source = ReplayPacketSource::new(path.to_path_buf(), ReplaySpeed::Unbounded);
+    let mut receiver = source.start(0).unwrap();
+    let mut packets = Vec::new();
+
+    while let Ok(packet) = receiver.recv() {
+        packets.push(packet);
+    }
+
+    packets
+}
+
+fn diff(expected: &Value, actual: &Value, path: String, differences: &mut Vec<String>) {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                match actual.get(key) {
+                    Some(other) => diff(value, other, format!("{}.{}", path, key), differences),
+                    None => differences.push(format!("{}.{}: missing", path, key)),
+                }
+            }
+
+            for key in actual.keys().filter(|key| !expected.contains_key(*key)) {
+                differences.push(format!("{}.{}: unexpected", path, key));
+            }
+        },
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => {
+            for (index, (value, other)) in expected.iter().zip(actual).enumerate() {
+                diff(value, other, format!("{}[{}]", path, index), differences);
+            }
+        },
+        (expected, actual) if expected != actual => {
+            differences.push(format!("{}: expected {} got {}", path, expected, actual));
+        },
+        _ => {}
+    }
+}
+
+/// Compares against `tests/golden/<name>.json`.
+/// Set `UPDATE_GOLDEN=1` to (re)write the golden file instead.
+fn assert_golden(name: &str, actual: &Value) {
+    let path = tests_dir().join("golden").join(format!("{}.json", name));
+
+    if std::env::var("UPDATE_GOLDEN").is_ok() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, serde_json::to_string_pretty(actual).unwrap()).unwrap();
+        eprintln!("wrote golden file {}", path.display());
+        return;
+    }
+
+    assert!(path.exists(), "missing golden file {}, run with UPDATE_GOLDEN=1 to create it", path.display());
+
+    let expected: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+    let mut differences = Vec::new();
+    diff(&expected, actual, "$".to_string(), &mut differences);
+
+    assert!(
+        differences.is_empty(),
+        "{} differs from golden file {}:\n{}",
+        name,
+        path.display(),
+        differences.join("\n"));
+}
+
+#[tokio::test]
+async fn should_match_golden_files_for_captures() {
+    require_assets();
+
+    let captures_dir = tests_dir().join("captures");
+
+    let mut captures: Vec<PathBuf> = fs::read_dir(&captures_dir)
+        .unwrap()
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "sypc"))
+        .collect();
+    captures.sort();
+
+    for capture in captures {
+        let name = capture.file_stem().unwrap().to_string_lossy().to_string();
+        let packets = read_capture(&capture);
+        let actual = run_packets(&name, packets);
+
+        assert_golden(&name, &actual);
+    }
+}
+
+#[tokio::test]
+async fn should_match_golden_file_for_scripted_raid() {
+    require_assets();
+
+    let packets = ScriptedRaidBuilder::demo()
+        .set_tick(Duration::ZERO)
+        .build()
+        .into_iter()
+        .map(|packet| (packet.op, packet.data));
+
+    let actual = run_packets("scripted_raid", packets);
+
+    assert_golden("scripted_raid", &actual);
+}
+
+#[tokio::test]
+async fn should_record_deaths_and_shields_from_script() {
+    require_assets();
+
+    let packets = ScriptedRaidBuilder::new()
+        .add_party(&[("Berserker", 102), ("Bard", 204)])
+        .add_phase(485800, 1_000_000)
+        .add_death(0, Duration::from_secs(5))
+        .add_shield(1, 0, 211606, 50_000, Duration::ZERO)
+        .set_duration(Duration::from_secs(10))
+        .set_cleared(false)
+        .build()
+        .into_iter()
+        .map(|packet| (packet.op, packet.data));
+
+    let mut session = create_session("scripted_deaths");
+
+    for (op, data) in packets {
+        session.handle(op, &data, false);
+    }
+
+    let encounter = session.encounter();
+    let berserker = encounter.entities.get("Berserker").unwrap();
+    let bard = encounter.entities.get("Bard").unwrap();
+
+    assert!(berserker.is_dead);
+    assert!(berserker.damage_stats.damage_dealt > 0);
+    assert!(bard.damage_stats.damage_dealt > 0);
+}
+
+#[tokio::test]
+async fn should_break_down_damage_taken_by_boss_skill() {
+    require_assets();
+
+    let player = ScriptedPlayer {
+        entity_id: 1000,
+        character_id: 5000,
+        name: "Berserker".to_string(),
+        class_id: 102,
+        gear_level: 1680.0,
+        hp: 500_000,
+        skills: vec![16_010],
+        crit_rate: 0.0,
+    };
+    let boss = ScriptedNpc {
+        entity_id: 9000,
+        npc_id: 485800,
+        level: 60,
+        hp: 1_000_000,
+    };
+    // the player as a damage target
+    let target = ScriptedNpc {
+        entity_id: player.entity_id,
+        npc_id: 0,
+        level: 60,
+        hp: player.hp,
+    };
+
+    let mut script = PacketScript::new();
+    script
+        .init_env(player.entity_id)
+        .init_pc(&player)
+        .new_npc(&boss, boss.hp)
+        .skill_damage(player.entity_id, 16_010, &boss, 1_000, boss.hp - 1_000, false)
+        .skill_damage(boss.entity_id, 48_580_010, &target, 100_000, 400_000, false)
+        .skill_damage(boss.entity_id, 48_580_020, &target, 50_000, 350_000, false)
+        .skill_damage(boss.entity_id, 48_580_010, &target, 350_000, 0, false)
+        .death(player.entity_id);
+
+    let mut session = create_session("scripted_damage_taken");
+
+    for packet in script.build() {
+        session.handle(packet.op, &packet.data, false);
+    }
+
+    let encounter = session.encounter();
+    let berserker = encounter.entities.get("Berserker").unwrap();
+    let taken = &berserker.damage_stats.damage_taken_by_skill;
+
+    assert_eq!(taken.len(), 2);
+    assert_eq!(taken[&48_580_010].damage, 450_000);
+    assert_eq!(taken[&48_580_010].hits, 2);
+    assert_eq!(taken[&48_580_010].deaths, 1);
+    assert_eq!(taken[&48_580_020].deaths, 0);
+
+    let boss = encounter.entities.values().find(|e| e.entity_type == EntityType::Boss).unwrap();
+    assert_eq!(boss.damage_stats.damage_dealt_by_skill[&48_580_010].deaths, 1);
+    assert_eq!(taken[&48_580_010].source, boss.name);
+}
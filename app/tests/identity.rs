@@ -0,0 +1,33 @@
+use sayafushi_lib::{live::utils::get_identity_stats, models::*};
+
+#[test]
+fn should_summarize_arcanist_cards() {
+    let log: IdentityLog = vec![
+        (0, (0, 10, 0)),
+        (2_000, (500, 10, 11)),
+        (4_000, (1000, 11, 0)),
+        (6_000, (1000, 11, 11)),
+    ];
+
+    let stats = get_identity_stats(202, &log, 0, 10_000).unwrap();
+    let stats: IdentityArcanist = serde_json::from_str(&stats).unwrap();
+
+    assert_eq!(stats.card_draws.get(&10), Some(&1));
+    assert_eq!(stats.card_draws.get(&11), Some(&2));
+    assert_eq!(stats.log[1], (2, (500, 10, 11)));
+    // 0 for 2s, 500 for 2s, 1000 for 6s
+    assert_eq!(stats.average, 700.0);
+}
+
+#[test]
+fn should_summarize_generic_gauge() {
+    let log: IdentityLog = vec![(1_000, (300, 0, 0)), (3_000, (0, 0, 0))];
+
+    let stats = get_identity_stats(102, &log, 1_000, 5_000).unwrap();
+    let stats: IdentityGeneric = serde_json::from_str(&stats).unwrap();
+
+    assert_eq!(stats.log, vec![(0, 300), (2, 0)]);
+    assert_eq!(stats.average, 150.0);
+
+    assert!(get_identity_stats(102, &Vec::new(), 1_000, 5_000).is_none());
+}
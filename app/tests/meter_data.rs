@@ -0,0 +1,36 @@
+use std::path::PathBuf;
+
+use sayafushi_lib::data::{AssetPreloader, MeterData};
+
+fn meter_data_dir(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("sayafushi_meter_data_{}", name));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    path
+}
+
+#[test]
+fn should_report_missing_file() {
+    let path = meter_data_dir("missing");
+
+    let err = AssetPreloader::new(path.clone()).wait().unwrap_err();
+    assert!(format!("{:#}", err).contains("GemSkillGroup.json"));
+
+    let err = MeterData::load(&path).err().unwrap();
+    assert!(format!("{:#}", err).starts_with("could not read"));
+}
+
+#[test]
+fn should_report_malformed_file() {
+    let path = meter_data_dir("malformed");
+    std::fs::write(path.join("version.json"), r#"{ "version": "2" }"#).unwrap();
+    std::fs::write(path.join("GemSkillGroup.json"), "{ \"1\": [").unwrap();
+
+    let preloader = AssetPreloader::new(path.clone());
+    assert!(preloader.wait().is_err());
+
+    let err = preloader.reload().unwrap_err();
+    let message = format!("{:#}", err);
+    assert!(message.starts_with("malformed"));
+    assert!(message.contains("GemSkillGroup.json"));
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "meter-core-fake")]
+
+use sayafushi_lib::{live::phase::{parse_gate, PhaseTracker}, models::*};
+
+const BOSS: &str = "Demon Beast Commander Valtan";
+
+#[test]
+fn should_split_phases_and_attribute_damage() {
+    let mut tracker = PhaseTracker::new();
+    let fight_start = 10_000;
+
+    // nothing to split before the fight started
+    tracker.split(PhaseReason::BattleStatus, BOSS, 10_000, fight_start);
+
+    tracker.on_damage(BOSS, "Berserker", 100, 11_000, fight_start);
+    tracker.on_boss_hp(BOSS, 500, 11_000, fight_start);
+    tracker.on_damage(BOSS, "Bard", 50, 12_000, fight_start);
+    tracker.on_boss_hp(BOSS, 0, 20_000, fight_start);
+    tracker.split(PhaseReason::BossSwap, "Ravaged Tyrant of Beasts", 21_000, fight_start);
+    tracker.on_damage("Ravaged Tyrant of Beasts", "Berserker", 300, 25_000, fight_start);
+
+    let phases = tracker.phases(fight_start, 40_000);
+
+    assert_eq!(phases.len(), 2);
+    assert_eq!(phases[0].reason, PhaseReason::Start);
+    assert_eq!((phases[0].start, phases[0].end), (1_000, 10_000));
+    assert_eq!(phases[0].total_damage_dealt, 150);
+    assert_eq!(phases[0].damage_dealt.get("Bard"), Some(&50));
+
+    // the empty hp threshold phase was replaced by the boss swap
+    assert_eq!(phases[1].reason, PhaseReason::BossSwap);
+    assert_eq!(phases[1].boss_name, "Ravaged Tyrant of Beasts");
+    assert_eq!((phases[1].start, phases[1].end), (10_000, 30_000));
+    assert_eq!(phases[1].damage_dealt.get("Berserker"), Some(&300));
+}
+
+#[test]
+fn should_parse_gate_names() {
+    assert_eq!(parse_gate("Act 2: Brelshaza G2"), Some(("Act 2: Brelshaza", 2)));
+    assert_eq!(parse_gate("Valtan G1"), Some(("Valtan", 1)));
+    assert_eq!(parse_gate("Valtan"), None);
+}
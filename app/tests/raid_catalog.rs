@@ -0,0 +1,64 @@
+use sayafushi_lib::models::*;
+
+fn load() -> RaidCatalog {
+    let json = std::fs::read_to_string("meter-data/RaidCatalog.json").unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn query(boss_name: &str, max_hp: i64) -> RaidQuery<'_> {
+    RaidQuery {
+        boss_name,
+        max_hp,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn should_find_gate_by_boss_name() {
+    let catalog = load();
+
+    let gate = catalog.find(&query("Demon Beast Commander Valtan", 1_000_000)).unwrap();
+    assert_eq!((gate.raid.as_str(), gate.gate.as_str()), ("Valtan", "Valtan G2"));
+    assert!(catalog.find(&query("Training Dummy", 1_000_000)).is_none());
+}
+
+#[test]
+fn should_tell_same_named_bosses_apart_by_hp() {
+    let catalog = load();
+    let boss = "Phantom Legion Commander Brelshaza";
+
+    assert_eq!(catalog.find(&query(boss, 50_000_000_000)).unwrap().gate, "Brelshaza G6");
+    assert_eq!(catalog.find(&query(boss, 200_000_000_000)).unwrap().gate, "Act 2: Brelshaza G2");
+}
+
+#[test]
+fn should_prefer_npc_id_zone_and_difficulty() {
+    let gate = |name: &str| RaidGate {
+        raid: "Raid".to_string(),
+        gate: name.to_string(),
+        bosses: vec!["Boss".to_string()],
+        ..Default::default()
+    };
+
+    let catalog = RaidCatalog {
+        gates: vec![
+            gate("Raid G1"),
+            RaidGate { zone_ids: vec![37001], ..gate("Raid G2") },
+            RaidGate { difficulties: vec!["Hard".to_string()], ..gate("Raid G3") },
+            RaidGate { npc_ids: vec![480005], bosses: Vec::new(), ..gate("Raid G4") },
+        ],
+    };
+
+    let mut query = query("Boss", 1_000_000);
+    assert_eq!(catalog.find(&query).unwrap().gate, "Raid G1");
+
+    query.zone_id = Some(37001);
+    assert_eq!(catalog.find(&query).unwrap().gate, "Raid G2");
+
+    query.zone_id = Some(1);
+    query.difficulty = "Hard";
+    assert_eq!(catalog.find(&query).unwrap().gate, "Raid G3");
+
+    query.npc_id = 480005;
+    assert_eq!(catalog.find(&query).unwrap().gate, "Raid G4");
+}
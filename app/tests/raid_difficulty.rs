@@ -0,0 +1,26 @@
+use sayafushi_lib::models::*;
+
+fn load() -> RaidDifficultyData {
+    let json = std::fs::read_to_string("meter-data/RaidDifficulty.json").unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+#[test]
+fn should_resolve_difficulty_by_raid_id() {
+    let data = load();
+
+    assert_eq!(data.by_raid(308226), Some(&RaidDifficulty { id: 7, name: "Trial".to_string() }));
+    assert_eq!(data.by_raid(308430).map(|d| d.name.as_str()), Some("Challenge"));
+    assert_eq!(data.by_raid(1), None);
+}
+
+#[test]
+fn should_prefer_zone_over_zone_level() {
+    let mut data = load();
+
+    assert_eq!(data.by_zone(37011, 1).map(|d| d.name.as_str()), Some("Hard"));
+    assert_eq!(data.by_zone(37011, 99), None);
+
+    data.zones.insert(37011, RaidDifficulty { id: 6, name: "Nightmare".to_string() });
+    assert_eq!(data.by_zone(37011, 1).map(|d| d.name.as_str()), Some("Nightmare"));
+}
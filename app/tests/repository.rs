@@ -1,6 +1,6 @@
 use chrono::{Local, Utc};
 use hashbrown::{HashMap, HashSet};
-use sayafushi_lib::{database::{models::{GetEncounterPreviewArgs, InsertEncounterArgs}, Database}, models::*};
+use sayafushi_lib::{database::{compute_raid_summary, models::{GetAttemptHistoryArgs, GetDeathRecapsArgs, GetEncounterPreviewArgs, GetRaidRunsArgs, InsertEncounterArgs}, Database}, models::*};
 use rand::{rngs::ThreadRng, Rng};
 
 fn test_db() -> String {
@@ -78,6 +78,8 @@ fn should_insert_encounter() {
     assert_eq!(actual_encounter.difficulty, expected_encounter.difficulty);
     assert_eq!(actual_encounter.cleared, expected_encounter.cleared);
     assert_eq!(actual_encounter.boss_only_damage, expected_encounter.boss_only_damage);
+    assert!(actual_encounter.raid_summary.is_some());
+    assert!(actual_encounter.encounter_damage_stats.misc.as_ref().is_some_and(|misc| misc.raid_summary.is_none()));
 
     let preview = paged.encounters.first().unwrap();
 
@@ -86,6 +88,369 @@ fn should_insert_encounter() {
     assert_eq!(preview.boss_name, expected_encounter.current_boss_name);
 }
 
+#[test]
+fn should_compute_raid_summary() {
+    let player = |name: &str, class_id: u32, damage_dealt: i64, buffed_by_support: i64, rdps_damage_given: i64| EncounterEntity {
+        name: name.to_string(),
+        entity_type: EntityType::Player,
+        class_id,
+        damage_stats: DamageStats {
+            damage_dealt,
+            buffed_by_support,
+            rdps_damage_given,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let entities = [
+        player("Berserker", 102, 600, 300, 0),
+        player("Sharpshooter", 502, 400, 400, 0),
+        player("Bard", 204, 100, 0, 250),
+        player("Aeromancer", 603, 900, 0, 0),
+    ];
+
+    let encounter = Encounter {
+        fight_start: 0,
+        last_combat_packet: 10_000,
+        entities: entities.into_iter().map(|e| (e.name.clone(), e)).collect(),
+        ..Default::default()
+    };
+
+    let party_info = vec![
+        vec!["Berserker".to_string(), "Sharpshooter".to_string(), "Bard".to_string()],
+        vec!["Aeromancer".to_string(), "Missing".to_string()],
+    ];
+
+    let summary = compute_raid_summary(&encounter, &party_info).unwrap();
+
+    assert_eq!(summary.total_damage_dealt, 2000);
+    assert_eq!(summary.dps, 200);
+    assert_eq!(summary.rdps_damage_given, 250);
+
+    let first = &summary.parties[0];
+    assert_eq!(first.damage_dealt, 1100);
+    assert_eq!(first.dps, 110);
+    assert_eq!(first.damage_share, 0.55);
+    assert_eq!(first.members.len(), 3);
+    assert_eq!(first.members[0].party_damage_share, 600.0 / 1100.0);
+
+    let support = first.support.as_ref().unwrap();
+    assert_eq!(support.name, "Bard");
+    assert_eq!(support.buff, 0.7);
+
+    let second = &summary.parties[1];
+    assert_eq!(second.party_id, 1);
+    assert_eq!(second.members.len(), 1);
+    assert_eq!(second.members[0].raid_damage_share, 0.45);
+    assert!(second.support.is_none());
+
+    assert!(compute_raid_summary(&encounter, &[]).is_none());
+}
+
+#[test]
+fn should_persist_stagger_log() {
+    let current_dir = std::env::current_dir().unwrap();
+    let database = Database::memory(
+        current_dir.join(test_db()),
+        &current_dir.join("migrations"),
+        "1.14.0"
+    ).unwrap();
+
+    let repository = database.create_repository();
+
+    let player = PlayerSpec { class_id: 102, class_name: "Berserker".to_string(), is_support: false, crit_rate: 0.25, gear_score: 1620.0, hp: 1_000_000 };
+    let support = PlayerSpec { class_id: 204, class_name: "Bard".to_string(), is_support: true, crit_rate: 0.15, gear_score: 1500.0, hp: 1_000_000 };
+
+    let mut args = RaidBuilder::new()
+        .add_party((player.clone(), player.clone(), player, support))
+        .set_boss("Mordum, the Abyssal Punisher", 485800, 1_100_000_000_000, 15)
+        .set_version("1.14.0")
+        .build();
+
+    let stagger = BossStagger {
+        max_stagger: 1000,
+        average_time_to_stagger: 12.0,
+        log: vec![StaggerLog { time: 5, p: 40.0 }, StaggerLog { time: 17, p: 100.0 }],
+        intervals: vec![StaggerInterval { time: 17, duration: 12, max: 1000 }],
+        contributions: HashMap::from([("Player1".to_string(), 1000)]),
+    };
+
+    args.encounter.encounter_damage_stats.stagger_stats =
+        HashMap::from([("Mordum, the Abyssal Punisher".to_string(), stagger.clone())]);
+
+    let id = repository.insert_data(args).unwrap();
+    let encounter = repository.get_encounter(id.to_string()).unwrap();
+
+    assert_eq!(
+        encounter.encounter_damage_stats.stagger_stats.get("Mordum, the Abyssal Punisher"),
+        Some(&stagger)
+    );
+}
+
+#[test]
+fn should_link_gate_encounters() {
+    let current_dir = std::env::current_dir().unwrap();
+    let database = Database::memory(
+        current_dir.join(test_db()),
+        &current_dir.join("migrations"),
+        "1.14.0"
+    ).unwrap();
+
+    let repository = database.create_repository();
+
+    let player = PlayerSpec { class_id: 102, class_name: "Berserker".to_string(), is_support: false, crit_rate: 0.25, gear_score: 1620.0, hp: 1_000_000 };
+    let support = PlayerSpec { class_id: 204, class_name: "Bard".to_string(), is_support: true, crit_rate: 0.15, gear_score: 1500.0, hp: 1_000_000 };
+
+    let gate = |boss: &str, npc_id: u32, gate: &str, duration_minutes: i64| {
+        let mut args = RaidBuilder::new()
+            .add_party((player.clone(), player.clone(), player.clone(), support.clone()))
+            .set_boss(boss, npc_id, 1_000_000_000, duration_minutes)
+            .set_version("1.14.0")
+            .build();
+        args.gate = Some(gate.to_string());
+        args
+    };
+
+    let gate1_id = repository.insert_data(gate("Dark Mountain Predator", 480005, "Valtan G1", 5)).unwrap();
+
+    // a wipe and the clear of the next gate both point to the first gate
+    let wipe_id = repository.insert_data(gate("Demon Beast Commander Valtan", 480009, "Valtan G2", 3)).unwrap();
+    let mut gate2 = gate("Demon Beast Commander Valtan", 480009, "Valtan G2", 8);
+    gate2.encounter.encounter_damage_stats.phases = vec![
+        EncounterPhase {
+            boss_name: "Demon Beast Commander Valtan".to_string(),
+            start: 0,
+            end: 120_000,
+            total_damage_dealt: 1000,
+            damage_dealt: HashMap::from([("Player1".to_string(), 1000)]),
+            ..Default::default()
+        },
+        EncounterPhase {
+            boss_name: "Ravaged Tyrant of Beasts".to_string(),
+            reason: PhaseReason::BossSwap,
+            start: 120_000,
+            end: 480_000,
+            total_damage_dealt: 2000,
+            damage_dealt: HashMap::from([("Player1".to_string(), 2000)]),
+        },
+    ];
+    let phases = gate2.encounter.encounter_damage_stats.phases.clone();
+    let gate2_id = repository.insert_data(gate2).unwrap();
+
+    let gate1 = repository.get_encounter(gate1_id.to_string()).unwrap();
+    let gate2 = repository.get_encounter(gate2_id.to_string()).unwrap();
+
+    assert_eq!(gate1.parent_id, None);
+    assert_eq!(gate1.child_ids, vec![wipe_id, gate2_id]);
+    assert_eq!(gate2.parent_id, Some(gate1_id));
+    assert!(gate2.child_ids.is_empty());
+    assert_eq!(gate2.encounter_damage_stats.phases, phases);
+
+    repository.delete_encounter(gate1_id.to_string()).unwrap();
+
+    let gate2 = repository.get_encounter(gate2_id.to_string()).unwrap();
+    assert_eq!(gate2.parent_id, None);
+}
+
+#[test]
+fn should_group_gates_into_raid_runs() {
+    let current_dir = std::env::current_dir().unwrap();
+    let database = Database::memory(
+        current_dir.join(test_db()),
+        &current_dir.join("migrations"),
+        "1.14.0"
+    ).unwrap();
+
+    let repository = database.create_repository();
+
+    let player = PlayerSpec { class_id: 102, class_name: "Berserker".to_string(), is_support: false, crit_rate: 0.25, gear_score: 1620.0, hp: 1_000_000 };
+    let support = PlayerSpec { class_id: 204, class_name: "Bard".to_string(), is_support: true, crit_rate: 0.15, gear_score: 1500.0, hp: 1_000_000 };
+
+    let gate = |boss: &str, gate: &str, difficulty: &str, duration_minutes: i64, cleared: bool| {
+        let mut args = RaidBuilder::new()
+            .add_party((player.clone(), player.clone(), player.clone(), support.clone()))
+            .set_boss(boss, 480005, 1_000_000_000, duration_minutes)
+            .set_difficulty(difficulty)
+            .set_version("1.14.0")
+            .build();
+        args.gate = Some(gate.to_string());
+        args.raid_clear = cleared;
+        args
+    };
+
+    let gate1_id = repository.insert_data(gate("Dark Mountain Predator", "Valtan G1", "Hard", 5, true)).unwrap();
+    let wipe_id = repository.insert_data(gate("Demon Beast Commander Valtan", "Valtan G2", "Hard", 3, false)).unwrap();
+    let gate2_id = repository.insert_data(gate("Demon Beast Commander Valtan", "Valtan G2", "Hard", 8, true)).unwrap();
+    repository.insert_data(gate("Dark Mountain Predator", "Valtan G1", "Normal", 4, true)).unwrap();
+
+    let overview = repository.get_raid_runs(GetRaidRunsArgs { page: 1, page_size: 10 }).unwrap();
+    assert_eq!(overview.total_runs, 2);
+
+    let run = overview.runs.iter().find(|run| run.difficulty.as_deref() == Some("Hard")).unwrap();
+    assert_eq!(run.raid, "Valtan");
+    assert_eq!(run.clear_time, 13 * 60 * 1000);
+    assert_eq!(run.gates.len(), 2);
+    assert_eq!(run.gates[0].encounter_ids, vec![gate1_id]);
+    assert_eq!(run.gates[1].gate, "Valtan G2");
+    assert_eq!((run.gates[1].attempts, run.gates[1].wipes), (2, 1));
+    assert_eq!(run.gates[1].encounter_ids, vec![wipe_id, gate2_id]);
+    assert!(run.gates[1].cleared);
+
+    assert_eq!(run.players.len(), 4);
+    assert!(run.players.iter().all(|player| player.damage_dealt > 0 && player.dps > 0));
+}
+
+#[test]
+fn should_number_attempts_and_keep_wipes() {
+    let current_dir = std::env::current_dir().unwrap();
+    let database = Database::memory(
+        current_dir.join(test_db()),
+        &current_dir.join("migrations"),
+        "1.14.0"
+    ).unwrap();
+
+    let repository = database.create_repository();
+
+    let player = PlayerSpec { class_id: 102, class_name: "Berserker".to_string(), is_support: false, crit_rate: 0.25, gear_score: 1620.0, hp: 1_000_000 };
+    let support = PlayerSpec { class_id: 204, class_name: "Bard".to_string(), is_support: true, crit_rate: 0.15, gear_score: 1500.0, hp: 1_000_000 };
+
+    let attempt = |boss_hp: Option<f32>| {
+        let mut args = RaidBuilder::new()
+            .add_party((player.clone(), player.clone(), player.clone(), support.clone()))
+            .set_boss("Mordum, the Abyssal Punisher", 485800, 1_100_000_000_000, 10)
+            .set_version("1.14.0")
+            .build();
+        args.raid_clear = boss_hp.is_none();
+        args.encounter.wipe = boss_hp.map(|boss_hp| WipeInfo { boss_hp: Some(boss_hp) });
+        args.encounter.death_recaps = vec![DeathRecap {
+            time: 30_000,
+            name: "Player4".to_string(),
+            class_id: 204,
+            ..Default::default()
+        }];
+        args
+    };
+
+    let first_id = repository.insert_data(attempt(Some(0.4))).unwrap();
+    repository.insert_data(attempt(Some(0.2))).unwrap();
+    let clear_id = repository.insert_data(attempt(None)).unwrap();
+
+    let first = repository.get_encounter(first_id.to_string()).unwrap();
+    assert_eq!(first.attempt, Some(1));
+    assert_eq!(first.wipe.unwrap().boss_hp, Some(0.4));
+    assert_eq!(first.death_recaps[0].name, "Player4");
+
+    let history = repository.get_attempt_history(GetAttemptHistoryArgs {
+        boss_name: "Mordum, the Abyssal Punisher".to_string(),
+        difficulty: "Hard".to_string(),
+    }).unwrap();
+
+    assert_eq!(history.attempts.iter().map(|a| a.attempt).collect::<Vec<_>>(), vec![Some(1), Some(2), Some(3)]);
+    assert_eq!(history.attempts[1].boss_hp, Some(0.2));
+    assert!(!history.attempts[1].cleared);
+    assert_eq!(history.best_boss_hp, Some(0.0));
+    assert_eq!(history.first_clear, Some(clear_id));
+}
+
+#[test]
+fn should_keep_attempt_history_when_deleting_uncleared() {
+    let current_dir = std::env::current_dir().unwrap();
+    let database = Database::memory(
+        current_dir.join(test_db()),
+        &current_dir.join("migrations"),
+        "1.14.0"
+    ).unwrap();
+
+    let repository = database.create_repository();
+
+    let player = PlayerSpec { class_id: 102, class_name: "Berserker".to_string(), is_support: false, crit_rate: 0.25, gear_score: 1620.0, hp: 1_000_000 };
+    let support = PlayerSpec { class_id: 204, class_name: "Bard".to_string(), is_support: true, crit_rate: 0.15, gear_score: 1500.0, hp: 1_000_000 };
+
+    let attempt = |boss_name: &str, cleared: bool| {
+        let mut args = RaidBuilder::new()
+            .add_party((player.clone(), player.clone(), player.clone(), support.clone()))
+            .set_boss(boss_name, 485800, 1_100_000_000_000, 10)
+            .set_version("1.14.0")
+            .build();
+        args.raid_clear = cleared;
+        args
+    };
+
+    repository.insert_data(attempt("Mordum, the Abyssal Punisher", false)).unwrap();
+    repository.insert_data(attempt("Mordum, the Abyssal Punisher", true)).unwrap();
+    repository.insert_data(attempt("Armoche, Sentinel of the Abyss", false)).unwrap();
+    repository.insert_data(attempt("Armoche, Sentinel of the Abyss", false)).unwrap();
+
+    repository.delete_all_uncleared_encounters(false).unwrap();
+
+    let history = |boss_name: &str| repository.get_attempt_history(GetAttemptHistoryArgs {
+        boss_name: boss_name.to_string(),
+        difficulty: "Hard".to_string(),
+    }).unwrap();
+
+    assert_eq!(history("Mordum, the Abyssal Punisher").attempts.len(), 2);
+    assert!(history("Armoche, Sentinel of the Abyss").attempts.is_empty());
+
+    repository.insert_data(attempt("Armoche, Sentinel of the Abyss", false)).unwrap();
+    assert_eq!(history("Armoche, Sentinel of the Abyss").attempts[0].attempt, Some(1));
+}
+
+#[test]
+fn should_keep_death_recaps() {
+    let current_dir = std::env::current_dir().unwrap();
+    let database = Database::memory(
+        current_dir.join(test_db()),
+        &current_dir.join("migrations"),
+        "1.14.0"
+    ).unwrap();
+
+    let repository = database.create_repository();
+
+    let player = PlayerSpec { class_id: 102, class_name: "Berserker".to_string(), is_support: false, crit_rate: 0.25, gear_score: 1620.0, hp: 1_000_000 };
+    let support = PlayerSpec { class_id: 204, class_name: "Bard".to_string(), is_support: true, crit_rate: 0.15, gear_score: 1500.0, hp: 1_000_000 };
+
+    let mut args = RaidBuilder::new()
+        .add_party((player.clone(), player.clone(), player.clone(), support.clone()))
+        .set_boss("Mordum, the Abyssal Punisher", 485800, 1_100_000_000_000, 10)
+        .set_version("1.14.0")
+        .build();
+
+    let recap = |name: &str, time: i64| DeathRecap {
+        time,
+        name: name.to_string(),
+        class_id: 102,
+        hits: vec![DeathRecapHit {
+            time: time - 500,
+            source: "Mordum, the Abyssal Punisher".to_string(),
+            skill_id: 48_580_010,
+            skill_name: "Hammer".to_string(),
+            damage: 1_200_000,
+            shield_absorbed: 200_000,
+            current_hp: 0,
+        }],
+        status_effects: vec![DeathRecapStatusEffect {
+            id: 211606,
+            name: "Shield".to_string(),
+            remaining: Some(1_000),
+            ..Default::default()
+        }],
+    };
+    args.encounter.death_recaps = vec![recap("Player1", 30_000), recap("Player2", 45_000), recap("Player1", 90_000)];
+
+    let id = repository.insert_data(args).unwrap();
+
+    let encounter = repository.get_encounter(id.to_string()).unwrap();
+    assert_eq!(encounter.death_recaps.len(), 3);
+    assert_eq!(encounter.death_recaps[0], recap("Player1", 30_000));
+
+    let recaps = repository.get_death_recaps(GetDeathRecapsArgs {
+        encounter_id: id as i32,
+        name: Some("Player1".to_string()),
+    }).unwrap();
+
+    assert_eq!(recaps.iter().map(|recap| recap.time).collect::<Vec<_>>(), vec![30_000, 90_000]);
+}
 
 #[derive(Clone)]
 struct PlayerSpec {
@@ -208,6 +573,7 @@ impl RaidBuilder {
             rdps_message: None,
             ntp_fight_start: Some(fight_start),
             manual_save: None,
+            raid_summary: None,
         };
 
         let encounter_damage_stats = EncounterDamageStats {
@@ -224,6 +590,8 @@ impl RaidBuilder {
             unknown_buffs: HashSet::new(),
             misc: Some(misc.clone()),
             boss_hp_log: boss_hp_logs,
+            stagger_stats: HashMap::new(),
+            phases: Vec::new(),
         };
 
         let encounter = Encounter {
@@ -241,6 +609,12 @@ impl RaidBuilder {
             boss_only_damage: false,
             sync: None,
             region: Some(self.region.clone()),
+            raid_summary: None,
+            parent_id: None,
+            child_ids: Vec::new(),
+            attempt: None,
+            wipe: None,
+            death_recaps: Vec::new(),
         };
 
         let insert_args = InsertEncounterArgs {
@@ -259,6 +633,7 @@ impl RaidBuilder {
             manual: false,
             skill_cast_log: HashMap::new(),
             skill_cooldowns: HashMap::new(),
+            gate: None,
         };
 
         insert_args
@@ -285,6 +660,7 @@ impl RaidBuilder {
             ark_passive_active: None,
             ark_passive_data: None,
             spec: None,
+            spec_confidence: None,
             loadout_hash: None,
             combat_power: None,
         }
@@ -364,6 +740,7 @@ fn generate_entities_for_parties(
                 ark_passive_active: Some(!spec.is_support),
                 ark_passive_data: None,
                 spec: Some(spec.class_name.clone()),
+                spec_confidence: None,
                 loadout_hash: None,
                 combat_power: Some(2000.0),
             };
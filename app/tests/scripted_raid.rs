@@ -0,0 +1,29 @@
+#![cfg(feature = "meter-core-fake")]
+
+use std::time::Duration;
+
+use sayafushi_lib::abstractions::{Pkt, ScriptedPacket, ScriptedRaidBuilder};
+
+fn raid() -> ScriptedRaidBuilder {
+    ScriptedRaidBuilder::new()
+        .add_party(&[("Berserker", 102), ("Bard", 204)])
+        .add_phase(485800, 1_000_000)
+        .set_duration(Duration::from_secs(10))
+}
+
+fn kills(packets: &[ScriptedPacket]) -> usize {
+    packets.iter().filter(|packet| matches!(packet.op, Pkt::RaidBossKillNotify)).count()
+}
+
+#[test]
+fn should_clear_only_when_boss_is_killed() {
+    assert_eq!(kills(&raid().build()), 1);
+    assert_eq!(kills(&raid().set_cleared(false).build()), 0);
+
+    // nobody is left to finish the boss
+    let wipe = raid()
+        .add_death(0, Duration::ZERO)
+        .add_death(1, Duration::ZERO)
+        .build();
+    assert_eq!(kills(&wipe), 0);
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "meter-core-fake")]
+
+use hashbrown::HashMap;
+use sayafushi_lib::{live::utils::detect_spec, models::*};
+
+fn load() -> SpecRules {
+    let json = std::fs::read_to_string("meter-data/SpecRules.json").unwrap();
+    serde_json::from_str(&json).unwrap()
+}
+
+fn player(class: &str, skill_ids: &[u32]) -> EncounterEntity {
+    EncounterEntity {
+        name: "Player".to_string(),
+        entity_type: EntityType::Player,
+        class: class.to_string(),
+        skills: skill_ids.iter().map(|id| (*id, Skill { id: *id, ..Default::default() })).collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn should_detect_spec_from_skills() {
+    let rules = load();
+    let buffs = HashMap::new();
+
+    let berserker = player("Berserker", &[16140, 1, 2, 3, 4, 5, 6, 7]);
+    let spec = detect_spec(&rules, &berserker, &buffs, false);
+    assert_eq!(spec, SpecMatch { spec: "Berserker Technique".to_string(), confidence: 1.0 });
+
+    let berserker = player("Berserker", &[1, 2, 3, 4, 5, 6, 7, 8]);
+    let spec = detect_spec(&rules, &berserker, &buffs, false);
+    assert_eq!(spec, SpecMatch { spec: "Mayhem".to_string(), confidence: 0.5 });
+
+    let berserker = player("Berserker", &[16140]);
+    assert_eq!(detect_spec(&rules, &berserker, &buffs, false), SpecMatch::unknown());
+}
+
+#[test]
+fn should_weigh_ark_passive_and_buffs() {
+    let rules = load();
+
+    let mut bard = player("Bard", &[21147]);
+    bard.ark_passive_data = Some(ArkPassiveData {
+        enlightenment: Some(vec![ArkPassiveNode { id: 2210000, lv: 1 }]),
+        ..Default::default()
+    });
+
+    // 10 for the desperate salvation node against 1 for the true courage skill
+    let spec = detect_spec(&rules, &bard, &HashMap::new(), true);
+    assert_eq!(spec.spec, "Desperate Salvation");
+    assert!((spec.confidence - 10.0 / 11.0).abs() < 1e-6);
+
+    let mut reaper = player("Reaper", &[1]);
+    reaper.damage_stats.buffed_by.insert(1, 100);
+    let buffs = HashMap::from([(1, StatusEffect {
+        source: StatusEffectSource { name: "Lunar Voice".to_string(), ..Default::default() },
+        ..Default::default()
+    })]);
+
+    assert_eq!(detect_spec(&rules, &reaper, &buffs, true).spec, "Lunar Voice");
+}
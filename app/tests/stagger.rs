@@ -0,0 +1,43 @@
+#![cfg(feature = "meter-core-fake")]
+
+use sayafushi_lib::{live::stagger::StaggerTracker, models::*};
+
+const BOSS: &str = "Mordum, the Abyssal Punisher";
+
+#[test]
+fn should_record_stagger_timeline_and_intervals() {
+    let mut tracker = StaggerTracker::new();
+    let fight_start = 10_000;
+
+    tracker.on_hit(BOSS, "Berserker");
+    tracker.on_stagger_change(BOSS, 400, 1000, 15_000, fight_start);
+    tracker.on_hit(BOSS, "Bard");
+    tracker.on_stagger_change(BOSS, 700, 1000, 15_500, fight_start);
+    tracker.on_hit(BOSS, "Berserker");
+    tracker.on_stagger_change(BOSS, 1000, 1000, 27_000, fight_start);
+    tracker.on_stagger_change(BOSS, 0, 1000, 30_000, fight_start);
+
+    let stats = tracker.stats();
+    let stagger = stats.get(BOSS).unwrap();
+
+    assert_eq!(stagger.max_stagger, 1000);
+    assert_eq!(stagger.log, vec![
+        StaggerLog { time: 5, p: 70.0 },
+        StaggerLog { time: 17, p: 100.0 },
+        StaggerLog { time: 20, p: 0.0 },
+    ]);
+    assert_eq!(stagger.intervals, vec![StaggerInterval { time: 17, duration: 12, max: 1000 }]);
+    assert_eq!(stagger.average_time_to_stagger, 12.0);
+    assert_eq!(stagger.contributions.get("Berserker"), Some(&700));
+    assert_eq!(stagger.contributions.get("Bard"), Some(&300));
+}
+
+#[test]
+fn should_skip_bosses_without_stagger() {
+    let mut tracker = StaggerTracker::new();
+
+    tracker.on_hit(BOSS, "Berserker");
+    tracker.on_stagger_change("Npc", 10, 0, 15_000, 10_000);
+
+    assert!(tracker.stats().is_empty());
+}
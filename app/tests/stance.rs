@@ -0,0 +1,26 @@
+#![cfg(feature = "meter-core-fake")]
+
+use sayafushi_lib::live::utils::get_stance_stats;
+
+#[test]
+fn should_compute_stance_timeline_and_uptime() {
+    let log = vec![(0, 1), (12_000, 2), (15_000, 1)];
+
+    let stats = get_stance_stats(&log, 10_000, 20_000).unwrap();
+
+    assert_eq!(stats.timeline, vec![(0, 1), (2_000, 2), (5_000, 1)]);
+    // 2s + 5s in stance 1, 3s in stance 2
+    assert_eq!(stats.uptime.get(&1), Some(&0.7));
+    assert_eq!(stats.uptime.get(&2), Some(&0.3));
+}
+
+#[test]
+fn should_skip_stances_outside_of_fight() {
+    let log = vec![(1_000, 2), (5_000, 1), (30_000, 2)];
+
+    let stats = get_stance_stats(&log, 10_000, 20_000).unwrap();
+
+    assert_eq!(stats.timeline, vec![(0, 1)]);
+    assert_eq!(stats.uptime.get(&1), Some(&1.0));
+    assert!(get_stance_stats(&[], 10_000, 20_000).is_none());
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "meter-core-fake")]
+
+use sayafushi_lib::{
+    live::store::{
+        entity::{Entity, EntityKind, PlayerInfo, ProjectileInfo, SummonInfo},
+        ids::IdTracker,
+        party::PartyTracker,
+        status::{StatusEffectDetails, StatusEffectTargetType, StatusEffectType, StatusTracker},
+        StateStore,
+    },
+    models::EntityType,
+};
+
+fn player(id: u64, character_id: u64, name: &str, class_id: u32) -> Entity {
+    Entity::player(id, name.to_string(), PlayerInfo {
+        character_id,
+        class_id,
+        ..Default::default()
+    })
+}
+
+fn shield(instance_id: u32, target_id: u64, value: u64) -> StatusEffectDetails {
+    StatusEffectDetails {
+        instance_id,
+        target_id,
+        value,
+        target_type: StatusEffectTargetType::Local,
+        status_effect_type: StatusEffectType::Shield,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn should_resolve_entity_id_when_adding_party_member() {
+    let mut ids = IdTracker::new();
+    let mut parties = PartyTracker::new();
+
+    ids.add_mapping(5000, 1000);
+    parties.add(&ids, 1, 7, 5000, 0, None);
+
+    assert_eq!(parties.entity_id_to_party_id.get(&1000), Some(&7));
+    assert_eq!(parties.character_id_to_party_id.get(&5000), Some(&7));
+
+    parties.change_entity_id(1000, 1001);
+
+    assert_eq!(parties.entity_id_to_party_id.get(&1001), Some(&7));
+    assert!(!parties.entity_id_to_party_id.contains_key(&1000));
+}
+
+#[test]
+fn should_report_broken_shields_on_removal() {
+    let mut statuses = StatusTracker::new();
+
+    statuses.register_status_effect(shield(1, 1000, 5000));
+    statuses.register_status_effect(shield(2, 1000, 3000));
+
+    let (has_shield, broken, removed, _) =
+        statuses.remove_status_effects(1000, vec![1], 4, StatusEffectTargetType::Local);
+
+    assert!(has_shield);
+    assert_eq!(broken.len(), 1);
+    assert!(removed.is_empty());
+
+    let (has_shield, broken, removed, _) =
+        statuses.remove_status_effects(1000, vec![2], 0, StatusEffectTargetType::Local);
+
+    assert!(has_shield);
+    assert!(broken.is_empty());
+    assert_eq!(removed.len(), 1);
+}
+
+#[test]
+fn should_group_party_members_by_party_id() {
+    let mut store = StateStore::new();
+
+    let members = [
+        player(1000, 5000, "Berserker", 102),
+        player(1001, 5001, "Bard", 204),
+        player(1002, 5002, "Paladin", 105),
+    ];
+
+    for member in members {
+        store.ids.add_mapping(member.character_id, member.id);
+        store.entities.insert(member);
+    }
+
+    store.add_party_member(1, 2, 5002, 0, None);
+    store.add_party_member(1, 1, 5000, 0, None);
+    store.add_party_member(1, 1, 5001, 0, None);
+
+    let mut parties = store.party_members();
+    parties.iter_mut().for_each(|party| party.sort());
+
+    assert_eq!(parties, vec![
+        vec!["Bard".to_string(), "Berserker".to_string()],
+        vec!["Paladin".to_string()],
+    ]);
+}
+
+#[test]
+fn should_drop_status_effects_with_removed_object() {
+    let mut store = StateStore::new();
+
+    store.entities.insert(player(1000, 5000, "Berserker", 102));
+    store.statuses.register_status_effect(shield(1, 1000, 5000));
+    store.remove_object(1000);
+
+    let (has_shield, _, _, _) =
+        store.statuses.remove_status_effects(1000, vec![1], 4, StatusEffectTargetType::Local);
+
+    assert!(store.entities.get_entity_ref(1000).is_none());
+    assert!(!has_shield);
+}
+
+#[test]
+fn should_resolve_projectiles_and_summons_to_owner() {
+    let mut store = StateStore::new();
+
+    store.entities.insert(player(1000, 5000, "Berserker", 102));
+    store.entities.insert(Entity {
+        id: 2000,
+        kind: EntityKind::Projectile(ProjectileInfo { owner_id: 1000, skill_id: 16140, skill_effect_id: 0 }),
+        ..Default::default()
+    });
+    store.entities.insert(Entity {
+        id: 2001,
+        kind: EntityKind::Summon(SummonInfo { owner_id: 1000, ..Default::default() }),
+        ..Default::default()
+    });
+
+    assert_eq!(store.entities.get_source_entity(2000).id, 1000);
+    assert_eq!(store.entities.get_source_entity(2001).id, 1000);
+
+    let unknown = store.entities.get_source_entity(3000);
+
+    assert_eq!(unknown.entity_type(), EntityType::Unknown);
+    assert_eq!(unknown.owner_id(), None);
+}
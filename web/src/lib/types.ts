@@ -3,6 +3,11 @@ export interface EncounterEvent {
   payload: Encounter;
 }
 
+export interface EncounterSummaryEvent {
+  event: string;
+  payload: EncounterSummary;
+}
+
 export interface PartyEvent {
   event: string;
   payload?: string[][];
@@ -26,6 +31,39 @@ export interface Encounter {
   region?: string;
 }
 
+export interface EncounterSummary {
+  fightStart: number;
+  lastCombatPacket: number;
+  localPlayer: string;
+  currentBossName: string;
+  totalDamageDealt: number;
+  dps: number;
+  currentBoss: BossSummary | null;
+  players: Array<PlayerSummary>;
+}
+
+export interface BossSummary {
+  name: string;
+  currentHp: number;
+  maxHp: number;
+  currentShield: number;
+  isDead: boolean;
+}
+
+export interface PlayerSummary {
+  name: string;
+  classId: number;
+  class: string;
+  gearScore: number;
+  isDead: boolean;
+  damageDealt: number;
+  dps: number;
+  damageShare: number;
+  supportBuff: number;
+  supportBrand: number;
+  supportIdentity: number;
+}
+
 export interface EncountersOverview {
   encounters: Array<EncounterPreview>;
   totalEncounters: number;
@@ -285,7 +285,7 @@ export function removeUnknownHtmlTags(input: string) {
   return input;
 }
 
-export function formatPlayerName(player: Entity): string {
+export function formatPlayerName(player: Pick<Entity, "name" | "class" | "gearScore" | "isDead">): string {
   let playerName = player.name;
   const validName = isNameValid(playerName);
   if (!validName || !settings.app.general.showNames) {